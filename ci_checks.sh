@@ -27,6 +27,7 @@ cargo install --locked --debug --path ./forc &&
 cargo install --locked --debug --path ./forc-plugins/forc-fmt &&
 cargo install --locked --debug --path ./forc-plugins/forc-lsp &&
 cargo install --locked --debug --path ./forc-plugins/forc-client &&
+cargo install --locked --debug --path ./forc-plugins/forc-mutate &&
 cargo install --locked --debug --path ./forc-plugins/forc-tx &&
 cargo install --locked --debug forc-explore &&
 cargo install --locked --debug --path ./scripts/mdbook-forc-documenter &&
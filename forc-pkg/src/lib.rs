@@ -10,7 +10,8 @@ mod pkg;
 
 pub use lock::Lock;
 pub use manifest::{
-    BuildProfile, PackageManifest, PackageManifestFile, WorkspaceManifest, WorkspaceManifestFile,
+    BuildProfile, PackageManifest, PackageManifestFile, TestCoin, TestEnv, TestOutput,
+    WorkspaceManifest, WorkspaceManifestFile,
 };
 #[doc(inline)]
 pub use pkg::*;
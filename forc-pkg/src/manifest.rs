@@ -9,7 +9,9 @@ use std::{
     sync::Arc,
 };
 
-use sway_core::{fuel_prelude::fuel_tx, language::parsed::TreeType, parse_tree_type, BuildTarget};
+use sway_core::{
+    fuel_prelude::fuel_tx, language::parsed::TreeType, parse_tree_type, BuildTarget, OptLevel,
+};
 pub use sway_types::ConfigTimeConstant;
 use sway_utils::constants;
 
@@ -146,6 +148,8 @@ pub struct PackageManifest {
     pub build_target: Option<BTreeMap<String, BuildTarget>>,
     build_profile: Option<BTreeMap<String, BuildProfile>>,
     pub contract_dependencies: Option<BTreeMap<String, ContractDependency>>,
+    /// The environment in which the package's tests are executed.
+    pub test: Option<TestEnv>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -201,6 +205,47 @@ pub struct DependencyDetails {
     pub(crate) rev: Option<String>,
 }
 
+/// The environment in which tests are executed, as specified by the `[test]` table of a package
+/// manifest or the arguments of a `#[test]` attribute.
+///
+/// Unspecified fields fall back to the defaults used by `forc test`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TestEnv {
+    /// The height of the block that tests are executed within.
+    pub block_height: Option<u32>,
+    /// The owner of the test transaction's coin inputs, observed as the caller by `msg_sender()`.
+    pub caller: Option<fuel_tx::Address>,
+    /// The coin inputs of the test transaction, which may be forwarded to contract calls.
+    ///
+    /// For predicate tests, these are the coins owned by the predicate under test.
+    pub coins: Option<Vec<TestCoin>>,
+    /// The coin outputs of the test transaction.
+    pub outputs: Option<Vec<TestOutput>>,
+}
+
+/// A coin input of a test transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TestCoin {
+    pub amount: u64,
+    /// The asset of the coin, the base asset by default.
+    #[serde(default)]
+    pub asset_id: fuel_tx::AssetId,
+}
+
+/// A coin output of a test transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TestOutput {
+    /// The recipient of the coin.
+    pub to: fuel_tx::Address,
+    pub amount: u64,
+    /// The asset of the coin, the base asset by default.
+    #[serde(default)]
+    pub asset_id: fuel_tx::AssetId,
+}
+
 /// Parameters to pass through to the `sway_core::BuildConfig` during compilation.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "kebab-case")]
@@ -213,6 +258,20 @@ pub struct BuildProfile {
     pub terse: bool,
     pub time_phases: bool,
     pub include_tests: bool,
+    #[serde(default)]
+    pub optimization_level: OptLevel,
+}
+
+impl TestEnv {
+    /// Combine this environment with `overrides`, preferring the fields specified by `overrides`.
+    pub fn with_overrides(&self, overrides: &TestEnv) -> TestEnv {
+        TestEnv {
+            block_height: overrides.block_height.or(self.block_height),
+            caller: overrides.caller.or(self.caller),
+            coins: overrides.coins.clone().or_else(|| self.coins.clone()),
+            outputs: overrides.outputs.clone().or_else(|| self.outputs.clone()),
+        }
+    }
 }
 
 impl Dependency {
@@ -604,9 +663,13 @@ impl BuildProfile {
             terse: false,
             time_phases: false,
             include_tests: false,
+            optimization_level: OptLevel::Opt1,
         }
     }
 
+    /// The release profile uses the same optimization level as the debug profile, as it did before
+    /// optimization levels were introduced.  Level 2 trades bytecode size for speed, which is
+    /// not a safe default for contracts near the size limit, so it must be selected explicitly.
     pub fn release() -> Self {
         Self {
             print_ast: false,
@@ -617,6 +680,7 @@ impl BuildProfile {
             terse: false,
             time_phases: false,
             include_tests: false,
+            optimization_level: OptLevel::Opt1,
         }
     }
 }
@@ -2,7 +2,7 @@ use crate::{
     lock::Lock,
     manifest::{
         BuildProfile, ConfigTimeConstant, Dependency, ManifestFile, MemberManifestFiles,
-        PackageManifest, PackageManifestFile,
+        PackageManifest, PackageManifestFile, TestCoin, TestEnv,
     },
     CORE, PRELUDE, STD,
 };
@@ -37,7 +37,7 @@ use sway_core::{
     language::{
         lexed::LexedProgram,
         parsed::{ParseProgram, TreeType},
-        ty,
+        ty, Literal,
     },
     semantic_analysis::namespace,
     source_map::SourceMap,
@@ -96,9 +96,21 @@ pub struct BuiltPackage {
     pub bytecode: Vec<u8>,
     pub entries: Vec<PkgEntry>,
     pub tree_type: TreeType,
-    source_map: SourceMap,
+    pub source_map: SourceMap,
     pub pkg_name: String,
     pub built_pkg_descriptor: BuiltPackageDescriptor,
+    /// The contracts declared under `[contract-dependencies]`, as they were compiled when
+    /// determining their `CONTRACT_ID`s.
+    pub contract_dependencies: Vec<BuiltContractDependency>,
+}
+
+/// A compiled contract dependency of a package, along with the salt it is deployed with.
+#[derive(Debug, Clone)]
+pub struct BuiltContractDependency {
+    /// The name of the dependency as declared under `[contract-dependencies]`.
+    pub name: String,
+    pub salt: fuel_tx::Salt,
+    pub built: Box<BuiltPackage>,
 }
 
 /// The package descriptors that a `BuiltPackage` holds so that the source used for building the
@@ -123,12 +135,14 @@ pub struct PkgEntry {
 pub enum PkgEntryKind {
     Main,
     Test(PkgTestEntry),
+    Bench(PkgBenchEntry),
 }
 
 /// The possible conditions for a test result to be considered "passing".
 #[derive(Debug, Clone)]
 pub enum TestPassCondition {
-    ShouldRevert,
+    /// The test must revert, optionally with the given revert code.
+    ShouldRevert(Option<u64>),
     ShouldNotRevert,
 }
 
@@ -137,6 +151,34 @@ pub enum TestPassCondition {
 pub struct PkgTestEntry {
     pub pass_condition: TestPassCondition,
     pub span: Span,
+    /// The parameters of the test function if it is a fuzz test, i.e. `#[test(fuzz)]`.
+    pub fuzz_params: Option<FuzzParams>,
+    /// The environment specified by the test's attribute, overriding that of the package manifest.
+    pub env: TestEnv,
+    /// Whether the predicate under test is expected to accept the predicate data returned by the
+    /// test function, if it is a predicate test, i.e. `#[test(should_accept)]` or
+    /// `#[test(should_reject)]`.
+    pub predicate: Option<bool>,
+    /// Whether the test is skipped unless ignored tests are requested, i.e. `#[test(ignore)]`.
+    pub ignored: bool,
+    /// The names of the modules leading from the package's root module to the module declaring
+    /// the test, e.g. `["tests", "math"]`. Empty for tests declared within the root module.
+    pub module_path: Vec<String>,
+}
+
+/// Data specific to the benchmark entry point.
+#[derive(Debug, Clone)]
+pub struct PkgBenchEntry {
+    pub span: Span,
+}
+
+/// The parameters of a fuzz test function, described in terms of the JSON ABI.
+#[derive(Debug, Clone)]
+pub struct FuzzParams {
+    /// The type of each of the test function's parameters.
+    pub inputs: Vec<program_abi::TypeApplication>,
+    /// The declarations of all types referred to by `inputs`.
+    pub types: Vec<program_abi::TypeDeclaration>,
 }
 
 /// The result of successfully compiling a workspace.
@@ -2356,7 +2398,8 @@ pub fn sway_build_config(
     .print_finalized_asm(build_profile.print_finalized_asm)
     .print_intermediate_asm(build_profile.print_intermediate_asm)
     .print_ir(build_profile.print_ir)
-    .include_tests(build_profile.include_tests);
+    .include_tests(build_profile.include_tests)
+    .optimization_level(build_profile.optimization_level);
     Ok(build_config)
 }
 
@@ -2632,10 +2675,12 @@ pub fn compile(
         .as_ref()
         .map(|asm| asm.0.entries.clone())
         .unwrap_or_default();
-    let decl_engine = engines.de();
+    let test_module_paths = test_fn_module_paths(typed_program, engines.de());
     let entries = entries
         .iter()
-        .map(|finalized_entry| PkgEntry::from_finalized_entry(finalized_entry, decl_engine))
+        .map(|finalized_entry| {
+            PkgEntry::from_finalized_entry(finalized_entry, &test_module_paths, engines)
+        })
         .collect::<anyhow::Result<_>>()?;
     let bc_res = time_expr!(
         "compile asm to bytecode",
@@ -2679,6 +2724,7 @@ pub fn compile(
                 source_map: source_map.to_owned(),
                 pkg_name: pkg.name.clone(),
                 built_pkg_descriptor,
+                contract_dependencies: vec![],
             };
             Ok((built_package, namespace))
         }
@@ -2692,14 +2738,35 @@ impl PkgEntry {
         self.kind.test().is_some()
     }
 
+    /// Returns whether this `PkgEntry` corresponds to a benchmark.
+    pub fn is_bench(&self) -> bool {
+        self.kind.bench().is_some()
+    }
+
     fn from_finalized_entry(
         finalized_entry: &FinalizedEntry,
-        decl_engine: &DeclEngine,
+        test_module_paths: &HashMap<usize, Vec<String>>,
+        engines: Engines<'_>,
     ) -> Result<Self> {
         let pkg_entry_kind = match &finalized_entry.test_decl_id {
-            Some(test_decl_id) => {
-                let pkg_test_entry = PkgTestEntry::from_decl(test_decl_id.clone(), decl_engine)?;
-                PkgEntryKind::Test(pkg_test_entry)
+            Some(decl_id) => {
+                let span = decl_id.span();
+                if engines
+                    .de()
+                    .get_function(decl_id.clone(), &span)?
+                    .is_bench()
+                {
+                    let pkg_bench_entry = PkgBenchEntry::from_decl(decl_id.clone(), engines)?;
+                    PkgEntryKind::Bench(pkg_bench_entry)
+                } else {
+                    let module_path = test_module_paths
+                        .get(&**decl_id)
+                        .cloned()
+                        .unwrap_or_default();
+                    let pkg_test_entry =
+                        PkgTestEntry::from_decl(decl_id.clone(), module_path, engines)?;
+                    PkgEntryKind::Test(pkg_test_entry)
+                }
             }
             None => PkgEntryKind::Main,
         };
@@ -2719,37 +2786,234 @@ impl PkgEntryKind {
             _ => None,
         }
     }
+
+    /// Returns `Some` if the `PkgEntryKind` is `Bench`.
+    pub fn bench(&self) -> Option<&PkgBenchEntry> {
+        match self {
+            PkgEntryKind::Bench(bench) => Some(bench),
+            _ => None,
+        }
+    }
+}
+
+impl PkgBenchEntry {
+    fn from_decl(decl_id: DeclId, engines: Engines<'_>) -> Result<Self> {
+        let span = decl_id.span();
+        let bench_function_decl = engines.de().get_function(decl_id, &span)?;
+        let bench_name = &bench_function_decl.name;
+
+        if bench_function_decl.is_test() {
+            bail!("Function {bench_name} cannot be both a test and a benchmark.");
+        }
+        let has_args = bench_function_decl
+            .attributes
+            .get(&AttributeKind::Bench)
+            .expect("bench declaration is missing bench attribute")
+            .iter()
+            .any(|attr| !attr.args.is_empty());
+        if has_args {
+            bail!("Invalid bench argument(s) for benchmark: {bench_name}.");
+        }
+        if !bench_function_decl.parameters.is_empty() {
+            bail!("Benchmark {bench_name} must not declare any parameters.");
+        }
+
+        Ok(Self { span })
+    }
 }
 
 impl PkgTestEntry {
-    fn from_decl(decl_id: DeclId, decl_engine: &DeclEngine) -> Result<Self> {
+    fn from_decl(decl_id: DeclId, module_path: Vec<String>, engines: Engines<'_>) -> Result<Self> {
         let span = decl_id.span();
-        let test_function_decl = decl_engine.get_function(decl_id, &span)?;
+        let test_function_decl = engines.de().get_function(decl_id, &span)?;
+        let test_name = &test_function_decl.name;
 
-        let test_args: HashSet<String> = test_function_decl
+        let test_args = test_function_decl
             .attributes
             .get(&AttributeKind::Test)
             .expect("test declaration is missing test attribute")
             .iter()
-            .flat_map(|attr| attr.args.iter().map(|arg| arg.to_string()))
-            .collect();
+            .flat_map(|attr| attr.args.iter());
+
+        let mut pass_condition = TestPassCondition::ShouldNotRevert;
+        let mut fuzz = false;
+        let mut predicate = None;
+        let mut ignored = false;
+        let mut env = TestEnv::default();
+        let mut coins = None;
+        let mut asset_id = None;
+        for arg in test_args {
+            match arg.name.as_str() {
+                "should_revert" => {
+                    let revert_code = arg
+                        .value
+                        .as_ref()
+                        .map(revert_code_from_literal)
+                        .transpose()
+                        .with_context(|| {
+                            format!("Invalid `should_revert` code for test: {test_name}.")
+                        })?;
+                    pass_condition = TestPassCondition::ShouldRevert(revert_code);
+                }
+                "fuzz" if arg.value.is_none() => fuzz = true,
+                "ignore" if arg.value.is_none() => ignored = true,
+                "should_accept" | "should_reject" if arg.value.is_none() => {
+                    let accept = arg.name.as_str() == "should_accept";
+                    if predicate.map_or(false, |expected| expected != accept) {
+                        bail!("Test {test_name} cannot both `should_accept` and `should_reject`.");
+                    }
+                    predicate = Some(accept);
+                }
+                "block_height" | "caller" | "coins" | "asset_id" => {
+                    let value = arg.value.as_ref().ok_or_else(|| {
+                        anyhow!("Missing value for `{}` of test: {test_name}.", arg.name)
+                    })?;
+                    let invalid = || format!("Invalid `{}` for test: {test_name}.", arg.name);
+                    match arg.name.as_str() {
+                        "block_height" => {
+                            let height = integer_from_literal(value).with_context(invalid)?;
+                            env.block_height = Some(u32::try_from(height).with_context(invalid)?);
+                        }
+                        "caller" => {
+                            env.caller = Some(parse_from_literal(value).with_context(invalid)?)
+                        }
+                        "coins" => coins = Some(integer_from_literal(value).with_context(invalid)?),
+                        _ => asset_id = Some(parse_from_literal(value).with_context(invalid)?),
+                    }
+                }
+                _ => bail!("Invalid test argument(s) for test: {test_name}."),
+            }
+        }
+        env.coins = match (coins, asset_id) {
+            (Some(amount), asset_id) => Some(vec![TestCoin {
+                amount,
+                asset_id: asset_id.unwrap_or_default(),
+            }]),
+            (None, Some(_)) => {
+                bail!("Test {test_name} specifies an `asset_id` without any `coins`.")
+            }
+            (None, None) => None,
+        };
 
-        let pass_condition = if test_args.is_empty() {
-            anyhow::Ok(TestPassCondition::ShouldNotRevert)
-        } else if test_args.get("should_revert").is_some() {
-            anyhow::Ok(TestPassCondition::ShouldRevert)
-        } else {
-            let test_name = &test_function_decl.name;
-            bail!("Invalid test argument(s) for test: {test_name}.")
-        }?;
+        if predicate.is_some() {
+            if fuzz {
+                bail!("Predicate test {test_name} cannot be a fuzz test.");
+            }
+            if let TestPassCondition::ShouldRevert(_) = pass_condition {
+                bail!("Predicate test {test_name} cannot specify `should_revert`.");
+            }
+        }
+
+        let fuzz_params = match (fuzz, test_function_decl.parameters.is_empty()) {
+            (false, true) => None,
+            (false, false) => bail!(
+                "Test {test_name} declares parameters but is not a fuzz test. \
+                 Use `#[test(fuzz)]` to run it with generated inputs."
+            ),
+            (true, true) => bail!("Fuzz test {test_name} must declare at least one parameter."),
+            (true, false) => {
+                let mut types = vec![];
+                let abi_function =
+                    test_function_decl.generate_json_abi_function(engines.te(), &mut types);
+                Some(FuzzParams {
+                    inputs: abi_function.inputs,
+                    types,
+                })
+            }
+        };
 
         Ok(Self {
             pass_condition,
             span,
+            fuzz_params,
+            env,
+            predicate,
+            ignored,
+            module_path,
         })
     }
 }
 
+/// The path of the module declaring each test and benchmark function within the given program,
+/// relative to the package's root module, keyed by the index of the function's declaration.
+fn test_fn_module_paths(
+    program: &ty::TyProgram,
+    decl_engine: &DeclEngine,
+) -> HashMap<usize, Vec<String>> {
+    fn collect(
+        module: &ty::TyModule,
+        module_path: &mut Vec<String>,
+        decl_engine: &DeclEngine,
+        paths: &mut HashMap<usize, Vec<String>>,
+    ) {
+        for (_, decl_id) in module.test_fns(decl_engine) {
+            paths.insert(*decl_id, module_path.clone());
+        }
+        for (dep_name, submod) in &module.submodules {
+            module_path.push(dep_name.to_string());
+            collect(&submod.module, module_path, decl_engine, paths);
+            module_path.pop();
+        }
+    }
+    let mut paths = HashMap::new();
+    collect(&program.root, &mut vec![], decl_engine, &mut paths);
+    paths
+}
+
+/// The revert codes of the error signals declared within `std::error_signals`, which may be referred
+/// to by name within `#[test(should_revert = "...")]`.
+const STD_ERROR_SIGNALS: &[(&str, u64)] = &[
+    ("FAILED_REQUIRE_SIGNAL", 0xffff_ffff_ffff_0000),
+    ("FAILED_TRANSFER_TO_ADDRESS_SIGNAL", 0xffff_ffff_ffff_0001),
+    ("FAILED_SEND_MESSAGE_SIGNAL", 0xffff_ffff_ffff_0002),
+    ("FAILED_ASSERT_EQ_SIGNAL", 0xffff_ffff_ffff_0003),
+    ("FAILED_ASSERT_SIGNAL", 0xffff_ffff_ffff_0004),
+];
+
+/// Determine the expected revert code from the value of a `should_revert` test argument.
+///
+/// The value may be an integer literal, or a string containing a decimal or `0x` prefixed
+/// hexadecimal integer or the name of one of the error signals declared in `std::error_signals`.
+fn revert_code_from_literal(value: &Literal) -> Result<u64> {
+    match value {
+        Literal::String(code) => {
+            let code = code.as_str();
+            if let Some((_, signal)) = STD_ERROR_SIGNALS.iter().find(|(name, _)| *name == code) {
+                return Ok(*signal);
+            }
+            let code = code.replace('_', "");
+            match code.strip_prefix("0x") {
+                Some(hex) => Ok(u64::from_str_radix(hex, 16)?),
+                None => Ok(code.parse()?),
+            }
+        }
+        _ => integer_from_literal(value),
+    }
+}
+
+/// Determine the value of an integer literal test argument.
+fn integer_from_literal(value: &Literal) -> Result<u64> {
+    match value {
+        Literal::U8(value) => Ok(u64::from(*value)),
+        Literal::U16(value) => Ok(u64::from(*value)),
+        Literal::U32(value) => Ok(u64::from(*value)),
+        Literal::U64(value) | Literal::Numeric(value) => Ok(*value),
+        _ => bail!("expected an integer, found `{value}`"),
+    }
+}
+
+/// Parse the value of a string literal test argument, e.g. a `0x` prefixed address.
+fn parse_from_literal<T>(value: &Literal) -> Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match value {
+        Literal::String(s) => s.as_str().parse().map_err(|e| anyhow!("{e}")),
+        _ => bail!("expected a string, found `{value}`"),
+    }
+}
+
 /// The suffix that helps identify the file which contains the hash of the binary file created when
 /// scripts are built_package.
 pub const SWAY_BIN_HASH_SUFFIX: &str = "-bin-hash";
@@ -2961,6 +3225,14 @@ pub fn build(
     let decl_engine = DeclEngine::default();
     let engines = Engines::new(&type_engine, &decl_engine);
 
+    // Tests are only compiled for the requested outputs. Dependencies are always compiled without
+    // their tests so that the `CONTRACT_ID`s of contract dependencies do not depend on whether
+    // the tests of the dependent package are included.
+    let dep_profile = BuildProfile {
+        include_tests: false,
+        ..profile.clone()
+    };
+
     let mut lib_namespace_map = Default::default();
     let mut compiled_contract_deps = HashMap::new();
     for &node in plan
@@ -2996,16 +3268,33 @@ pub fn build(
                 bail!("Failed to compile {}", pkg.name);
             }
         };
+        let pkg_profile = if outputs.contains(&node) {
+            profile
+        } else {
+            &dep_profile
+        };
         let res = compile(
             pkg,
             manifest,
             target,
-            profile,
+            pkg_profile,
             dep_namespace,
             engines,
             &mut source_map,
         )?;
         let (mut built_package, namespace) = res;
+        built_package.contract_dependencies = plan
+            .graph()
+            .edges_directed(node, Direction::Outgoing)
+            .filter_map(|e| match e.weight().kind {
+                DepKind::Contract { salt } => Some(BuiltContractDependency {
+                    name: e.weight().name.clone(),
+                    salt,
+                    built: Box::new(compiled_contract_deps[&e.target()].clone()),
+                }),
+                DepKind::Library => None,
+            })
+            .collect();
         // If the current node is a contract dependency, collect the contract_id
         if plan
             .graph()
@@ -3383,6 +3672,27 @@ fn test_source_git_pinned_parsing() {
     }
 }
 
+#[test]
+fn test_std_error_signals_match_std() {
+    let current_dir = env!("CARGO_MANIFEST_DIR");
+    let error_signals_path = PathBuf::from(current_dir)
+        .parent()
+        .unwrap()
+        .join("sway-lib-std/src/error_signals.sw");
+    let error_signals = fs::read_to_string(error_signals_path).unwrap();
+    let declared: Vec<(&str, u64)> = error_signals
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("pub const "))
+        .map(|decl| {
+            let (name, value) = decl.split_once('=').unwrap();
+            let value = value.trim().trim_end_matches(';').replace('_', "");
+            let value = u64::from_str_radix(value.strip_prefix("0x").unwrap(), 16).unwrap();
+            (name.trim(), value)
+        })
+        .collect();
+    assert_eq!(declared, STD_ERROR_SIGNALS);
+}
+
 /// Format an error message for an absent `Forc.toml`.
 pub fn manifest_file_missing(dir: &Path) -> anyhow::Error {
     let message = format!(
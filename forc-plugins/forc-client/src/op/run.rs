@@ -7,7 +7,7 @@ use crate::{
 };
 use anyhow::{anyhow, bail, Context, Result};
 use forc_pkg::{self as pkg, fuel_core_not_running, PackageManifestFile};
-use forc_util::format_log_receipts;
+use forc_util::{abi_decode::LogDecoder, format_log_receipts};
 use fuel_core_client::client::FuelClient;
 use fuel_tx::{ContractId, Transaction, TransactionBuilder, UniqueIdentifier};
 use futures::TryFutureExt;
@@ -86,8 +86,15 @@ pub async fn run_pkg(
         info!("{:?}", tx);
         Ok(RanScript { receipts: vec![] })
     } else {
-        let receipts =
-            try_send_tx(node_url, &tx.into(), command.pretty_print, command.simulate).await?;
+        let log_decoder = LogDecoder::new(&compiled.json_abi_program)?;
+        let receipts = try_send_tx(
+            node_url,
+            &tx.into(),
+            command.pretty_print,
+            command.simulate,
+            &log_decoder,
+        )
+        .await?;
         Ok(RanScript { receipts })
     }
 }
@@ -97,13 +104,14 @@ async fn try_send_tx(
     tx: &Transaction,
     pretty_print: bool,
     simulate: bool,
+    log_decoder: &LogDecoder,
 ) -> Result<Vec<fuel_tx::Receipt>> {
     let client = FuelClient::new(node_url)?;
 
     match client.health().await {
         Ok(_) => timeout(
             Duration::from_millis(TX_SUBMIT_TIMEOUT_MS),
-            send_tx(&client, tx, pretty_print, simulate),
+            send_tx(&client, tx, pretty_print, simulate, log_decoder),
         )
         .await
         .with_context(|| format!("timeout waiting for {} to be included in a block", tx.id()))?,
@@ -116,6 +124,7 @@ async fn send_tx(
     tx: &Transaction,
     pretty_print: bool,
     simulate: bool,
+    log_decoder: &LogDecoder,
 ) -> Result<Vec<fuel_tx::Receipt>> {
     let id = format!("{:#x}", tx.id());
     let outputs = {
@@ -134,7 +143,7 @@ async fn send_tx(
 
     match outputs {
         Ok(logs) => {
-            info!("{}", format_log_receipts(&logs, pretty_print)?);
+            info!("{}", format_log_receipts(&logs, pretty_print, log_decoder)?);
             Ok(logs)
         }
         Err(e) => bail!("{e}"),
@@ -0,0 +1,260 @@
+//! Mutation testing for Sway packages.
+//!
+//! Each mutation makes a small change to the source of a package, e.g. flipping a comparison
+//! operator. The package is then rebuilt and its tests are run against the mutated source. If all
+//! of the tests still pass, the mutant "survived", indicating that the change in behaviour is not
+//! covered by the tests.
+
+pub mod mutation;
+
+use anyhow::{bail, Result};
+use forc_pkg::manifest::ManifestFile;
+use forc_test::{fuzz::FuzzOpts, IgnoredTests, TestRunnerCount, Tested};
+use mutation::Mutation;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use sway_error::handler::Handler;
+use sway_utils::{constants::SRC_DIR, get_sway_files};
+use tempfile::{Builder, TempDir};
+use toml_edit::{value, Document};
+
+/// The outcome of running the tests of a package against a mutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// At least one test failed.
+    Killed,
+    /// All tests passed.
+    Survived,
+    /// The mutated package failed to build.
+    Unviable,
+}
+
+/// A mutation, and the outcome of running the tests of its package against it.
+#[derive(Debug)]
+pub struct Mutant {
+    pub mutation: Mutation,
+    pub outcome: Outcome,
+}
+
+/// A copy of a package or workspace in a temporary directory, to which the mutations are applied so
+/// that the user's sources are never modified, even if testing a mutation is interrupted.
+///
+/// The relative paths of the dependencies of each package which point outside of the copy are made
+/// absolute, so that they are still found from the copy.
+pub struct MutationDir {
+    src_dir: PathBuf,
+    dir: PathBuf,
+    // The copy is removed when this is dropped.
+    _temp_dir: TempDir,
+}
+
+impl MutationDir {
+    const TEMP_PREFIX: &'static str = "forc-mutate";
+
+    /// Copy the package or workspace found at the given path to a new temporary directory.
+    pub fn new(path: &Path) -> Result<Self> {
+        let manifest_file = ManifestFile::from_dir(path)?;
+        let src_dir = manifest_file.dir().canonicalize()?;
+        let temp_dir = Builder::new().prefix(Self::TEMP_PREFIX).tempdir()?;
+        let dir = match src_dir.file_name() {
+            Some(name) => temp_dir.path().canonicalize()?.join(name),
+            None => bail!("Cannot copy the root directory {}", src_dir.display()),
+        };
+        copy_dir_contents(&src_dir, &dir)?;
+
+        let mutation_dir = Self {
+            src_dir,
+            dir,
+            _temp_dir: temp_dir,
+        };
+        for (_, member_manifest) in manifest_file.member_manifests()? {
+            mutation_dir.copy_manifest(member_manifest.path())?;
+        }
+        Ok(mutation_dir)
+    }
+
+    /// The path to the copy of the package or workspace.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path to the copy of the given file of the package or workspace.
+    fn copy_path(&self, path: &Path) -> Result<PathBuf> {
+        let path = path.canonicalize()?;
+        match path.strip_prefix(&self.src_dir) {
+            Ok(rel_path) => Ok(self.dir.join(rel_path)),
+            Err(_) => bail!(
+                "{} is not within {}",
+                path.display(),
+                self.src_dir.display()
+            ),
+        }
+    }
+
+    /// Rewrite the copy of the given manifest with the paths of its dependencies which are outside
+    /// of the copy made absolute.
+    fn copy_manifest(&self, manifest_path: &Path) -> Result<()> {
+        let manifest_dir = match manifest_path.parent() {
+            Some(dir) => dir.canonicalize()?,
+            None => bail!("Cannot find the directory of {}", manifest_path.display()),
+        };
+        let mut manifest_toml = fs::read_to_string(manifest_path)?.parse::<Document>()?;
+        let mut dep_tables = vec![];
+        for (key, item) in manifest_toml.iter_mut() {
+            match key.get() {
+                "dependencies" | "contract-dependencies" => {
+                    dep_tables.extend(item.as_table_like_mut())
+                }
+                "patch" => {
+                    if let Some(patches) = item.as_table_like_mut() {
+                        dep_tables.extend(
+                            patches
+                                .iter_mut()
+                                .filter_map(|(_, patch)| patch.as_table_like_mut()),
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
+        for dep_table in dep_tables {
+            for (_, dep) in dep_table.iter_mut() {
+                let dep_path = match dep.get_mut("path") {
+                    Some(dep_path) => dep_path,
+                    None => continue,
+                };
+                let abs_path = match dep_path.as_str().map(Path::new) {
+                    Some(path) if path.is_relative() => manifest_dir.join(path).canonicalize()?,
+                    _ => continue,
+                };
+                if !abs_path.starts_with(&self.src_dir) {
+                    *dep_path = value(abs_path.display().to_string());
+                }
+            }
+        }
+        fs::write(self.copy_path(manifest_path)?, manifest_toml.to_string())?;
+        Ok(())
+    }
+}
+
+/// Copy the contents of the given directory into the target directory.
+fn copy_dir_contents(src_dir: &Path, target_dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(target_dir)?;
+    for entry in fs::read_dir(src_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir_contents(&entry.path(), &target_dir.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), target_dir.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// A source file in the [MutationDir] that has been overwritten with a mutation. The original
+/// source is restored when this is dropped, ready for the next mutation.
+struct MutatedFile {
+    path: PathBuf,
+    original: Arc<str>,
+}
+
+impl MutatedFile {
+    fn write(mutation_dir: &MutationDir, mutation: &Mutation) -> Result<Self> {
+        let path = match mutation.span.path() {
+            Some(path) => mutation_dir.copy_path(path)?,
+            None => bail!(
+                "The mutation of `{}` has no source file",
+                mutation.span.as_str()
+            ),
+        };
+        fs::write(&path, mutation.apply())?;
+        Ok(Self {
+            path,
+            original: mutation.span.src().clone(),
+        })
+    }
+}
+
+impl Drop for MutatedFile {
+    fn drop(&mut self) {
+        if let Err(err) = fs::write(&self.path, self.original.as_bytes()) {
+            tracing::error!(
+                "Failed to restore the original source of {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Collect the mutations of all source files of the package or workspace members found at the
+/// given path.
+pub fn mutations(path: &Path) -> Result<Vec<Mutation>> {
+    let manifest_file = ManifestFile::from_dir(path)?;
+    let mut mutations = vec![];
+    for (_, member_manifest) in manifest_file.member_manifests()? {
+        let mut files = get_sway_files(member_manifest.dir().join(SRC_DIR));
+        files.sort();
+        for file in files {
+            mutations.extend(file_mutations(file)?);
+        }
+    }
+    Ok(mutations)
+}
+
+/// Collect the mutations of the given source file.
+fn file_mutations(path: PathBuf) -> Result<Vec<Mutation>> {
+    let src: Arc<str> = Arc::from(fs::read_to_string(&path)?);
+    let handler = Handler::default();
+    let mutations = mutation::collect(&handler, src, Some(Arc::new(path.clone())));
+    let (errors, _) = handler.consume();
+    match mutations {
+        Ok(mutations) if errors.is_empty() => Ok(mutations),
+        _ => bail!("Failed to parse {}", path.display()),
+    }
+}
+
+/// Build the package or workspace and run all of its tests, returning whether they all passed,
+/// or `None` if the build failed.
+pub fn run_tests(opts: &forc_test::Opts) -> Result<Option<bool>> {
+    let built = match forc_test::build(opts.clone()) {
+        Ok(built) => built,
+        Err(_) => return Ok(None),
+    };
+    let tested = built.run(
+        TestRunnerCount::Auto,
+        None,
+        IgnoredTests::Skip,
+        FuzzOpts::default(),
+        None,
+    )?;
+    let tested_pkgs = match &tested {
+        Tested::Package(tested_pkg) => vec![tested_pkg.as_ref()],
+        Tested::Workspace(tested_pkgs) => tested_pkgs.iter().collect(),
+    };
+    let passed = tested_pkgs
+        .iter()
+        .flat_map(|tested_pkg| &tested_pkg.tests)
+        .all(|test| test.passed());
+    Ok(Some(passed))
+}
+
+/// Apply the given mutation to its source file in the [MutationDir], run the tests of the copy of
+/// the package or workspace against it and restore the original source.
+pub fn test_mutation(
+    opts: &forc_test::Opts,
+    mutation_dir: &MutationDir,
+    mutation: Mutation,
+) -> Result<Mutant> {
+    let outcome = {
+        let _mutated = MutatedFile::write(mutation_dir, &mutation)?;
+        match run_tests(opts)? {
+            Some(true) => Outcome::Survived,
+            Some(false) => Outcome::Killed,
+            None => Outcome::Unviable,
+        }
+    };
+    Ok(Mutant { mutation, outcome })
+}
@@ -0,0 +1,130 @@
+//! A `forc` plugin for mutation testing of Sway packages.
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use forc_mutate::{mutation::Mutation, Outcome};
+use forc_tracing::init_tracing_subscriber;
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "forc-mutate",
+    about = "Forc plugin for mutation testing of Sway packages.",
+    version
+)]
+/// Applies mutations to the expressions of a package, such as flipping comparison operators,
+/// swapping `+` and `-`, replacing literals, deleting `require` calls and negating `if`
+/// conditions. The package is rebuilt and its tests are run against each mutation in turn, and the
+/// mutations that do not cause any test to fail are reported as surviving mutants.
+///
+/// The package is copied to a temporary directory and the mutations are applied to the copy, so
+/// the sources of the package itself are never modified.
+pub struct App {
+    /// Path to the project, if not specified, current working directory will be used.
+    #[clap(short, long)]
+    pub path: Option<String>,
+    /// List the mutations of the package without running its tests against them.
+    #[clap(long)]
+    pub list: bool,
+    /// Offline mode, prevents Forc from using the network when managing dependencies.
+    /// Meaning it will only try to use previously downloaded dependencies.
+    #[clap(long = "offline")]
+    pub offline: bool,
+    /// Requires that the Forc.lock file is up-to-date. If the lock file is missing, or it
+    /// needs to be updated, Forc will exit with an error
+    #[clap(long)]
+    pub locked: bool,
+}
+
+fn main() {
+    init_tracing_subscriber(Default::default());
+    if let Err(err) = run() {
+        error!("Error: {:?}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let app = App::parse();
+    let dir = match app.path.as_ref() {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir()?,
+    };
+
+    let mutations = forc_mutate::mutations(&dir)?;
+    if app.list {
+        for mutation in &mutations {
+            info!("{}", location(&dir, mutation));
+        }
+        return Ok(());
+    }
+
+    let mutation_dir = forc_mutate::MutationDir::new(&dir)?;
+    let opts = forc_test::Opts {
+        pkg: forc_pkg::PkgOpts {
+            path: Some(mutation_dir.path().display().to_string()),
+            offline: app.offline,
+            terse: true,
+            locked: app.locked,
+            output_directory: None,
+        },
+        ..Default::default()
+    };
+    if forc_mutate::run_tests(&opts)? != Some(true) {
+        bail!("The package must build and all of its tests must pass before it can be mutated");
+    }
+
+    info!("\n  Testing {} mutants\n", mutations.len());
+    let mut survived = vec![];
+    let (mut killed, mut unviable) = (0, 0);
+    for mutation in mutations {
+        let mutant = forc_mutate::test_mutation(&opts, &mutation_dir, mutation)?;
+        let outcome = match mutant.outcome {
+            Outcome::Killed => {
+                killed += 1;
+                "killed"
+            }
+            Outcome::Survived => "survived",
+            Outcome::Unviable => {
+                unviable += 1;
+                "unviable"
+            }
+        };
+        info!("{} ... {outcome}", location(&dir, &mutant.mutation));
+        if mutant.outcome == Outcome::Survived {
+            survived.push(mutant.mutation);
+        }
+    }
+
+    if !survived.is_empty() {
+        info!("\nSurviving mutants:\n");
+        for mutation in &survived {
+            info!("    {}", location(&dir, mutation));
+        }
+    }
+    info!(
+        "\nResult: {killed} killed. {} survived. {unviable} unviable.",
+        survived.len()
+    );
+    if !survived.is_empty() {
+        bail!("{} mutants survived", survived.len());
+    }
+    Ok(())
+}
+
+/// Formats the location of the mutation relative to the given directory, and its description.
+fn location(dir: &Path, mutation: &Mutation) -> String {
+    let path = mutation
+        .span
+        .path()
+        .map(|path| path.strip_prefix(dir).unwrap_or(path).display().to_string())
+        .unwrap_or_default();
+    let (start, _) = mutation.span.line_col();
+    format!(
+        "{path}:{}:{}: {}",
+        start.line,
+        start.col,
+        mutation.description()
+    )
+}
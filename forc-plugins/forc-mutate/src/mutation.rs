@@ -0,0 +1,439 @@
+//! Collecting the mutations that can be applied to the expressions of a Sway source file.
+
+use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
+use sway_ast::{
+    literal::LitBoolType, AttributeDecl, CodeBlockContents, Expr, ExprArrayDescriptor,
+    ExprTupleDescriptor, IfCondition, IfExpr, ItemFn, ItemKind, Literal, MatchBranchKind, Module,
+    Statement,
+};
+use sway_error::handler::{ErrorEmitted, Handler};
+use sway_types::{
+    constants::{BENCH_ATTRIBUTE_NAME, TEST_ATTRIBUTE_NAME},
+    Span, Spanned,
+};
+
+/// The name of the function whose calls are deleted by `MutationKind::DeleteRequire`.
+const REQUIRE_FN_NAME: &str = "require";
+
+/// The kinds of mutations applied to expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationKind {
+    /// Replace a comparison operator with its opposite, e.g. `<` with `>=`.
+    FlipComparison,
+    /// Replace `+` with `-` and vice versa.
+    SwapArithmetic,
+    /// Replace an integer literal with `0` (or `1` if it is `0`), or flip a boolean literal.
+    ReplaceLiteral,
+    /// Replace a call to `require` with `()`.
+    DeleteRequire,
+    /// Negate the condition of an `if` expression.
+    NegateCondition,
+}
+
+/// A single change to the source of a file, replacing the source covered by `span`.
+#[derive(Debug, Clone)]
+pub struct Mutation {
+    pub kind: MutationKind,
+    /// The span of the source replaced by this mutation.
+    pub span: Span,
+    /// The source replacing the span.
+    pub replacement: String,
+}
+
+impl Mutation {
+    /// The full source of the mutated file, with this mutation applied.
+    pub fn apply(&self) -> String {
+        let src = self.span.src();
+        format!(
+            "{}{}{}",
+            &src[..self.span.start()],
+            self.replacement,
+            &src[self.span.end()..]
+        )
+    }
+
+    /// A short description of the change made by this mutation.
+    pub fn description(&self) -> String {
+        match self.kind {
+            MutationKind::DeleteRequire => format!("delete `{REQUIRE_FN_NAME}` call"),
+            MutationKind::NegateCondition => "negate `if` condition".to_string(),
+            MutationKind::FlipComparison
+            | MutationKind::SwapArithmetic
+            | MutationKind::ReplaceLiteral => {
+                format!(
+                    "replace `{}` with `{}`",
+                    self.span.as_str(),
+                    self.replacement
+                )
+            }
+        }
+    }
+}
+
+/// Parse the given source and collect the mutations of all expressions within the bodies of its
+/// functions, excluding those of test and benchmark functions.
+pub fn collect(
+    handler: &Handler,
+    src: Arc<str>,
+    path: Option<Arc<PathBuf>>,
+) -> Result<Vec<Mutation>, ErrorEmitted> {
+    let module = sway_parse::parse_file(handler, src, path)?;
+    let mut collector = Collector::default();
+    collector.module(&module);
+    Ok(collector.mutations)
+}
+
+#[derive(Default)]
+struct Collector {
+    mutations: Vec<Mutation>,
+}
+
+impl Collector {
+    fn push(&mut self, kind: MutationKind, span: Span, replacement: impl Into<String>) {
+        self.mutations.push(Mutation {
+            kind,
+            span,
+            replacement: replacement.into(),
+        });
+    }
+
+    fn module(&mut self, module: &Module) {
+        for item in &module.items {
+            self.item(&item.attribute_list, &item.value);
+        }
+    }
+
+    fn item(&mut self, attribute_list: &[AttributeDecl], item: &ItemKind) {
+        match item {
+            ItemKind::Fn(item_fn) => self.item_fn(attribute_list, item_fn),
+            ItemKind::Impl(item_impl) => {
+                for item_fn in item_impl.contents.get() {
+                    self.item_fn(&item_fn.attribute_list, &item_fn.value);
+                }
+            }
+            ItemKind::Trait(item_trait) => {
+                for item_fn in item_trait.trait_defs_opt.iter().flat_map(|defs| defs.get()) {
+                    self.item_fn(&item_fn.attribute_list, &item_fn.value);
+                }
+            }
+            ItemKind::Abi(item_abi) => {
+                for item_fn in item_abi.abi_defs_opt.iter().flat_map(|defs| defs.get()) {
+                    self.item_fn(&item_fn.attribute_list, &item_fn.value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn item_fn(&mut self, attribute_list: &[AttributeDecl], item_fn: &ItemFn) {
+        let is_test_or_bench = attribute_list
+            .iter()
+            .flat_map(|decl| decl.attribute.get())
+            .any(|attr| {
+                let name = attr.name.as_str();
+                name == TEST_ATTRIBUTE_NAME || name == BENCH_ATTRIBUTE_NAME
+            });
+        if !is_test_or_bench {
+            self.block(item_fn.body.get());
+        }
+    }
+
+    fn block(&mut self, block: &CodeBlockContents) {
+        for statement in &block.statements {
+            match statement {
+                Statement::Let(statement_let) => self.expr(&statement_let.expr),
+                Statement::Item(item) => self.item(&item.attribute_list, &item.value),
+                Statement::Expr { expr, .. } => self.expr(expr),
+            }
+        }
+        if let Some(expr) = &block.final_expr_opt {
+            self.expr(expr);
+        }
+    }
+
+    fn if_expr(&mut self, if_expr: &IfExpr) {
+        match &if_expr.condition {
+            IfCondition::Expr(condition) => {
+                let span = condition.span();
+                let replacement = format!("!({})", span.as_str());
+                self.push(MutationKind::NegateCondition, span, replacement);
+                self.expr(condition);
+            }
+            IfCondition::Let { rhs, .. } => self.expr(rhs),
+        }
+        self.block(if_expr.then_block.get());
+        match &if_expr.else_opt {
+            Some((_, ControlFlow::Break(block))) => self.block(block.get()),
+            Some((_, ControlFlow::Continue(if_expr))) => self.if_expr(if_expr),
+            None => (),
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal) {
+        match literal {
+            Literal::Int(lit_int) => {
+                // `b256` literals are written as 64 hex digits and cannot be replaced by an
+                // integer.
+                let digits = lit_int.span.as_str();
+                if digits.starts_with("0x") && digits.len() == 66 {
+                    return;
+                }
+                let replacement = if lit_int.parsed == 0u8.into() {
+                    "1"
+                } else {
+                    "0"
+                };
+                self.push(
+                    MutationKind::ReplaceLiteral,
+                    lit_int.span.clone(),
+                    replacement,
+                );
+            }
+            Literal::Bool(lit_bool) => {
+                let replacement = match lit_bool.kind {
+                    LitBoolType::True => "false",
+                    LitBoolType::False => "true",
+                };
+                self.push(
+                    MutationKind::ReplaceLiteral,
+                    lit_bool.span.clone(),
+                    replacement,
+                );
+            }
+            Literal::String(_) | Literal::Char(_) => (),
+        }
+    }
+
+    fn comparison(&mut self, op: Span, replacement: &str, lhs: &Expr, rhs: &Expr) {
+        self.push(MutationKind::FlipComparison, op, replacement);
+        self.expr(lhs);
+        self.expr(rhs);
+    }
+
+    fn arithmetic(&mut self, op: Span, replacement: &str, lhs: &Expr, rhs: &Expr) {
+        self.push(MutationKind::SwapArithmetic, op, replacement);
+        self.expr(lhs);
+        self.expr(rhs);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(literal) => self.literal(literal),
+            Expr::Equal {
+                lhs,
+                double_eq_token,
+                rhs,
+            } => self.comparison(double_eq_token.span(), "!=", lhs, rhs),
+            Expr::NotEqual {
+                lhs,
+                bang_eq_token,
+                rhs,
+            } => self.comparison(bang_eq_token.span(), "==", lhs, rhs),
+            Expr::LessThan {
+                lhs,
+                less_than_token,
+                rhs,
+            } => self.comparison(less_than_token.span(), ">=", lhs, rhs),
+            Expr::GreaterThan {
+                lhs,
+                greater_than_token,
+                rhs,
+            } => self.comparison(greater_than_token.span(), "<=", lhs, rhs),
+            Expr::LessThanEq {
+                lhs,
+                less_than_eq_token,
+                rhs,
+            } => self.comparison(less_than_eq_token.span(), ">", lhs, rhs),
+            Expr::GreaterThanEq {
+                lhs,
+                greater_than_eq_token,
+                rhs,
+            } => self.comparison(greater_than_eq_token.span(), "<", lhs, rhs),
+            Expr::Add {
+                lhs,
+                add_token,
+                rhs,
+            } => self.arithmetic(add_token.span(), "-", lhs, rhs),
+            Expr::Sub {
+                lhs,
+                sub_token,
+                rhs,
+            } => self.arithmetic(sub_token.span(), "+", lhs, rhs),
+            Expr::Mul { lhs, rhs, .. }
+            | Expr::Div { lhs, rhs, .. }
+            | Expr::Pow { lhs, rhs, .. }
+            | Expr::Modulo { lhs, rhs, .. }
+            | Expr::Shl { lhs, rhs, .. }
+            | Expr::Shr { lhs, rhs, .. }
+            | Expr::BitAnd { lhs, rhs, .. }
+            | Expr::BitXor { lhs, rhs, .. }
+            | Expr::BitOr { lhs, rhs, .. }
+            | Expr::LogicalAnd { lhs, rhs, .. }
+            | Expr::LogicalOr { lhs, rhs, .. } => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            Expr::FuncApp { func, args } => {
+                let is_require = matches!(
+                    func.as_ref(),
+                    Expr::Path(path) if path.root_opt.is_none()
+                        && path.suffix.is_empty()
+                        && path.prefix.name.as_str() == REQUIRE_FN_NAME
+                );
+                if is_require {
+                    self.push(MutationKind::DeleteRequire, expr.span(), "()");
+                }
+                for arg in args.get() {
+                    self.expr(arg);
+                }
+            }
+            Expr::MethodCall { target, args, .. } => {
+                self.expr(target);
+                for arg in args.get() {
+                    self.expr(arg);
+                }
+            }
+            Expr::If(if_expr) => self.if_expr(if_expr),
+            Expr::Match {
+                value, branches, ..
+            } => {
+                self.expr(value);
+                for branch in branches.get() {
+                    match &branch.kind {
+                        MatchBranchKind::Block { block, .. } => self.block(block.get()),
+                        MatchBranchKind::Expr { expr, .. } => self.expr(expr),
+                    }
+                }
+            }
+            Expr::While {
+                condition, block, ..
+            } => {
+                self.expr(condition);
+                self.block(block.get());
+            }
+            Expr::Block(block) => self.block(block.get()),
+            Expr::Parens(expr) => self.expr(expr.get()),
+            Expr::Tuple(tuple) => {
+                if let ExprTupleDescriptor::Cons { head, tail, .. } = tuple.get() {
+                    self.expr(head);
+                    for expr in tail {
+                        self.expr(expr);
+                    }
+                }
+            }
+            Expr::Array(array) => match array.get() {
+                ExprArrayDescriptor::Sequence(exprs) => {
+                    for expr in exprs {
+                        self.expr(expr);
+                    }
+                }
+                // The length of a repeated array must remain a constant, so only the value is
+                // mutated.
+                ExprArrayDescriptor::Repeat { value, .. } => self.expr(value),
+            },
+            Expr::Struct { fields, .. } => {
+                for field in fields.get() {
+                    if let Some((_, expr)) = &field.expr_opt {
+                        self.expr(expr);
+                    }
+                }
+            }
+            Expr::Index { target, arg } => {
+                self.expr(target);
+                self.expr(arg.get());
+            }
+            Expr::Return { expr_opt, .. } => {
+                if let Some(expr) = expr_opt {
+                    self.expr(expr);
+                }
+            }
+            Expr::FieldProjection { target, .. } | Expr::TupleFieldProjection { target, .. } => {
+                self.expr(target)
+            }
+            Expr::Ref { expr, .. }
+            | Expr::Deref { expr, .. }
+            | Expr::Not { expr, .. }
+            | Expr::Reassignment { expr, .. } => self.expr(expr),
+            Expr::Error(_)
+            | Expr::Path(_)
+            | Expr::AbiCast { .. }
+            | Expr::Asm(_)
+            | Expr::Break { .. }
+            | Expr::Continue { .. } => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptions(src: &str) -> Vec<String> {
+        let handler = Handler::default();
+        collect(&handler, Arc::from(src), None)
+            .expect("failed to parse source")
+            .iter()
+            .map(Mutation::description)
+            .collect()
+    }
+
+    #[test]
+    fn collects_mutations_of_fn_bodies() {
+        let src = r#"library test;
+
+fn check(a: u64, b: u64) -> bool {
+    require(a != 0, 1);
+    if a < b {
+        a + b == 3
+    } else {
+        true
+    }
+}
+"#;
+        assert_eq!(
+            descriptions(src),
+            vec![
+                "delete `require` call",
+                "replace `!=` with `==`",
+                "replace `0` with `1`",
+                "replace `1` with `0`",
+                "negate `if` condition",
+                "replace `<` with `>=`",
+                "replace `==` with `!=`",
+                "replace `+` with `-`",
+                "replace `3` with `0`",
+                "replace `true` with `false`",
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_test_fns() {
+        let src = r#"library test;
+
+fn add(a: u64) -> u64 {
+    a + 1
+}
+
+#[test]
+fn test_add() {
+    assert(add(1) == 2);
+}
+"#;
+        assert_eq!(
+            descriptions(src),
+            vec!["replace `+` with `-`", "replace `1` with `0`"]
+        );
+    }
+
+    #[test]
+    fn apply_replaces_span() {
+        let src = "library test;\n\nfn sub(a: u64) -> u64 {\n    a - 1\n}\n";
+        let handler = Handler::default();
+        let mutations = collect(&handler, Arc::from(src), None).unwrap();
+        assert_eq!(
+            mutations[0].apply(),
+            "library test;\n\nfn sub(a: u64) -> u64 {\n    a + 1\n}\n"
+        );
+    }
+}
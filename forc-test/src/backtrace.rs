@@ -0,0 +1,195 @@
+//! Symbolicated backtraces for tests that revert or panic.
+//!
+//! When a test fails to execute successfully, the program counter of the failing instruction and
+//! those of the contract calls leading to it are recovered from the VM's call frames. These are
+//! translated to source locations using the `SourceMap` of the executing program, the same way
+//! `forc addr2line` resolves a single instruction.
+
+use crate::coverage::ProgramSourceMap;
+use fuel_tx::{AssetId, ContractId, Word};
+use fuel_vm::{
+    consts::{VM_REGISTER_COUNT, WORD_SIZE},
+    fuel_asm::RegId,
+    prelude::Instruction,
+};
+use std::{fmt, fs, path::PathBuf};
+
+/// The chain of calls leading to the instruction at which a test reverted or panicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backtrace {
+    /// The frames of the backtrace, starting with the failing instruction and ending with the
+    /// test itself.
+    pub frames: Vec<BacktraceFrame>,
+}
+
+/// A single instruction within a `Backtrace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacktraceFrame {
+    /// The contract executing the instruction, or `None` for the test script.
+    pub contract_id: Option<ContractId>,
+    /// The index of the instruction within the executing bytecode.
+    pub instruction_ix: usize,
+    /// The source location the instruction was generated from, if known.
+    pub location: Option<SourceLocation>,
+}
+
+/// A location within a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub path: PathBuf,
+    /// The line number, starting from 1.
+    pub line: usize,
+    /// The column number, starting from 1.
+    pub column: usize,
+}
+
+/// The position of an instruction executed by a test, prior to symbolication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ProgramCounter {
+    /// The contract executing the instruction, or `None` for the test script.
+    pub(crate) contract_id: Option<ContractId>,
+    pub(crate) instruction_ix: usize,
+}
+
+impl Backtrace {
+    /// Resolve the source locations of the given program counters using the source maps of the
+    /// programs executing them.
+    pub(crate) fn symbolicate(pcs: &[ProgramCounter], programs: &[ProgramSourceMap]) -> Self {
+        let frames = pcs
+            .iter()
+            .map(|pc| {
+                let location = programs
+                    .iter()
+                    .find(|program| program.contract_id == pc.contract_id)
+                    .and_then(|program| program.source_map.addr_to_span(pc.instruction_ix))
+                    .and_then(|(path, range)| {
+                        let src = fs::read_to_string(&path).ok()?;
+                        let (line, column) = line_col(&src, range.start)?;
+                        Some(SourceLocation { path, line, column })
+                    });
+                BacktraceFrame {
+                    contract_id: pc.contract_id,
+                    instruction_ix: pc.instruction_ix,
+                    location,
+                }
+            })
+            .collect();
+        Backtrace { frames }
+    }
+}
+
+/// Recover the program counters of the failing instruction and the contract calls leading to it
+/// from the registers and memory of the VM at the point of failure.
+///
+/// Each contract call pushes a call frame onto the stack at `$fp`, recording the contract being
+/// called followed by the registers of its caller at the point of the call.
+pub(crate) fn program_counters(
+    registers: &[Word; VM_REGISTER_COUNT],
+    memory: &[u8],
+) -> Vec<ProgramCounter> {
+    const REGISTERS_OFFSET: usize = ContractId::LEN + AssetId::LEN;
+    const REGISTERS_LEN: usize = WORD_SIZE * VM_REGISTER_COUNT;
+
+    let mut pcs = vec![];
+    let mut registers = *registers;
+    loop {
+        let fp = registers[RegId::FP] as usize;
+        let contract_id = match fp {
+            0 => None,
+            fp => match memory.get(fp..fp + ContractId::LEN) {
+                Some(bytes) => ContractId::try_from(bytes).ok(),
+                None => break,
+            },
+        };
+        let offset = registers[RegId::PC].saturating_sub(registers[RegId::IS]);
+        pcs.push(ProgramCounter {
+            contract_id,
+            instruction_ix: offset as usize / Instruction::SIZE,
+        });
+        let caller_registers = match contract_id
+            .and_then(|_| memory.get(fp + REGISTERS_OFFSET..fp + REGISTERS_OFFSET + REGISTERS_LEN))
+        {
+            Some(bytes) => bytes,
+            None => break,
+        };
+        for (register, word) in registers
+            .iter_mut()
+            .zip(caller_registers.chunks_exact(WORD_SIZE))
+        {
+            *register = Word::from_be_bytes(word.try_into().unwrap());
+        }
+    }
+    pcs
+}
+
+/// The line and column of the given byte offset within `src`, both starting from 1.
+fn line_col(src: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = src.get(..offset)?;
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|ix| ix + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    Some((line, column))
+}
+
+impl fmt::Display for BacktraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location {
+            Some(loc) => write!(f, "{}:{}:{}", loc.path.display(), loc.line, loc.column)?,
+            None => write!(f, "<unknown>")?,
+        }
+        write!(f, " (instruction {}", self.instruction_ix)?;
+        if let Some(contract_id) = self.contract_id {
+            write!(f, " in contract 0x{contract_id}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_and_column() {
+        let src = "library foo;\n\nfn bar() {\n    revert(0);\n}\n";
+        let offset = src.find("revert").unwrap();
+        assert_eq!(line_col(src, offset), Some((4, 5)));
+        assert_eq!(line_col(src, 0), Some((1, 1)));
+        assert_eq!(line_col(src, src.len() + 1), None);
+    }
+
+    #[test]
+    fn contract_call_frames() {
+        const FP: usize = 1024;
+        let contract_id = ContractId::from([7; ContractId::LEN]);
+
+        // The test script called the contract from its 5th instruction.
+        let mut caller = [0; VM_REGISTER_COUNT];
+        caller[RegId::IS] = 64;
+        caller[RegId::PC] = 64 + 5 * Instruction::SIZE as Word;
+        let mut memory = vec![0; 4096];
+        memory[FP..FP + ContractId::LEN].copy_from_slice(contract_id.as_ref());
+        let saved = &mut memory[FP + ContractId::LEN + AssetId::LEN..];
+        for (word, register) in saved.chunks_exact_mut(WORD_SIZE).zip(caller) {
+            word.copy_from_slice(&register.to_be_bytes());
+        }
+
+        // The contract failed at its 3rd instruction.
+        let mut registers = [0; VM_REGISTER_COUNT];
+        registers[RegId::FP] = FP as Word;
+        registers[RegId::IS] = 2048;
+        registers[RegId::PC] = 2048 + 3 * Instruction::SIZE as Word;
+
+        let expected = vec![
+            ProgramCounter {
+                contract_id: Some(contract_id),
+                instruction_ix: 3,
+            },
+            ProgramCounter {
+                contract_id: None,
+                instruction_ix: 5,
+            },
+        ];
+        assert_eq!(program_counters(&registers, &memory), expected);
+    }
+}
@@ -0,0 +1,324 @@
+//! Running the functions of a package decorated with `#[bench]`, measuring the gas used and the
+//! number of instructions executed by each, and comparing these against a previously saved
+//! baseline.
+//!
+//! Each line of a baseline file records the measurements of a single benchmark:
+//!
+//! ```ignore
+//! my_package::bench_foo (gas: 1234, instructions: 567)
+//! ```
+
+use crate::{
+    backtrace::Backtrace, exec_test, revert_code, script_gas_used, ExecutedTest, PackageTests,
+    TestCount, TestFilter,
+};
+use anyhow::{anyhow, Result};
+use forc_pkg as pkg;
+use forc_util::abi_decode::LogDecoder;
+use fuel_vm as vm;
+use rayon::prelude::*;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, fs,
+    path::Path,
+};
+use sway_types::Span;
+
+/// The name of the file that the benchmark baseline is written to within the project directory.
+pub const BENCH_BASELINE_FILE_NAME: &str = ".bench-baseline";
+
+/// The result of a `forc test --bench` invocation.
+#[derive(Debug)]
+pub enum Benched {
+    Package(Box<BenchedPackage>),
+    Workspace(Vec<BenchedPackage>),
+}
+
+/// The result of benchmarking a specific package.
+#[derive(Debug)]
+pub struct BenchedPackage {
+    pub built: Box<pkg::BuiltPackage>,
+    pub benches: Vec<BenchResult>,
+    /// The number of benchmarks that were skipped as they did not match the `TestFilter`.
+    pub filtered: usize,
+    /// Decodes the values logged by the benchmarks and the contract they are measuring, if any.
+    pub log_decoder: LogDecoder,
+}
+
+/// The result of executing a single benchmark within a single package.
+#[derive(Debug)]
+pub struct BenchResult {
+    /// The name of the function.
+    pub name: String,
+    /// The time taken for the benchmark to execute.
+    pub duration: std::time::Duration,
+    /// The gas used and the instructions executed by the benchmark.
+    pub measurement: BenchMeasurement,
+    /// The span for the function declaring this benchmark.
+    pub span: Span,
+    /// The resulting state after executing the benchmark function.
+    pub state: vm::state::ProgramState,
+    /// Emitted `Log` and `LogData` receipts during the execution of the benchmark.
+    pub logs: Vec<fuel_tx::Receipt>,
+    /// The source locations leading to the revert or panic, if the benchmark did not execute
+    /// successfully.
+    pub backtrace: Option<Backtrace>,
+}
+
+/// The gas used and the number of instructions executed by a benchmark.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BenchMeasurement {
+    pub gas_used: u64,
+    /// The number of instructions executed, including those executed by called contracts.
+    pub instruction_count: u64,
+}
+
+/// The change of a `BenchMeasurement` relative to a baseline, in percent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchChange {
+    pub gas_used: f64,
+    pub instruction_count: f64,
+}
+
+/// The measurements of each benchmark, keyed by `<package>::<bench>`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BenchBaseline {
+    entries: BTreeMap<String, BenchMeasurement>,
+}
+
+impl BenchResult {
+    /// Whether or not the benchmark executed successfully.
+    pub fn passed(&self) -> bool {
+        revert_code(&self.state).is_none()
+    }
+}
+
+impl BenchMeasurement {
+    /// The change of this measurement relative to the given `baseline`.
+    pub fn change_from(&self, baseline: &BenchMeasurement) -> BenchChange {
+        BenchChange {
+            gas_used: percent_change(baseline.gas_used, self.gas_used),
+            instruction_count: percent_change(baseline.instruction_count, self.instruction_count),
+        }
+    }
+}
+
+fn percent_change(baseline: u64, actual: u64) -> f64 {
+    match baseline {
+        0 if actual == 0 => 0.0,
+        0 => f64::INFINITY,
+        baseline => (actual as f64 - baseline as f64) / baseline as f64 * 100.0,
+    }
+}
+
+impl BenchBaseline {
+    /// Collect the measurements of each benchmark within the given results.
+    pub fn from_benched(benched: &Benched) -> Self {
+        let pkgs: Vec<&BenchedPackage> = match benched {
+            Benched::Package(pkg) => vec![pkg],
+            Benched::Workspace(pkgs) => pkgs.iter().collect(),
+        };
+        let entries = pkgs
+            .iter()
+            .flat_map(|pkg| {
+                pkg.benches.iter().map(|bench| {
+                    let name = format!("{}::{}", pkg.built.pkg_name, bench.name);
+                    (name, bench.measurement)
+                })
+            })
+            .collect();
+        BenchBaseline { entries }
+    }
+
+    /// Parse a baseline from the contents of a `.bench-baseline` file.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let invalid = || anyhow!("invalid bench baseline entry: {line:?}");
+                let (name, measurement) = line
+                    .strip_suffix(')')
+                    .and_then(|line| line.rsplit_once(" (gas: "))
+                    .ok_or_else(invalid)?;
+                let (gas_used, instruction_count) = measurement
+                    .split_once(", instructions: ")
+                    .ok_or_else(invalid)?;
+                let measurement = BenchMeasurement {
+                    gas_used: gas_used.parse().map_err(|_| invalid())?,
+                    instruction_count: instruction_count.parse().map_err(|_| invalid())?,
+                };
+                Ok((name.to_string(), measurement))
+            })
+            .collect::<Result<_>>()?;
+        Ok(BenchBaseline { entries })
+    }
+
+    /// Read the baseline from the file at the given path.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read bench baseline {}: {e}", path.display()))?;
+        Self::parse(&contents)
+    }
+
+    /// Write the baseline to the file at the given path.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+            .map_err(|e| anyhow!("failed to write bench baseline {}: {e}", path.display()))
+    }
+
+    /// The measurement recorded for the benchmark with the given fully qualified name.
+    pub fn get(&self, bench: &str) -> Option<BenchMeasurement> {
+        self.entries.get(bench).copied()
+    }
+}
+
+impl fmt::Display for BenchBaseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (bench, measurement) in &self.entries {
+            writeln!(
+                f,
+                "{bench} (gas: {}, instructions: {})",
+                measurement.gas_used, measurement.instruction_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl PackageTests {
+    /// Iterate over the benchmark entries of this package.
+    fn bench_entries(&self) -> impl Iterator<Item = (&pkg::PkgEntry, &pkg::PkgBenchEntry)> {
+        // TODO: Remove this once https://github.com/FuelLabs/sway/issues/3947 is solved.
+        let mut visited_benches = HashSet::new();
+        self.built_pkg_with_tests()
+            .entries
+            .iter()
+            .filter_map(|entry| entry.kind.bench().map(|bench| (entry, bench)))
+            .filter(move |(_, bench_entry)| visited_benches.insert(&bench_entry.span))
+    }
+
+    /// The number of benchmarks in this package that match and do not match the given filter.
+    pub(crate) fn bench_count(&self, filter: Option<&TestFilter>) -> TestCount {
+        self.bench_entries()
+            .fold(TestCount::default(), |mut count, (entry, _)| {
+                match filter {
+                    Some(filter) if !filter.matches(&entry.finalized.fn_name) => {
+                        count.filtered += 1
+                    }
+                    _ => count.total += 1,
+                }
+                count
+            })
+    }
+
+    /// Run all benchmarks matching the given filter for this package and collect their results.
+    pub(crate) fn run_benches(&self, filter: Option<&TestFilter>) -> Result<BenchedPackage> {
+        let pkg_with_tests = self.built_pkg_with_tests();
+        let source_maps = self.source_maps();
+        let env = pkg_with_tests
+            .built_pkg_descriptor
+            .manifest_file
+            .test
+            .clone()
+            .unwrap_or_default();
+        let mut filtered = 0;
+        let bench_entries: Vec<_> = self
+            .bench_entries()
+            .filter(|(entry, _)| match filter {
+                Some(filter) if !filter.matches(&entry.finalized.fn_name) => {
+                    filtered += 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+        let benches = bench_entries
+            .into_par_iter()
+            .map(|(entry, bench_entry)| {
+                let offset = u32::try_from(entry.finalized.imm)
+                    .expect("bench instruction offset out of range");
+                let test_setup = self.setup(&env)?;
+                // Instructions are counted by stepping through the benchmark, so it is always
+                // executed with its instructions recorded.
+                let ExecutedTest {
+                    state,
+                    duration,
+                    receipts,
+                    hits,
+                    revert_pcs,
+                } = exec_test(
+                    &pkg_with_tests.bytecode,
+                    offset,
+                    vec![],
+                    test_setup,
+                    &env,
+                    true,
+                )?;
+                let measurement = BenchMeasurement {
+                    gas_used: script_gas_used(&receipts),
+                    instruction_count: hits.map(|hits| hits.total()).unwrap_or_default(),
+                };
+                let logs = receipts
+                    .into_iter()
+                    .filter(|receipt| {
+                        matches!(
+                            receipt,
+                            fuel_tx::Receipt::Log { .. } | fuel_tx::Receipt::LogData { .. }
+                        )
+                    })
+                    .collect();
+                Ok(BenchResult {
+                    name: entry.finalized.fn_name.clone(),
+                    duration,
+                    measurement,
+                    span: bench_entry.span.clone(),
+                    state,
+                    logs,
+                    backtrace: revert_pcs.map(|pcs| Backtrace::symbolicate(&pcs, &source_maps)),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(BenchedPackage {
+            built: Box::new(pkg_with_tests.clone()),
+            benches,
+            filtered,
+            log_decoder: self.log_decoder()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_roundtrip() {
+        let contents = "pkg::bench_bar (gas: 20, instructions: 8)\n\
+                        pkg::bench_foo (gas: 10, instructions: 4)\n";
+        let baseline = BenchBaseline::parse(contents).unwrap();
+        let expected = BenchMeasurement {
+            gas_used: 10,
+            instruction_count: 4,
+        };
+        assert_eq!(baseline.get("pkg::bench_foo"), Some(expected));
+        assert_eq!(baseline.to_string(), contents);
+        assert!(BenchBaseline::parse("pkg::bench_foo (gas: 10)").is_err());
+    }
+
+    #[test]
+    fn change_from_baseline() {
+        let baseline = BenchMeasurement {
+            gas_used: 200,
+            instruction_count: 100,
+        };
+        let latest = BenchMeasurement {
+            gas_used: 150,
+            instruction_count: 110,
+        };
+        let change = latest.change_from(&baseline);
+        assert_eq!(change.gas_used, -25.0);
+        assert_eq!(change.instruction_count, 10.0);
+    }
+}
@@ -0,0 +1,218 @@
+//! Source-level code coverage for tests.
+//!
+//! When coverage is enabled, each test is additionally executed one instruction at a time in
+//! order to record the instructions it executes. The executed instructions are mapped back to the
+//! source lines they were generated from using the `SourceMap` produced alongside the bytecode.
+
+use anyhow::{anyhow, Result};
+use fuel_tx::ContractId;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+use sway_core::source_map::SourceMap;
+
+/// The name of the LCOV file that coverage is written to by default.
+pub const LCOV_FILE_NAME: &str = "lcov.info";
+
+/// The set of options controlling the collection of coverage.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CoverageOpts {
+    /// Whether to also record the coverage of dependencies, e.g. `std` and `core`.
+    pub include_dependencies: bool,
+}
+
+/// The number of times each instruction was executed.
+///
+/// Instructions are identified by the contract executing them, or `None` for the test script
+/// itself, and their index within the executing bytecode.
+#[derive(Debug, Default, Clone)]
+pub struct InstructionHits {
+    hits: HashMap<(Option<ContractId>, usize), u64>,
+}
+
+/// The number of times each line of each source file was executed.
+///
+/// Only lines that instructions were generated from are recorded.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    files: BTreeMap<PathBuf, BTreeMap<usize, u64>>,
+}
+
+/// The number of lines executed within a single source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCoverage {
+    pub path: PathBuf,
+    /// The number of lines that were executed at least once.
+    pub lines_hit: usize,
+    /// The number of lines that instructions were generated from.
+    pub lines_found: usize,
+}
+
+/// The source map for a program executed by the tests.
+pub(crate) struct ProgramSourceMap<'a> {
+    /// The contract executing the program, or `None` for the test script.
+    pub(crate) contract_id: Option<ContractId>,
+    pub(crate) source_map: &'a SourceMap,
+}
+
+impl InstructionHits {
+    /// Record an execution of the instruction at the given index.
+    pub(crate) fn record(&mut self, contract_id: Option<ContractId>, instruction_ix: usize) {
+        *self.hits.entry((contract_id, instruction_ix)).or_default() += 1;
+    }
+
+    /// The total number of instructions executed.
+    pub(crate) fn total(&self) -> u64 {
+        self.hits.values().sum()
+    }
+
+    /// Add the hits recorded within `other` to `self`.
+    pub fn merge(&mut self, other: &InstructionHits) {
+        for (&instruction, &count) in &other.hits {
+            *self.hits.entry(instruction).or_default() += count;
+        }
+    }
+
+    /// The number of times the given instruction was executed.
+    pub fn get(&self, contract_id: Option<ContractId>, instruction_ix: usize) -> u64 {
+        self.hits
+            .get(&(contract_id, instruction_ix))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Coverage {
+    /// Map the given instruction hits back to the source lines of the given programs.
+    ///
+    /// Only files for which `include_file` returns `true` are recorded.
+    pub(crate) fn from_hits(
+        programs: &[ProgramSourceMap],
+        hits: &InstructionHits,
+        include_file: impl Fn(&Path) -> bool,
+    ) -> Result<Self> {
+        let mut coverage = Coverage::default();
+        let mut line_starts: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        for program in programs {
+            for instruction_ix in program.source_map.addrs() {
+                let (path, range) = match program.source_map.addr_to_span(instruction_ix) {
+                    Some(span) => span,
+                    None => continue,
+                };
+                if !include_file(&path) {
+                    continue;
+                }
+                if !line_starts.contains_key(&path) {
+                    let src = fs::read_to_string(&path).map_err(|e| {
+                        anyhow!("failed to read source file {}: {e}", path.display())
+                    })?;
+                    let starts = std::iter::once(0)
+                        .chain(src.match_indices('\n').map(|(ix, _)| ix + 1))
+                        .collect();
+                    line_starts.insert(path.clone(), starts);
+                }
+                // Lines are numbered from 1.
+                let line = line_starts[&path].partition_point(|&start| start <= range.start);
+                let count = hits.get(program.contract_id, instruction_ix);
+                *coverage
+                    .files
+                    .entry(path)
+                    .or_default()
+                    .entry(line)
+                    .or_default() += count;
+            }
+        }
+        Ok(coverage)
+    }
+
+    /// Add the line hits recorded within `other` to `self`.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (path, lines) in &other.files {
+            let file = self.files.entry(path.clone()).or_default();
+            for (&line, &count) in lines {
+                *file.entry(line).or_default() += count;
+            }
+        }
+    }
+
+    /// The number of lines executed within each source file.
+    pub fn summary(&self) -> Vec<FileCoverage> {
+        self.files
+            .iter()
+            .map(|(path, lines)| FileCoverage {
+                path: path.clone(),
+                lines_hit: lines.values().filter(|&&count| count > 0).count(),
+                lines_found: lines.len(),
+            })
+            .collect()
+    }
+
+    /// Render the coverage in the LCOV tracefile format.
+    pub fn to_lcov(&self) -> String {
+        // Writing to a `String` cannot fail.
+        let mut lcov = String::new();
+        for (file, (path, lines)) in self.summary().iter().zip(&self.files) {
+            writeln!(lcov, "SF:{}", path.display()).unwrap();
+            for (line, count) in lines {
+                writeln!(lcov, "DA:{line},{count}").unwrap();
+            }
+            writeln!(lcov, "LH:{}", file.lines_hit).unwrap();
+            writeln!(lcov, "LF:{}", file.lines_found).unwrap();
+            writeln!(lcov, "end_of_record").unwrap();
+        }
+        lcov
+    }
+
+    /// Write the coverage in the LCOV tracefile format to the file at the given path.
+    pub fn write_lcov(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_lcov())
+            .map_err(|e| anyhow!("failed to write coverage {}: {e}", path.display()))
+    }
+}
+
+impl FileCoverage {
+    /// The percentage of lines that were executed.
+    pub fn percentage(&self) -> f64 {
+        match self.lines_found {
+            0 => 100.0,
+            found => self.lines_hit as f64 / found as f64 * 100.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lcov() {
+        let mut coverage = Coverage::default();
+        coverage.files.insert(
+            PathBuf::from("/pkg/src/lib.sw"),
+            BTreeMap::from([(3, 2), (4, 0), (7, 1)]),
+        );
+        let mut other = Coverage::default();
+        other
+            .files
+            .insert(PathBuf::from("/pkg/src/lib.sw"), BTreeMap::from([(4, 1)]));
+        coverage.merge(&other);
+        let expected = "SF:/pkg/src/lib.sw\nDA:3,2\nDA:4,1\nDA:7,1\nLH:3\nLF:3\nend_of_record\n";
+        assert_eq!(coverage.to_lcov(), expected);
+    }
+
+    #[test]
+    fn summary() {
+        let mut coverage = Coverage::default();
+        coverage.files.insert(
+            PathBuf::from("/pkg/src/lib.sw"),
+            BTreeMap::from([(3, 2), (4, 0), (5, 0), (7, 1)]),
+        );
+        let summary = coverage.summary();
+        assert_eq!(summary[0].lines_hit, 2);
+        assert_eq!(summary[0].lines_found, 4);
+        assert_eq!(summary[0].percentage(), 50.0);
+    }
+}
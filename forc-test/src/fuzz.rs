@@ -0,0 +1,416 @@
+//! Support for fuzz tests, i.e. test functions declared with `#[test(fuzz)]` that accept
+//! parameters.
+//!
+//! Inputs for each of the test function's parameters are randomly generated from a seeded RNG so
+//! that failures are reproducible. The inputs are encoded according to the Fuel ABI and passed to
+//! the test as its script data. Upon failure, the failing inputs are repeatedly simplified while
+//! the test still fails in order to report a minimal counterexample.
+
+use crate::{coverage::InstructionHits, ExecutedTest, TestSetup};
+use anyhow::{anyhow, bail, Result};
+use forc_pkg::FuzzParams;
+use forc_util::abi_decode::{format_token, resolve_param_type, type_lookup};
+use fuels_core::abi_encoder::ABIEncoder;
+use fuels_types::{enum_variants::EnumVariants, param_types::ParamType, StringToken, Token};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The number of inputs generated for each fuzz test by default.
+pub const DEFAULT_FUZZ_RUNS: usize = 256;
+
+/// The seed used to generate fuzz test inputs by default.
+pub const DEFAULT_FUZZ_SEED: u64 = 0xF022;
+
+/// The maximum number of executions spent on shrinking a failing input.
+const MAX_SHRINK_RUNS: usize = 1024;
+
+/// The set of options controlling the generation of fuzz test inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuzzOpts {
+    /// The number of inputs to generate and execute each fuzz test with.
+    pub runs: usize,
+    /// The seed used to generate the inputs.
+    pub seed: u64,
+}
+
+/// The outcome of executing a fuzz test with generated inputs.
+#[derive(Debug, Clone)]
+pub struct FuzzResult {
+    /// The number of inputs the test was executed with before passing or failing.
+    pub runs: usize,
+    /// The seed used to generate the inputs.
+    pub seed: u64,
+    /// The minimal failing input found for each of the test's parameters, if the test failed.
+    pub counterexample: Option<Vec<FuzzInput>>,
+}
+
+/// A generated input for a single parameter of a fuzz test.
+#[derive(Debug, Clone)]
+pub struct FuzzInput {
+    /// The name of the parameter.
+    pub name: String,
+    /// The generated value, formatted as a Sway expression.
+    pub value: String,
+}
+
+/// A parameter of a fuzz test, with its type resolved from the JSON ABI.
+struct FuzzParam {
+    name: String,
+    param_type: ParamType,
+}
+
+impl Default for FuzzOpts {
+    fn default() -> Self {
+        Self {
+            runs: DEFAULT_FUZZ_RUNS,
+            seed: DEFAULT_FUZZ_SEED,
+        }
+    }
+}
+
+/// Execute the fuzz test with `opts.runs` generated inputs using `exec` until an input causes the
+/// test to fail, as determined by `passed`.
+///
+/// Returns the result of fuzzing along with the execution to report for the test. This is the
+/// execution of the shrunk counterexample if the test failed, or the last execution otherwise.
+/// The reported duration and instruction hits are accumulated over all generated inputs.
+pub(crate) fn fuzz_test(
+    params: &FuzzParams,
+    opts: &FuzzOpts,
+    exec: impl Fn(Vec<u8>, TestSetup) -> Result<ExecutedTest>,
+    setup: &TestSetup,
+    passed: impl Fn(&ExecutedTest) -> bool,
+) -> Result<(FuzzResult, ExecutedTest)> {
+    let params = resolve_params(params)?;
+    let mut rng = StdRng::seed_from_u64(opts.seed);
+    let run = |tokens: &[Token]| -> Result<ExecutedTest> { exec(encode(tokens)?, setup.clone()) };
+
+    let mut last_executed = None;
+    let mut total_duration = std::time::Duration::default();
+    let mut total_hits: Option<InstructionHits> = None;
+    for run_ix in 0..opts.runs {
+        let tokens: Vec<Token> = params
+            .iter()
+            .map(|param| generate(&param.param_type, &mut rng))
+            .collect();
+        let executed = run(&tokens)?;
+        total_duration += executed.duration;
+        if let Some(hits) = &executed.hits {
+            total_hits.get_or_insert_with(Default::default).merge(hits);
+        }
+        if !passed(&executed) {
+            let (tokens, mut executed) = shrink(&params, tokens, executed, &run, &passed)?;
+            executed.duration = total_duration;
+            executed.hits = total_hits;
+            let counterexample = params
+                .iter()
+                .zip(&tokens)
+                .map(|(param, token)| FuzzInput {
+                    name: param.name.clone(),
+                    value: format_token(token, &param.param_type),
+                })
+                .collect();
+            let result = FuzzResult {
+                runs: run_ix + 1,
+                seed: opts.seed,
+                counterexample: Some(counterexample),
+            };
+            return Ok((result, executed));
+        }
+        last_executed = Some(executed);
+    }
+
+    let mut executed = last_executed.ok_or_else(|| anyhow!("fuzz tests require at least 1 run"))?;
+    executed.duration = total_duration;
+    executed.hits = total_hits;
+    let result = FuzzResult {
+        runs: opts.runs,
+        seed: opts.seed,
+        counterexample: None,
+    };
+    Ok((result, executed))
+}
+
+/// Resolve the type of each of the fuzz test's parameters from their JSON ABI declarations.
+fn resolve_params(params: &FuzzParams) -> Result<Vec<FuzzParam>> {
+    let type_lookup = type_lookup(&params.types);
+    params
+        .inputs
+        .iter()
+        .map(|input| {
+            let param_type = resolve_param_type(input, &type_lookup)?;
+            check_supported(&param_type).map_err(|e| {
+                anyhow!("unsupported type for fuzz parameter `{}`: {e}", input.name)
+            })?;
+            Ok(FuzzParam {
+                name: input.name.clone(),
+                param_type,
+            })
+        })
+        .collect()
+}
+
+/// Inputs may only be generated for types that are encoded inline, i.e. types without heap data.
+fn check_supported(param_type: &ParamType) -> Result<()> {
+    match param_type {
+        ParamType::Vector(_) => bail!("vectors are not supported"),
+        ParamType::RawSlice => bail!("raw slices are not supported"),
+        ParamType::Array(elem, _) => check_supported(elem),
+        ParamType::Struct { fields, .. } => fields
+            .iter()
+            .try_for_each(|(_, field)| check_supported(field)),
+        ParamType::Enum { variants, .. } => {
+            variants.param_types().iter().try_for_each(check_supported)
+        }
+        ParamType::Tuple(elems) => elems.iter().try_for_each(check_supported),
+        _ => Ok(()),
+    }
+}
+
+/// Encode the given inputs as script data.
+fn encode(tokens: &[Token]) -> Result<Vec<u8>> {
+    // The supported types contain no pointers, so the encoding does not depend on its address.
+    Ok(ABIEncoder::encode(tokens)?.resolve(0))
+}
+
+/// Generate a random value of the given type.
+///
+/// Integers are biased towards their boundary values, as these are the most likely to trigger
+/// edge cases such as overflows.
+fn generate(param_type: &ParamType, rng: &mut StdRng) -> Token {
+    fn int(rng: &mut StdRng, max: u64) -> u64 {
+        match rng.gen_range(0..8) {
+            0 => *[0, 1, max, max - 1].get(rng.gen_range(0..4)).unwrap(),
+            _ => rng.gen_range(0..=max),
+        }
+    }
+    match param_type {
+        ParamType::U8 => Token::U8(int(rng, u8::MAX.into()) as u8),
+        ParamType::U16 => Token::U16(int(rng, u16::MAX.into()) as u16),
+        ParamType::U32 => Token::U32(int(rng, u32::MAX.into()) as u32),
+        ParamType::U64 => Token::U64(int(rng, u64::MAX)),
+        ParamType::Bool => Token::Bool(rng.gen()),
+        ParamType::Byte => Token::Byte(rng.gen()),
+        ParamType::B256 => Token::B256(rng.gen()),
+        ParamType::Unit => Token::Unit,
+        ParamType::Array(elem, len) => {
+            Token::Array((0..*len).map(|_| generate(elem, rng)).collect())
+        }
+        ParamType::String(len) => {
+            let s = (0..*len)
+                .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
+                .collect();
+            Token::String(StringToken::new(s, *len))
+        }
+        ParamType::Struct { fields, .. } => Token::Struct(
+            fields
+                .iter()
+                .map(|(_, field)| generate(field, rng))
+                .collect(),
+        ),
+        ParamType::Enum { variants, .. } => {
+            let discriminant = rng.gen_range(0..variants.variants().len());
+            let variant = &variants.variants()[discriminant].1;
+            enum_token(discriminant, generate(variant, rng), variants)
+        }
+        ParamType::Tuple(elems) => {
+            Token::Tuple(elems.iter().map(|elem| generate(elem, rng)).collect())
+        }
+        ParamType::Vector(_) | ParamType::RawSlice => {
+            unreachable!("unsupported fuzz parameter types are rejected before generation")
+        }
+    }
+}
+
+fn enum_token(discriminant: usize, value: Token, variants: &EnumVariants) -> Token {
+    let discriminant = u8::try_from(discriminant).expect("enums have at most 256 variants");
+    Token::Enum(Box::new((discriminant, value, variants.clone())))
+}
+
+/// Repeatedly replace the failing inputs with simpler inputs for which the test still fails.
+fn shrink(
+    params: &[FuzzParam],
+    mut tokens: Vec<Token>,
+    mut executed: ExecutedTest,
+    run: &impl Fn(&[Token]) -> Result<ExecutedTest>,
+    passed: &impl Fn(&ExecutedTest) -> bool,
+) -> Result<(Vec<Token>, ExecutedTest)> {
+    let mut shrink_runs = 0;
+    'shrinking: loop {
+        for (ix, param) in params.iter().enumerate() {
+            for candidate in shrink_candidates(&tokens[ix], &param.param_type) {
+                if shrink_runs == MAX_SHRINK_RUNS {
+                    break 'shrinking;
+                }
+                shrink_runs += 1;
+                let mut candidate_tokens = tokens.clone();
+                candidate_tokens[ix] = candidate;
+                let candidate_executed = run(&candidate_tokens)?;
+                if !passed(&candidate_executed) {
+                    tokens = candidate_tokens;
+                    executed = candidate_executed;
+                    continue 'shrinking;
+                }
+            }
+        }
+        break;
+    }
+    Ok((tokens, executed))
+}
+
+/// Produce values that are strictly simpler than the given value, simplest first.
+fn shrink_candidates(token: &Token, param_type: &ParamType) -> Vec<Token> {
+    // Approach the original value from zero by halving the distance, like a binary search.
+    fn ints(value: u64) -> Vec<u64> {
+        if value == 0 {
+            return vec![];
+        }
+        let mut candidates = vec![0];
+        let mut delta = value / 2;
+        while delta > 0 {
+            candidates.push(value - delta);
+            delta /= 2;
+        }
+        candidates
+    }
+    // Shrink each of the elements of an aggregate independently.
+    fn elems<'a>(
+        elems: &[Token],
+        types: impl Iterator<Item = &'a ParamType>,
+        rebuild: impl Fn(Vec<Token>) -> Token,
+    ) -> Vec<Token> {
+        elems
+            .iter()
+            .zip(types)
+            .enumerate()
+            .flat_map(|(ix, (elem, elem_type))| {
+                shrink_candidates(elem, elem_type)
+                    .into_iter()
+                    .map(|candidate| {
+                        let mut elems = elems.to_vec();
+                        elems[ix] = candidate;
+                        rebuild(elems)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+    match (token, param_type) {
+        (Token::U8(v), _) => ints(u64::from(*v))
+            .into_iter()
+            .map(|v| Token::U8(v as u8))
+            .collect(),
+        (Token::U16(v), _) => ints(u64::from(*v))
+            .into_iter()
+            .map(|v| Token::U16(v as u16))
+            .collect(),
+        (Token::U32(v), _) => ints(u64::from(*v))
+            .into_iter()
+            .map(|v| Token::U32(v as u32))
+            .collect(),
+        (Token::U64(v), _) => ints(*v).into_iter().map(Token::U64).collect(),
+        (Token::Byte(v), _) => ints(u64::from(*v))
+            .into_iter()
+            .map(|v| Token::Byte(v as u8))
+            .collect(),
+        (Token::Bool(true), _) => vec![Token::Bool(false)],
+        (Token::B256(v), _) if *v != [0; 32] => vec![Token::B256([0; 32])],
+        (Token::String(s), ParamType::String(len)) => {
+            let simplest = "a".repeat(*len);
+            match s.get_encodable_str() {
+                Ok(s) if s != simplest => vec![Token::String(StringToken::new(simplest, *len))],
+                _ => vec![],
+            }
+        }
+        (Token::Array(tokens), ParamType::Array(elem, _)) => {
+            elems(tokens, std::iter::repeat(&**elem), Token::Array)
+        }
+        (Token::Struct(tokens), ParamType::Struct { fields, .. }) => {
+            elems(tokens, fields.iter().map(|(_, field)| field), Token::Struct)
+        }
+        (Token::Tuple(tokens), ParamType::Tuple(types)) => {
+            elems(tokens, types.iter(), Token::Tuple)
+        }
+        (Token::Enum(selector), ParamType::Enum { variants, .. }) => {
+            let (discriminant, value, _) = &**selector;
+            let variant = &variants.variants()[usize::from(*discriminant)].1;
+            // Prefer the first variant, then simpler values of the current variant.
+            let mut candidates = vec![];
+            if *discriminant != 0 {
+                let first_variant = &variants.variants()[0].1;
+                candidates.push(enum_token(0, simplest(first_variant), variants));
+            }
+            candidates.extend(
+                shrink_candidates(value, variant)
+                    .into_iter()
+                    .map(|value| enum_token(usize::from(*discriminant), value, variants)),
+            );
+            candidates
+        }
+        _ => vec![],
+    }
+}
+
+/// The simplest value of the given type.
+fn simplest(param_type: &ParamType) -> Token {
+    match param_type {
+        ParamType::U8 => Token::U8(0),
+        ParamType::U16 => Token::U16(0),
+        ParamType::U32 => Token::U32(0),
+        ParamType::U64 => Token::U64(0),
+        ParamType::Bool => Token::Bool(false),
+        ParamType::Byte => Token::Byte(0),
+        ParamType::B256 => Token::B256([0; 32]),
+        ParamType::Array(elem, len) => Token::Array(vec![simplest(elem); *len]),
+        ParamType::String(len) => Token::String(StringToken::new("a".repeat(*len), *len)),
+        ParamType::Struct { fields, .. } => {
+            Token::Struct(fields.iter().map(|(_, field)| simplest(field)).collect())
+        }
+        ParamType::Enum { variants, .. } => {
+            enum_token(0, simplest(&variants.variants()[0].1), variants)
+        }
+        ParamType::Tuple(elems) => Token::Tuple(elems.iter().map(simplest).collect()),
+        ParamType::Unit | ParamType::Vector(_) | ParamType::RawSlice => Token::Unit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point_type() -> ParamType {
+        ParamType::Struct {
+            name: "Point".to_string(),
+            fields: vec![
+                ("x".to_string(), ParamType::U64),
+                ("y".to_string(), ParamType::Bool),
+            ],
+            generics: vec![],
+        }
+    }
+
+    #[test]
+    fn generation_is_reproducible() {
+        let param_type = ParamType::Array(Box::new(point_type()), 4);
+        let a = generate(&param_type, &mut StdRng::seed_from_u64(DEFAULT_FUZZ_SEED));
+        let b = generate(&param_type, &mut StdRng::seed_from_u64(DEFAULT_FUZZ_SEED));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn shrink_candidates_are_simpler() {
+        let token = Token::Struct(vec![Token::U64(10), Token::Bool(true)]);
+        let candidates = shrink_candidates(&token, &point_type());
+        assert_eq!(
+            candidates,
+            vec![
+                Token::Struct(vec![Token::U64(0), Token::Bool(true)]),
+                Token::Struct(vec![Token::U64(5), Token::Bool(true)]),
+                Token::Struct(vec![Token::U64(8), Token::Bool(true)]),
+                Token::Struct(vec![Token::U64(9), Token::Bool(true)]),
+                Token::Struct(vec![Token::U64(10), Token::Bool(false)]),
+            ]
+        );
+        let simplest = simplest(&point_type());
+        assert!(shrink_candidates(&simplest, &point_type()).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! Recording the gas used by each test within a `.gas-snapshot` file, and checking the gas used by
+//! later test runs against a previously recorded snapshot.
+//!
+//! Each line of a snapshot file records the gas used by a single test:
+//!
+//! ```ignore
+//! my_package::test_foo (gas: 1234)
+//! ```
+
+use crate::{Tested, TestedPackage};
+use anyhow::{anyhow, Result};
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+/// The name of the file that the gas snapshot is written to within the project directory.
+pub const GAS_SNAPSHOT_FILE_NAME: &str = ".gas-snapshot";
+
+/// The gas used by each test, keyed by `<package>::<test>`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GasSnapshot {
+    entries: BTreeMap<String, u64>,
+}
+
+/// A test whose gas usage differs from the gas recorded for it within a `GasSnapshot`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasDiff {
+    /// The fully qualified name of the test, i.e. `<package>::<test>`.
+    pub test: String,
+    /// The gas recorded within the snapshot.
+    pub expected: u64,
+    /// The gas used by the latest run of the test.
+    pub actual: u64,
+}
+
+impl GasSnapshot {
+    /// Collect the gas used by each test within the given test results.
+    pub fn from_tested(tested: &Tested) -> Self {
+        let pkgs: Vec<&TestedPackage> = match tested {
+            Tested::Package(pkg) => vec![pkg],
+            Tested::Workspace(pkgs) => pkgs.iter().collect(),
+        };
+        let entries = pkgs
+            .iter()
+            .flat_map(|pkg| {
+                pkg.tests.iter().map(|test| {
+                    let name = format!("{}::{}", pkg.built.pkg_name, test.name);
+                    (name, test.gas_used)
+                })
+            })
+            .collect();
+        GasSnapshot { entries }
+    }
+
+    /// Parse a snapshot from the contents of a `.gas-snapshot` file.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (name, gas) = line
+                    .strip_suffix(')')
+                    .and_then(|line| line.rsplit_once(" (gas: "))
+                    .ok_or_else(|| anyhow!("invalid gas snapshot entry: {line:?}"))?;
+                let gas = gas
+                    .parse()
+                    .map_err(|e| anyhow!("invalid gas value in snapshot entry {line:?}: {e}"))?;
+                Ok((name.to_string(), gas))
+            })
+            .collect::<Result<_>>()?;
+        Ok(GasSnapshot { entries })
+    }
+
+    /// Read the snapshot from the file at the given path.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read gas snapshot {}: {e}", path.display()))?;
+        Self::parse(&contents)
+    }
+
+    /// Write the snapshot to the file at the given path.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+            .map_err(|e| anyhow!("failed to write gas snapshot {}: {e}", path.display()))
+    }
+
+    /// Record the gas used by each test within `latest`, replacing any gas previously recorded for
+    /// it.  The gas recorded for the tests which aren't within `latest` is kept.
+    pub fn merge(&mut self, latest: GasSnapshot) {
+        self.entries.extend(latest.entries);
+    }
+
+    /// The gas recorded for the test with the given fully qualified name.
+    pub fn get(&self, test: &str) -> Option<u64> {
+        self.entries.get(test).copied()
+    }
+
+    /// Returns all tests whose gas usage within `self` exceeds the gas recorded within `baseline`
+    /// by more than `tolerance_percent` percent.
+    ///
+    /// Tests that are missing from the `baseline` are not considered regressions.
+    pub fn regressions(&self, baseline: &GasSnapshot, tolerance_percent: f64) -> Vec<GasDiff> {
+        self.entries
+            .iter()
+            .filter_map(|(test, &actual)| {
+                let expected = baseline.get(test)?;
+                let allowed = expected as f64 * (1.0 + tolerance_percent / 100.0);
+                (actual as f64 > allowed).then(|| GasDiff {
+                    test: test.clone(),
+                    expected,
+                    actual,
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for GasSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (test, gas) in &self.entries {
+            writeln!(f, "{test} (gas: {gas})")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_roundtrip() {
+        let contents = "pkg::test_bar (gas: 20)\npkg::test_foo (gas: 10)\n";
+        let snapshot = GasSnapshot::parse(contents).unwrap();
+        assert_eq!(snapshot.get("pkg::test_foo"), Some(10));
+        assert_eq!(snapshot.get("pkg::test_bar"), Some(20));
+        assert_eq!(snapshot.to_string(), contents);
+    }
+
+    #[test]
+    fn parse_invalid_entry() {
+        assert!(GasSnapshot::parse("pkg::test_foo 10").is_err());
+        assert!(GasSnapshot::parse("pkg::test_foo (gas: ten)").is_err());
+    }
+
+    #[test]
+    fn merge_keeps_unrun_tests() {
+        let mut snapshot = GasSnapshot::parse("pkg::a (gas: 100)\npkg::b (gas: 100)").unwrap();
+        let latest = GasSnapshot::parse("pkg::b (gas: 90)\npkg::c (gas: 10)").unwrap();
+        snapshot.merge(latest);
+        assert_eq!(
+            snapshot.to_string(),
+            "pkg::a (gas: 100)\npkg::b (gas: 90)\npkg::c (gas: 10)\n"
+        );
+    }
+
+    #[test]
+    fn regressions_respect_tolerance() {
+        let baseline = GasSnapshot::parse("pkg::a (gas: 100)\npkg::b (gas: 100)").unwrap();
+        let latest =
+            GasSnapshot::parse("pkg::a (gas: 105)\npkg::b (gas: 90)\npkg::c (gas: 1000)").unwrap();
+        let expected = vec![GasDiff {
+            test: "pkg::a".to_string(),
+            expected: 100,
+            actual: 105,
+        }];
+        assert_eq!(latest.regressions(&baseline, 0.0), expected);
+        assert!(latest.regressions(&baseline, 5.0).is_empty());
+    }
+}
@@ -1,14 +1,32 @@
+pub mod backtrace;
+pub mod bench;
+pub mod coverage;
+pub mod fuzz;
+pub mod gas_snapshot;
+pub mod predicate;
+pub mod report;
+
 use std::collections::HashSet;
 use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
 
+use backtrace::{Backtrace, ProgramCounter};
+use coverage::{Coverage, CoverageOpts, InstructionHits, ProgramSourceMap};
 use forc_pkg as pkg;
+use forc_util::abi_decode::LogDecoder;
 use fuel_tx as tx;
-use fuel_vm::checked_transaction::builder::TransactionBuilderExt;
+use fuel_tx::Finalizable;
+use fuel_vm::checked_transaction::{builder::TransactionBuilderExt, Checked, IntoChecked};
 use fuel_vm::gas::GasCosts;
-use fuel_vm::{self as vm, fuel_asm, prelude::Instruction};
+use fuel_vm::{
+    self as vm, fuel_asm,
+    prelude::{Hasher, Instruction, SecretKey},
+};
+use fuzz::{FuzzOpts, FuzzResult};
 use pkg::TestPassCondition;
-use pkg::{Built, BuiltPackage, CONTRACT_ID_CONSTANT_NAME};
+use pkg::{Built, BuiltPackage, PkgEntry, PkgTestEntry, CONTRACT_ID_CONSTANT_NAME};
+use predicate::PredicateResult;
 use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
 use sway_core::{language::parsed::TreeType, BuildTarget};
 use sway_types::{ConfigTimeConstant, Span};
 
@@ -25,6 +43,14 @@ pub struct TestedPackage {
     pub built: Box<pkg::BuiltPackage>,
     /// The resulting `ProgramState` after executing the test.
     pub tests: Vec<TestResult>,
+    /// The number of tests that were skipped as they did not match the `TestFilter`.
+    pub filtered: usize,
+    /// The tests that were skipped as they are decorated with `#[test(ignore)]`.
+    pub ignored: Vec<IgnoredTest>,
+    /// The source lines executed by the tests, if coverage was enabled.
+    pub coverage: Option<Coverage>,
+    /// Decodes the values logged by the tests and the contract they are testing, if any.
+    pub log_decoder: LogDecoder,
 }
 
 #[derive(Debug)]
@@ -42,6 +68,8 @@ pub struct TestResult {
     pub name: String,
     /// The time taken for the test to execute.
     pub duration: std::time::Duration,
+    /// The gas consumed by the test script, as reported by its `ScriptResult` receipt.
+    pub gas_used: u64,
     /// The span for the function declaring this tests.
     pub span: Span,
     /// The resulting state after executing the test function.
@@ -50,10 +78,91 @@ pub struct TestResult {
     pub condition: pkg::TestPassCondition,
     /// Emitted `Recipt`s during the execution of the test.
     pub logs: Vec<fuel_tx::Receipt>,
+    /// The result of fuzzing the test, if it is a fuzz test.
+    pub fuzz: Option<FuzzResult>,
+    /// The result of verifying the predicate against the data returned by the test, if it is a
+    /// predicate test.
+    pub predicate: Option<PredicateResult>,
+    /// The source locations leading to the revert or panic, if the test did not execute
+    /// successfully.
+    pub backtrace: Option<Backtrace>,
+}
+
+/// Selects the subset of tests to run by matching against the test function name.
+#[derive(Debug, Clone)]
+pub enum TestFilter {
+    /// Run the tests whose name contains the given phrase.
+    Contains(String),
+    /// Run the tests whose name is exactly the given phrase.
+    Exact(String),
+    /// Run the tests whose name matches the given regular expression.
+    Regex(regex::Regex),
+}
+
+/// Whether the tests decorated with `#[test(ignore)]` are run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoredTests {
+    /// Skip the ignored tests.
+    #[default]
+    Skip,
+    /// Run the ignored tests along with all other tests.
+    Include,
+    /// Only run the ignored tests.
+    Only,
+}
+
+/// The number of tests to be run and the number of tests skipped by a `TestFilter` or as they
+/// are ignored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TestCount {
+    pub total: usize,
+    pub filtered: usize,
+    pub ignored: usize,
+}
+
+/// A test declared by a package, as listed by `forc test --list`.
+#[derive(Debug)]
+pub struct ListedTest {
+    /// The name of the package declaring the test.
+    pub package: String,
+    /// The name of the function.
+    pub name: String,
+    /// The names of the modules leading from the package's root module to the module declaring
+    /// the test.
+    pub module_path: Vec<String>,
+    /// The span for the function declaring this test.
+    pub span: Span,
+    /// Whether the test is decorated with `#[test(ignore)]`.
+    pub ignored: bool,
+}
+
+/// A test that was skipped as it is decorated with `#[test(ignore)]`.
+#[derive(Debug)]
+pub struct IgnoredTest {
+    /// The name of the function.
+    pub name: String,
+    /// The span for the function declaring this test.
+    pub span: Span,
+}
+
+/// The number of threads used to execute tests concurrently.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TestRunnerCount {
+    /// Use the given number of threads.
+    Manual(usize),
+    /// Use as many threads as there are available cores.
+    #[default]
+    Auto,
 }
 
 const TEST_METADATA_SEED: u64 = 0x7E57u64;
 
+/// The height of the block that tests are executed within, unless specified by the `TestEnv`.
+///
+/// This is the height of the default `MemoryStorage`, which tests observed before the block
+/// height was configurable.
+const DEFAULT_TEST_BLOCK_HEIGHT: u32 = 1;
+
 /// A package or a workspace that has been built, ready for test execution.
 pub enum BuiltTests {
     Package(PackageTests),
@@ -62,11 +171,12 @@ pub enum BuiltTests {
 
 /// A built package ready for test execution.
 ///
-/// If the built package is a contract, a second built package for the same contract without the
-/// tests are also populated.
+/// If the built package is a contract or a predicate, a second built package for the same
+/// program without the tests are also populated.
 #[derive(Debug)]
 pub enum PackageTests {
     Contract(ContractToTest),
+    Predicate(PredicateToTest),
     NonContract(pkg::BuiltPackage),
 }
 
@@ -80,6 +190,16 @@ pub struct ContractToTest {
     pub tests_excluded: pkg::BuiltPackage,
 }
 
+/// A built predicate ready for test execution.
+///
+/// `tests_included` is the built pkg with the `--test` flag, whose tests provide predicate data.
+/// `tests_excluded` is the built pkg without the `--test` flag, which is the predicate verified.
+#[derive(Debug)]
+pub struct PredicateToTest {
+    pub tests_included: pkg::BuiltPackage,
+    pub tests_excluded: pkg::BuiltPackage,
+}
+
 /// The set of options provided to the `test` function.
 #[derive(Default, Clone)]
 pub struct Opts {
@@ -110,29 +230,57 @@ pub struct TestPrintOpts {
     pub print_logs: bool,
 }
 
-/// The storage and the contract id (if a contract is being tested) for a test.
-#[derive(Debug)]
+/// The storage and the ids of the contracts deployed to it for a test.
+#[derive(Debug, Clone)]
 struct TestSetup {
     storage: vm::storage::MemoryStorage,
-    contract_id: Option<tx::ContractId>,
+    contract_ids: Vec<tx::ContractId>,
+}
+
+/// A contract deployed to the storage in which the tests of a package are executed.
+struct TestContract<'a> {
+    contract_id: tx::ContractId,
+    salt: tx::Salt,
+    built_pkg: &'a BuiltPackage,
+}
+
+/// The outcome of a single execution of a test.
+#[derive(Debug)]
+struct ExecutedTest {
+    state: vm::state::ProgramState,
+    duration: std::time::Duration,
+    receipts: Vec<fuel_tx::Receipt>,
+    /// The instructions executed by the test, if coverage is enabled.
+    hits: Option<InstructionHits>,
+    /// The failing instruction and the contract calls leading to it, if the test reverted or
+    /// panicked.
+    revert_pcs: Option<Vec<ProgramCounter>>,
 }
 
 impl BuiltTests {
     /// Constructs a `PackageTests` from `Built`.
     ///
-    /// Contracts are already compiled once without tests included to do `CONTRACT_ID` injection. `built_contracts` map holds already compiled contracts so that they can be matched with their "tests included" version.
+    /// Contracts are already compiled once without tests included to do `CONTRACT_ID` injection,
+    /// and predicates are compiled once without tests included to be verified by predicate tests.
+    /// `built_without_tests` map holds these already compiled packages so that they can be matched
+    /// with their "tests included" version.
     pub(crate) fn from_built(
         built: Built,
-        built_contracts: HashMap<pkg::Pinned, BuiltPackage>,
+        built_without_tests: HashMap<pkg::Pinned, BuiltPackage>,
     ) -> anyhow::Result<BuiltTests> {
         let built = match built {
-            Built::Package(built_pkg) => {
-                BuiltTests::Package(PackageTests::from_built_pkg(*built_pkg, &built_contracts)?)
-            }
+            Built::Package(built_pkg) => BuiltTests::Package(PackageTests::from_built_pkg(
+                *built_pkg,
+                &built_without_tests,
+            )?),
             Built::Workspace(built_workspace) => {
-                let pkg_tests = built_workspace
-                    .into_values()
-                    .map(|built_pkg| PackageTests::from_built_pkg(built_pkg, &built_contracts))
+                // Sort the members by name so that the test results are reported in a
+                // deterministic order.
+                let mut built_pkgs: Vec<_> = built_workspace.into_values().collect();
+                built_pkgs.sort_by(|a, b| a.pkg_name.cmp(&b.pkg_name));
+                let pkg_tests = built_pkgs
+                    .into_iter()
+                    .map(|built_pkg| PackageTests::from_built_pkg(built_pkg, &built_without_tests))
                     .collect::<anyhow::Result<_>>()?;
                 BuiltTests::Workspace(pkg_tests)
             }
@@ -144,27 +292,29 @@ impl BuiltTests {
 impl<'a> PackageTests {
     /// Return a reference to the underlying `BuiltPackage`.
     ///
-    /// If this `PackageTests` is `PackageTests::Contract`, built package with tests included is
-    /// returned.
+    /// If this `PackageTests` is `PackageTests::Contract` or `PackageTests::Predicate`, built
+    /// package with tests included is returned.
     pub(crate) fn built_pkg_with_tests(&'a self) -> &'a BuiltPackage {
         match self {
             PackageTests::Contract(contract) => &contract.tests_included,
+            PackageTests::Predicate(predicate) => &predicate.tests_included,
             PackageTests::NonContract(non_contract) => non_contract,
         }
     }
 
     /// Construct a `PackageTests` from `BuiltPackage`.
     ///
-    /// If the `BuiltPackage` is a contract, match the contract with the contract's
+    /// If the `BuiltPackage` is a contract or a predicate, match it with its version built without
+    /// tests.
     fn from_built_pkg(
         built_pkg: BuiltPackage,
-        built_contracts: &HashMap<pkg::Pinned, BuiltPackage>,
+        built_without_tests: &HashMap<pkg::Pinned, BuiltPackage>,
     ) -> anyhow::Result<PackageTests> {
         let tree_type = &built_pkg.tree_type;
         let package_test = match tree_type {
             sway_core::language::parsed::TreeType::Contract => {
                 let built_pkg_descriptor = &built_pkg.built_pkg_descriptor;
-                let built_contract_without_tests = built_contracts
+                let built_contract_without_tests = built_without_tests
                     .get(&built_pkg_descriptor.pinned)
                     .ok_or_else(|| anyhow::anyhow!("missing built contract without tests"))?;
                 let contract_to_test = ContractToTest {
@@ -173,29 +323,196 @@ impl<'a> PackageTests {
                 };
                 PackageTests::Contract(contract_to_test)
             }
+            sway_core::language::parsed::TreeType::Predicate => {
+                let built_pkg_descriptor = &built_pkg.built_pkg_descriptor;
+                let built_predicate_without_tests = built_without_tests
+                    .get(&built_pkg_descriptor.pinned)
+                    .ok_or_else(|| anyhow::anyhow!("missing built predicate without tests"))?;
+                let predicate_to_test = PredicateToTest {
+                    tests_included: built_pkg,
+                    tests_excluded: built_predicate_without_tests.clone(),
+                };
+                PackageTests::Predicate(predicate_to_test)
+            }
             _ => PackageTests::NonContract(built_pkg),
         };
         Ok(package_test)
     }
 
-    /// Run all tests for this package and collect their results.
-    pub(crate) fn run_tests(&self) -> anyhow::Result<TestedPackage> {
+    /// Iterate over the test entries of this package.
+    fn test_entries(&self) -> impl Iterator<Item = (&PkgEntry, &PkgTestEntry)> {
         // TODO: Remove this once https://github.com/FuelLabs/sway/issues/3947 is solved.
         let mut visited_tests = HashSet::new();
-        let pkg_with_tests = self.built_pkg_with_tests();
-        // TODO: We can easily parallelise this, but let's wait until testing is stable first.
-        let tests = pkg_with_tests
+        self.built_pkg_with_tests()
             .entries
             .iter()
             .filter_map(|entry| entry.kind.test().map(|test| (entry, test)))
-            .filter(|(_, test_entry)| visited_tests.insert(&test_entry.span))
+            .filter(move |(_, test_entry)| visited_tests.insert(&test_entry.span))
+    }
+
+    /// The test entries of this package selected by the given filter and the treatment of ignored
+    /// tests, along with the number of tests that are to be run and skipped.
+    ///
+    /// Tests are only counted as ignored if they match the filter. When only running ignored
+    /// tests, all other tests are counted as filtered.
+    fn selected_test_entries(
+        &self,
+        test_filter: Option<&TestFilter>,
+        ignored: IgnoredTests,
+    ) -> (Vec<(&PkgEntry, &PkgTestEntry)>, TestCount) {
+        let (test_entries, _, count) = self.partition_test_entries(test_filter, ignored);
+        (test_entries, count)
+    }
+
+    /// The same as [Self::selected_test_entries], but also returns the test entries that are
+    /// skipped as they are ignored.
+    #[allow(clippy::type_complexity)]
+    fn partition_test_entries(
+        &self,
+        test_filter: Option<&TestFilter>,
+        ignored: IgnoredTests,
+    ) -> (
+        Vec<(&PkgEntry, &PkgTestEntry)>,
+        Vec<(&PkgEntry, &PkgTestEntry)>,
+        TestCount,
+    ) {
+        let mut count = TestCount::default();
+        let mut ignored_entries = vec![];
+        let test_entries: Vec<_> = self
+            .test_entries()
+            .filter(|&(entry, test_entry)| {
+                let matches =
+                    test_filter.map_or(true, |filter| filter.matches(&entry.finalized.fn_name));
+                match (matches, ignored, test_entry.ignored) {
+                    (false, _, _) | (true, IgnoredTests::Only, false) => count.filtered += 1,
+                    (true, IgnoredTests::Skip, true) => ignored_entries.push((entry, test_entry)),
+                    _ => return true,
+                }
+                false
+            })
+            .collect();
+        count.total = test_entries.len();
+        count.ignored = ignored_entries.len();
+        (test_entries, ignored_entries, count)
+    }
+
+    /// The number of tests in this package that are to be run and skipped.
+    fn test_count(&self, test_filter: Option<&TestFilter>, ignored: IgnoredTests) -> TestCount {
+        self.selected_test_entries(test_filter, ignored).1
+    }
+
+    /// The tests of this package matching the given filter.
+    fn list_tests(
+        &self,
+        test_filter: Option<&TestFilter>,
+        ignored: IgnoredTests,
+    ) -> Vec<ListedTest> {
+        let package = &self.built_pkg_with_tests().pkg_name;
+        let listed = match ignored {
+            // Ignored tests are listed too, as they are marked as such.
+            IgnoredTests::Skip => IgnoredTests::Include,
+            ignored => ignored,
+        };
+        self.selected_test_entries(test_filter, listed)
+            .0
+            .into_iter()
+            .map(|(entry, test_entry)| ListedTest {
+                package: package.clone(),
+                name: entry.finalized.fn_name.clone(),
+                module_path: test_entry.module_path.clone(),
+                span: test_entry.span.clone(),
+                ignored: test_entry.ignored,
+            })
+            .collect()
+    }
+
+    /// Run all tests selected by the given filter and the treatment of ignored tests for this
+    /// package and collect their results.
+    pub(crate) fn run_tests(
+        &self,
+        test_filter: Option<&TestFilter>,
+        ignored: IgnoredTests,
+        fuzz_opts: &FuzzOpts,
+        coverage_opts: Option<&CoverageOpts>,
+    ) -> anyhow::Result<TestedPackage> {
+        let pkg_with_tests = self.built_pkg_with_tests();
+        let source_maps = self.source_maps();
+        let pkg_env = pkg_with_tests
+            .built_pkg_descriptor
+            .manifest_file
+            .test
+            .clone()
+            .unwrap_or_default();
+        let (test_entries, ignored_entries, count) =
+            self.partition_test_entries(test_filter, ignored);
+        // Each test executes within its own interpreter over its own storage, so tests are run
+        // concurrently. Collecting the results preserves the declaration order of the tests.
+        let (tests, hits): (Vec<_>, Vec<_>) = test_entries
+            .into_par_iter()
             .map(|(entry, test_entry)| {
                 let offset = u32::try_from(entry.finalized.imm)
                     .expect("test instruction offset out of range");
                 let name = entry.finalized.fn_name.clone();
-                let test_setup = self.setup()?;
-                let (state, duration, receipts) =
-                    exec_test(&pkg_with_tests.bytecode, offset, test_setup);
+                let condition = test_entry.pass_condition.clone();
+                let env = pkg_env.with_overrides(&test_entry.env);
+                let test_setup = self.setup(&env)?;
+                let exec = |script_data, test_setup| {
+                    exec_test(
+                        &pkg_with_tests.bytecode,
+                        offset,
+                        script_data,
+                        test_setup,
+                        &env,
+                        coverage_opts.is_some(),
+                    )
+                };
+                let predicate_bytecode = match (test_entry.predicate, self) {
+                    (None, _) => None,
+                    (Some(_), PackageTests::Predicate(predicate)) => {
+                        Some(&predicate.tests_excluded.bytecode)
+                    }
+                    (Some(_), _) => anyhow::bail!(
+                        "test {name} is a predicate test, but {} is not a predicate",
+                        pkg_with_tests.pkg_name
+                    ),
+                };
+                let (executed, fuzz) = match &test_entry.fuzz_params {
+                    Some(fuzz_params) => {
+                        let (fuzz_result, executed) = fuzz::fuzz_test(
+                            fuzz_params,
+                            fuzz_opts,
+                            exec,
+                            &test_setup,
+                            |executed| condition.passed(&executed.state),
+                        )
+                        .map_err(|e| anyhow::anyhow!("failed to fuzz test {name}: {e}"))?;
+                        (executed, Some(fuzz_result))
+                    }
+                    None => (exec(vec![], test_setup)?, None),
+                };
+                let ExecutedTest {
+                    state,
+                    duration,
+                    receipts,
+                    hits,
+                    revert_pcs,
+                } = executed;
+
+                let gas_used = script_gas_used(&receipts);
+
+                // The predicate is only verified if the test returned its data successfully.
+                let predicate = match (test_entry.predicate, predicate_bytecode) {
+                    (Some(expected), Some(bytecode)) if revert_code(&state).is_none() => {
+                        let predicate_data = predicate::predicate_data(&receipts);
+                        Some(predicate::verify_predicate(
+                            bytecode,
+                            predicate_data,
+                            &env,
+                            expected,
+                        )?)
+                    }
+                    _ => None,
+                };
 
                 // Only retain `Log` and `LogData` receipts.
                 let logs = receipts
@@ -207,40 +524,136 @@ impl<'a> PackageTests {
                     .collect();
 
                 let span = test_entry.span.clone();
-                let condition = test_entry.pass_condition.clone();
-                Ok(TestResult {
+                let test_result = TestResult {
                     name,
                     duration,
+                    gas_used,
                     span,
                     state,
                     condition,
                     logs,
-                })
+                    fuzz,
+                    predicate,
+                    backtrace: revert_pcs.map(|pcs| Backtrace::symbolicate(&pcs, &source_maps)),
+                };
+                Ok((test_result, hits))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let coverage = coverage_opts
+            .map(|coverage_opts| {
+                let mut total_hits = InstructionHits::default();
+                hits.iter()
+                    .flatten()
+                    .for_each(|hits| total_hits.merge(hits));
+                self.coverage(&total_hits, coverage_opts)
             })
-            .collect::<anyhow::Result<_>>()?;
+            .transpose()?;
         let tested_pkg = TestedPackage {
             built: Box::new(pkg_with_tests.clone()),
             tests,
+            filtered: count.filtered,
+            ignored: ignored_entries
+                .into_iter()
+                .map(|(entry, test_entry)| IgnoredTest {
+                    name: entry.finalized.fn_name.clone(),
+                    span: test_entry.span.clone(),
+                })
+                .collect(),
+            coverage,
+            log_decoder: self.log_decoder()?,
         };
         Ok(tested_pkg)
     }
 
-    /// Setup the storage for a test and returns a contract id for testing contracts.
+    /// Construct a decoder for the values logged by this package's tests.
     ///
-    /// For testing contracts, storage returned from this function contains the deployed contract.
-    /// For other types, default storage is returned.
-    fn setup(&self) -> anyhow::Result<TestSetup> {
-        match self {
-            PackageTests::Contract(contract_to_test) => {
-                let contract_pkg_without_tests = contract_to_test.tests_excluded.clone();
-                let test_setup = deploy_test_contract(contract_pkg_without_tests)?;
-                Ok(test_setup)
+    /// The values logged by calls into the contract under test and into contract dependencies
+    /// are decoded too.
+    fn log_decoder(&self) -> anyhow::Result<LogDecoder> {
+        let mut log_decoder = LogDecoder::new(&self.built_pkg_with_tests().json_abi_program)?;
+        for contract in self.contracts() {
+            let abi = &contract.built_pkg.json_abi_program;
+            log_decoder.insert_program(contract.contract_id, abi)?;
+        }
+        Ok(log_decoder)
+    }
+
+    /// Map the instructions executed by this package's tests back to the lines of its source.
+    ///
+    /// The instructions executed by calls into deployed contracts are included.
+    fn coverage(&self, hits: &InstructionHits, opts: &CoverageOpts) -> anyhow::Result<Coverage> {
+        let pkg_dir = self
+            .built_pkg_with_tests()
+            .built_pkg_descriptor
+            .manifest_file
+            .dir();
+        Coverage::from_hits(&self.source_maps(), hits, |path| {
+            opts.include_dependencies || path.starts_with(pkg_dir)
+        })
+    }
+
+    /// The source maps of the programs executed by this package's tests.
+    ///
+    /// This includes the source maps of the contract under test and of contract dependencies.
+    fn source_maps(&self) -> Vec<ProgramSourceMap<'_>> {
+        let script = ProgramSourceMap {
+            contract_id: None,
+            source_map: &self.built_pkg_with_tests().source_map,
+        };
+        let contracts = self
+            .contracts()
+            .into_iter()
+            .map(|contract| ProgramSourceMap {
+                contract_id: Some(contract.contract_id),
+                source_map: &contract.built_pkg.source_map,
+            });
+        std::iter::once(script).chain(contracts).collect()
+    }
+
+    /// The contracts deployed before executing this package's tests.
+    ///
+    /// For contracts, this is the contract under test followed by its contract dependencies.
+    /// The contract dependencies of those dependencies are included too. Each contract is only
+    /// deployed once, even if it is depended upon more than once with the same salt.
+    fn contracts(&self) -> Vec<TestContract<'_>> {
+        let mut contracts = vec![];
+        if let PackageTests::Contract(contract_to_test) = self {
+            let built_pkg = &contract_to_test.tests_excluded;
+            contracts.push(TestContract {
+                contract_id: pkg::contract_id(built_pkg, &tx::Salt::zeroed()),
+                salt: tx::Salt::zeroed(),
+                built_pkg,
+            });
+        }
+        let mut deps: Vec<_> = self
+            .built_pkg_with_tests()
+            .contract_dependencies
+            .iter()
+            .collect();
+        while let Some(dep) = deps.pop() {
+            let contract_id = pkg::contract_id(&dep.built, &dep.salt);
+            if contracts.iter().any(|c| c.contract_id == contract_id) {
+                continue;
             }
-            PackageTests::NonContract(_) => Ok(TestSetup {
-                storage: vm::storage::MemoryStorage::default(),
-                contract_id: None,
-            }),
+            contracts.push(TestContract {
+                contract_id,
+                salt: dep.salt,
+                built_pkg: &dep.built,
+            });
+            deps.extend(&dep.built.contract_dependencies);
         }
+        contracts
+    }
+
+    /// Setup the storage for a test and return the ids of the contracts deployed to it.
+    ///
+    /// The storage returned from this function contains the contract under test, if any, and
+    /// all contract dependencies of the package, at the block height determined by `env`.
+    fn setup(&self, env: &pkg::TestEnv) -> anyhow::Result<TestSetup> {
+        let block_height = env.block_height.unwrap_or(DEFAULT_TEST_BLOCK_HEIGHT);
+        deploy_test_contracts(&self.contracts(), block_height)
     }
 }
 
@@ -273,25 +686,61 @@ impl Opts {
 
 impl TestResult {
     /// Whether or not the test passed.
+    ///
+    /// A predicate test only passes if the predicate accepted or rejected the data returned by the
+    /// test as expected.
     pub fn passed(&self) -> bool {
-        match &self.condition {
-            TestPassCondition::ShouldRevert => {
-                matches!(self.state, vm::state::ProgramState::Revert(_))
-            }
-            TestPassCondition::ShouldNotRevert => {
-                !matches!(self.state, vm::state::ProgramState::Revert(_))
-            }
-        }
+        self.condition.passed(&self.state)
+            && self
+                .predicate
+                .as_ref()
+                .map_or(true, PredicateResult::passed)
+    }
+
+    /// The revert code of the test if it reverted.
+    pub fn revert_code(&self) -> Option<u64> {
+        revert_code(&self.state)
+    }
+
+    /// Return `TestDetails` from the span of the function declaring this test.
+    pub fn details(&self) -> anyhow::Result<TestDetails> {
+        TestDetails::from_span(&self.span)
+    }
+}
+
+impl ListedTest {
+    /// The path of the test function qualified by its package and module, e.g.
+    /// `my_pkg::tests::test_add`.
+    pub fn path(&self) -> String {
+        std::iter::once(&self.package)
+            .chain(&self.module_path)
+            .chain(std::iter::once(&self.name))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Return `TestDetails` from the span of the function declaring this test.
+    pub fn details(&self) -> anyhow::Result<TestDetails> {
+        TestDetails::from_span(&self.span)
     }
+}
 
+impl IgnoredTest {
     /// Return `TestDetails` from the span of the function declaring this test.
     pub fn details(&self) -> anyhow::Result<TestDetails> {
-        let file_path = self
-            .span
+        TestDetails::from_span(&self.span)
+    }
+}
+
+impl TestDetails {
+    /// Locate the function declaring a test from its span.
+    fn from_span(span: &Span) -> anyhow::Result<Self> {
+        let file_path = span
             .path()
             .ok_or_else(|| anyhow::anyhow!("Missing span for test function"))?
             .to_owned();
-        let span_start = self.span.start();
+        let span_start = span.start();
         let file_str = fs::read_to_string(&*file_path)?;
         let line_number = file_str[..span_start]
             .chars()
@@ -305,32 +754,175 @@ impl TestResult {
     }
 }
 
+/// Extends `TestPassCondition` with checking whether a test's resulting state passes the test.
+trait PassCondition {
+    fn passed(&self, state: &vm::state::ProgramState) -> bool;
+}
+
+impl PassCondition for TestPassCondition {
+    fn passed(&self, state: &vm::state::ProgramState) -> bool {
+        match self {
+            TestPassCondition::ShouldRevert(None) => revert_code(state).is_some(),
+            TestPassCondition::ShouldRevert(Some(expected)) => {
+                revert_code(state) == Some(*expected)
+            }
+            TestPassCondition::ShouldNotRevert => revert_code(state).is_none(),
+        }
+    }
+}
+
+/// The gas used by a test script, as reported by its `ScriptResult` receipt.
+fn script_gas_used(receipts: &[tx::Receipt]) -> u64 {
+    receipts
+        .iter()
+        .find_map(|receipt| match receipt {
+            tx::Receipt::ScriptResult { gas_used, .. } => Some(*gas_used),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// The revert code of the program if it reverted.
+fn revert_code(state: &vm::state::ProgramState) -> Option<u64> {
+    match state {
+        vm::state::ProgramState::Revert(revert_code) => Some(*revert_code),
+        _ => None,
+    }
+}
+
+impl TestFilter {
+    /// Construct a `TestFilter` from the given regular expression.
+    pub fn regex(pattern: &str) -> anyhow::Result<Self> {
+        let regex = regex::Regex::new(pattern)
+            .map_err(|e| anyhow::anyhow!("invalid test filter regex {pattern:?}: {e}"))?;
+        Ok(TestFilter::Regex(regex))
+    }
+
+    /// Whether or not the test with the given name should be run.
+    pub fn matches(&self, test_name: &str) -> bool {
+        match self {
+            TestFilter::Contains(phrase) => test_name.contains(phrase.as_str()),
+            TestFilter::Exact(phrase) => test_name == phrase,
+            TestFilter::Regex(regex) => regex.is_match(test_name),
+        }
+    }
+}
+
 impl BuiltTests {
-    /// The total number of tests.
-    pub fn test_count(&self) -> usize {
+    /// The total number of tests to be run and the number of tests skipped by the given filter or
+    /// as they are ignored.
+    pub fn test_count(&self, test_filter: Option<&TestFilter>, ignored: IgnoredTests) -> TestCount {
+        let pkgs: Vec<&PackageTests> = match self {
+            BuiltTests::Package(pkg) => vec![pkg],
+            BuiltTests::Workspace(workspace) => workspace.iter().collect(),
+        };
+        pkgs.iter()
+            .map(|pkg| pkg.test_count(test_filter, ignored))
+            .fold(TestCount::default(), |acc, count| TestCount {
+                total: acc.total + count.total,
+                filtered: acc.filtered + count.filtered,
+                ignored: acc.ignored + count.ignored,
+            })
+    }
+
+    /// List the tests matching the given filter without running them.
+    ///
+    /// Ignored tests are listed unless only ignored tests are requested, in which case only
+    /// these are listed.
+    pub fn list(&self, test_filter: Option<&TestFilter>, ignored: IgnoredTests) -> Vec<ListedTest> {
+        let pkgs: Vec<&PackageTests> = match self {
+            BuiltTests::Package(pkg) => vec![pkg],
+            BuiltTests::Workspace(workspace) => workspace.iter().collect(),
+        };
+        pkgs.iter()
+            .flat_map(|pkg| pkg.list_tests(test_filter, ignored))
+            .collect()
+    }
+
+    /// The total number of benchmarks to be run and the number of benchmarks skipped by the given
+    /// filter.
+    pub fn bench_count(&self, filter: Option<&TestFilter>) -> TestCount {
         let pkgs: Vec<&PackageTests> = match self {
             BuiltTests::Package(pkg) => vec![pkg],
             BuiltTests::Workspace(workspace) => workspace.iter().collect(),
         };
         pkgs.iter()
-            .map(|pkg| {
-                pkg.built_pkg_with_tests()
-                    .entries
-                    .iter()
-                    .filter(|e| e.is_test())
-                    .count()
+            .map(|pkg| pkg.bench_count(filter))
+            .fold(TestCount::default(), |acc, count| TestCount {
+                total: acc.total + count.total,
+                filtered: acc.filtered + count.filtered,
+                ignored: acc.ignored + count.ignored,
             })
-            .sum()
     }
 
-    /// Run all built tests, return the result.
-    pub fn run(self) -> anyhow::Result<Tested> {
-        run_tests(self)
+    /// Run all built benchmarks matching the given filter, return the result.
+    ///
+    /// Benchmarks are executed concurrently on a thread pool with the number of threads
+    /// specified by `test_runner_count`.
+    pub fn bench(
+        self,
+        test_runner_count: TestRunnerCount,
+        filter: Option<&TestFilter>,
+    ) -> anyhow::Result<bench::Benched> {
+        let num_threads = match test_runner_count {
+            TestRunnerCount::Manual(num_threads) => num_threads,
+            TestRunnerCount::Auto => 0,
+        };
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()?;
+        thread_pool.install(|| match self {
+            BuiltTests::Package(pkg) => {
+                let benched_pkg = pkg.run_benches(filter)?;
+                Ok(bench::Benched::Package(Box::new(benched_pkg)))
+            }
+            BuiltTests::Workspace(workspace) => {
+                let benched_pkgs = workspace
+                    .into_par_iter()
+                    .map(|pkg| pkg.run_benches(filter))
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(bench::Benched::Workspace(benched_pkgs))
+            }
+        })
+    }
+
+    /// Run all built tests matching the given filter, return the result.
+    ///
+    /// Tests decorated with `#[test(ignore)]` are skipped, included or exclusively run according
+    /// to `ignored`. Tests are executed concurrently on a thread pool with the number of threads
+    /// specified by `test_runner_count`. Fuzz tests are executed with inputs generated according to
+    /// `fuzz_opts`. If `coverage_opts` are provided, the source lines executed by the tests of
+    /// each package are recorded.
+    pub fn run(
+        self,
+        test_runner_count: TestRunnerCount,
+        test_filter: Option<&TestFilter>,
+        ignored: IgnoredTests,
+        fuzz_opts: FuzzOpts,
+        coverage_opts: Option<CoverageOpts>,
+    ) -> anyhow::Result<Tested> {
+        let num_threads = match test_runner_count {
+            TestRunnerCount::Manual(num_threads) => num_threads,
+            // Letting rayon choose the number of threads defaults to the number of cores.
+            TestRunnerCount::Auto => 0,
+        };
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()?;
+        thread_pool.install(|| {
+            run_tests(
+                self,
+                test_filter,
+                ignored,
+                &fuzz_opts,
+                coverage_opts.as_ref(),
+            )
+        })
     }
 }
 
-/// Build all contracts in the given buld plan without tests.
-fn build_contracts_without_tests(
+/// Build all contracts and predicates in the given buld plan without tests.
+fn build_without_tests(
     opts: &Opts,
     build_plan: &pkg::BuildPlan,
 ) -> Vec<(pkg::Pinned, anyhow::Result<BuiltPackage>)> {
@@ -343,7 +935,12 @@ fn build_contracts_without_tests(
                 .expect("missing manifest for member to test");
             (pinned_pkg, pkg_manifest)
         })
-        .filter(|(_, pkg_manifest)| matches!(pkg_manifest.program_type(), Ok(TreeType::Contract)))
+        .filter(|(_, pkg_manifest)| {
+            matches!(
+                pkg_manifest.program_type(),
+                Ok(TreeType::Contract | TreeType::Predicate)
+            )
+        })
         .map(|(pinned_pkg, pkg_manifest)| {
             let pkg_path = pkg_manifest.dir();
             let build_opts_without_tests = opts
@@ -361,94 +958,107 @@ fn build_contracts_without_tests(
 /// First builds the package or workspace, ready for execution.
 ///
 /// If the workspace contains contracts, those contracts will be built first without tests
-/// in order to determine their `CONTRACT_ID`s and enable contract calling.
+/// in order to determine their `CONTRACT_ID`s and enable contract calling. Predicates are also
+/// built without tests, as this is the predicate verified by predicate tests.
 pub fn build(opts: Opts) -> anyhow::Result<BuiltTests> {
     let build_opts = opts.clone().into_build_opts();
 
     let build_plan = pkg::BuildPlan::from_build_opts(&build_opts)?;
     let mut const_inject_map = HashMap::new();
-    let mut built_contracts = HashMap::new();
-    let built_contracts_without_tests = build_contracts_without_tests(&opts, &build_plan);
-    for (pinned_contract, built_contract) in built_contracts_without_tests {
-        let built_contract = built_contract?;
-        let contract_id = pkg::contract_id(&built_contract, &fuel_tx::Salt::zeroed());
-        built_contracts.insert(pinned_contract.clone(), built_contract);
-
-        // Construct namespace with contract id
-        let contract_id_constant_name = CONTRACT_ID_CONSTANT_NAME.to_string();
-        let contract_id_value = format!("0x{contract_id}");
-        let contract_id_constant = ConfigTimeConstant {
-            r#type: "b256".to_string(),
-            value: contract_id_value.clone(),
-            public: true,
-        };
-        let constant_declarations = vec![(contract_id_constant_name, contract_id_constant)];
-        const_inject_map.insert(pinned_contract, constant_declarations);
+    let mut built_without_tests = HashMap::new();
+    for (pinned_pkg, built_pkg) in build_without_tests(&opts, &build_plan) {
+        let built_pkg = built_pkg?;
+        if matches!(built_pkg.tree_type, TreeType::Contract) {
+            let contract_id = pkg::contract_id(&built_pkg, &fuel_tx::Salt::zeroed());
+
+            // Construct namespace with contract id
+            let contract_id_constant_name = CONTRACT_ID_CONSTANT_NAME.to_string();
+            let contract_id_value = format!("0x{contract_id}");
+            let contract_id_constant = ConfigTimeConstant {
+                r#type: "b256".to_string(),
+                value: contract_id_value.clone(),
+                public: true,
+            };
+            let constant_declarations = vec![(contract_id_constant_name, contract_id_constant)];
+            const_inject_map.insert(pinned_pkg.clone(), constant_declarations);
+        }
+        built_without_tests.insert(pinned_pkg, built_pkg);
     }
 
     // Injection map is collected in the previous pass, we should build the workspace/package with injection map.
     let build_opts_with_injection = build_opts.const_injection_map(const_inject_map);
     let built = pkg::build_with_options(build_opts_with_injection)?;
-    BuiltTests::from_built(built, built_contracts)
-}
-
-/// Deploys the provided contract and returns an interpreter instance ready to be used in test
-/// executions with deployed contract.
-fn deploy_test_contract(built_pkg: BuiltPackage) -> anyhow::Result<TestSetup> {
-    // Obtain the contract id for deployment.
-    let mut storage_slots = built_pkg.storage_slots;
-    storage_slots.sort();
-    let bytecode = built_pkg.bytecode;
-    let contract = tx::Contract::from(bytecode.clone());
-    let root = contract.root();
-    let state_root = tx::Contract::initial_state_root(storage_slots.iter());
-    let salt = tx::Salt::zeroed();
-    let contract_id = contract.id(&salt, &root, &state_root);
+    BuiltTests::from_built(built, built_without_tests)
+}
 
+/// Deploys the provided contracts with their salts and storage slots, and returns the storage
+/// containing them at the given block height, ready to be used in test executions.
+fn deploy_test_contracts(
+    contracts: &[TestContract],
+    block_height: u32,
+) -> anyhow::Result<TestSetup> {
     // Setup the interpreter for deployment.
     let params = tx::ConsensusParameters::default();
-    let storage = vm::storage::MemoryStorage::default();
+    let coinbase = tx::Address::from(*Hasher::hash(b"coinbase"));
+    let storage = vm::storage::MemoryStorage::new(block_height, coinbase);
     let mut interpreter =
         vm::interpreter::Interpreter::with_storage(storage, params, GasCosts::default());
 
-    // Create the deployment transaction.
+    // Create the deployment transactions.
     let mut rng = rand::rngs::StdRng::seed_from_u64(TEST_METADATA_SEED);
+    let mut contract_ids = vec![];
+    for contract in contracts {
+        // Obtain the contract id for deployment.
+        let mut storage_slots = contract.built_pkg.storage_slots.clone();
+        storage_slots.sort();
+        let bytecode = contract.built_pkg.bytecode.clone();
+        let state_root = tx::Contract::initial_state_root(storage_slots.iter());
+        let salt = contract.salt;
+        let contract_id = contract.contract_id;
 
-    // Prepare the transaction metadata.
-    let secret_key = rng.gen();
-    let utxo_id = rng.gen();
-    let amount = 1;
-    let maturity = 1;
-    let asset_id = rng.gen();
-    let tx_pointer = rng.gen();
-    let block_height = (u32::MAX >> 1) as u64;
-
-    let tx = tx::TransactionBuilder::create(bytecode.into(), salt, storage_slots)
-        .add_unsigned_coin_input(secret_key, utxo_id, amount, asset_id, tx_pointer, maturity)
-        .add_output(tx::Output::contract_created(contract_id, state_root))
-        .maturity(maturity)
-        .finalize_checked(block_height, &params, &GasCosts::default());
-
-    // Deploy the contract.
-    interpreter.transact(tx)?;
+        // Prepare the transaction metadata.
+        let secret_key = rng.gen();
+        let utxo_id = rng.gen();
+        let amount = 1;
+        let maturity = 1;
+        let asset_id = rng.gen();
+        let tx_pointer = rng.gen();
+        let block_height = (u32::MAX >> 1) as u64;
+
+        let tx = tx::TransactionBuilder::create(bytecode.into(), salt, storage_slots)
+            .add_unsigned_coin_input(secret_key, utxo_id, amount, asset_id, tx_pointer, maturity)
+            .add_output(tx::Output::contract_created(contract_id, state_root))
+            .maturity(maturity)
+            .finalize_checked(block_height, &params, &GasCosts::default());
+
+        // Deploy the contract.
+        interpreter.transact(tx)?;
+        contract_ids.push(contract_id);
+    }
     let storage_after_deploy = interpreter.as_ref();
     Ok(TestSetup {
         storage: storage_after_deploy.clone(),
-        contract_id: Some(contract_id),
+        contract_ids,
     })
 }
 
 /// Build the given package and run its tests, returning the results.
-fn run_tests(built: BuiltTests) -> anyhow::Result<Tested> {
+fn run_tests(
+    built: BuiltTests,
+    test_filter: Option<&TestFilter>,
+    ignored: IgnoredTests,
+    fuzz_opts: &FuzzOpts,
+    coverage_opts: Option<&CoverageOpts>,
+) -> anyhow::Result<Tested> {
     match built {
         BuiltTests::Package(pkg) => {
-            let tested_pkg = pkg.run_tests()?;
+            let tested_pkg = pkg.run_tests(test_filter, ignored, fuzz_opts, coverage_opts)?;
             Ok(Tested::Package(Box::new(tested_pkg)))
         }
         BuiltTests::Workspace(workspace) => {
             let tested_pkgs = workspace
-                .into_iter()
-                .map(|pkg| pkg.run_tests())
+                .into_par_iter()
+                .map(|pkg| pkg.run_tests(test_filter, ignored, fuzz_opts, coverage_opts))
                 .collect::<anyhow::Result<Vec<TestedPackage>>>()?;
             Ok(Tested::Workspace(tested_pkgs))
         }
@@ -493,41 +1103,66 @@ fn patch_test_bytecode(bytecode: &[u8], test_offset: u32) -> std::borrow::Cow<[u
 }
 
 // Execute the test whose entry point is at the given instruction offset as if it were a script.
+//
+// The test's arguments, if any, are provided via the given ABI encoded script data. The block
+// height, coin inputs and caller of the test transaction are determined by `env`. If
+// `record_hits` is set, the instructions executed by the test are also recorded.
 fn exec_test(
     bytecode: &[u8],
     test_offset: u32,
+    script_input_data: Vec<u8>,
     test_setup: TestSetup,
-) -> (
-    vm::state::ProgramState,
-    std::time::Duration,
-    Vec<fuel_tx::Receipt>,
-) {
+    env: &pkg::TestEnv,
+    record_hits: bool,
+) -> anyhow::Result<ExecutedTest> {
     let storage = test_setup.storage;
-    let contract_id = test_setup.contract_id;
+    let contract_ids = test_setup.contract_ids;
 
     // Patch the bytecode to jump to the relevant test.
     let bytecode = patch_test_bytecode(bytecode, test_offset).into_owned();
 
     // Create a transaction to execute the test function.
-    let script_input_data = vec![];
     let mut rng = rand::rngs::StdRng::seed_from_u64(TEST_METADATA_SEED);
 
     // Prepare the transaction metadata.
-    let secret_key = rng.gen();
-    let utxo_id = rng.gen();
-    let amount = 1;
-    let maturity = 1;
-    let asset_id = rng.gen();
-    let tx_pointer = rng.gen();
-    let block_height = (u32::MAX >> 1) as u64;
+    let secret_key: SecretKey = rng.gen();
+    let block_height = env.block_height.unwrap_or(DEFAULT_TEST_BLOCK_HEIGHT);
+    let maturity = test_tx_maturity(block_height);
+    let caller = env
+        .caller
+        .unwrap_or_else(|| tx::Input::owner(&secret_key.public_key()));
+    let coins = match &env.coins {
+        Some(coins) => coins.clone(),
+        None => vec![pkg::TestCoin {
+            amount: 1,
+            asset_id: rng.gen(),
+        }],
+    };
 
     let params = tx::ConsensusParameters::default();
-    let mut tx = tx::TransactionBuilder::script(bytecode, script_input_data)
-        .add_unsigned_coin_input(secret_key, utxo_id, amount, asset_id, tx_pointer, 0)
-        .gas_limit(tx::ConsensusParameters::DEFAULT.max_gas_per_tx)
-        .maturity(maturity)
-        .clone();
-    if let Some(contract_id) = contract_id {
+    let mut tx = tx::TransactionBuilder::script(bytecode, script_input_data);
+    tx.gas_limit(tx::ConsensusParameters::DEFAULT.max_gas_per_tx)
+        .maturity(maturity.into());
+    // The caller need not correspond to a known key, so the coin inputs are not signed and the
+    // transaction is only checked for validity without its signatures.
+    let coin_count = coins.len();
+    for coin in coins {
+        tx.add_input(tx::Input::coin_signed(
+            rng.gen(),
+            caller,
+            coin.amount,
+            coin.asset_id,
+            rng.gen(),
+            0,
+            0,
+        ));
+    }
+    tx.add_witness(tx::Witness::default());
+    for output in env.outputs.iter().flatten() {
+        tx.add_output(tx::Output::coin(output.to, output.amount, output.asset_id));
+    }
+    let contract_count = contract_ids.len();
+    for (ix, contract_id) in contract_ids.into_iter().enumerate() {
         tx.add_input(tx::Input::Contract {
             utxo_id: tx::UtxoId::new(tx::Bytes32::zeroed(), 0),
             balance_root: tx::Bytes32::zeroed(),
@@ -536,22 +1171,127 @@ fn exec_test(
             contract_id,
         })
         .add_output(tx::Output::Contract {
-            input_index: 1,
+            input_index: u8::try_from(coin_count + ix).map_err(|_| {
+                anyhow::anyhow!(
+                    "invalid test environment: there are too many `coins` to also provide the \
+                    {} deployed contracts as inputs",
+                    contract_count
+                )
+            })?,
             balance_root: fuel_tx::Bytes32::zeroed(),
             state_root: tx::Bytes32::zeroed(),
         });
     }
-    let tx = tx.finalize_checked(block_height, &params, &GasCosts::default());
+    let tx = check_test_tx(&mut tx, block_height, &params)?;
+
+    // Stepping through the test records the executed instructions, but does not produce the
+    // final receipts, so the test is executed separately to determine its result.
+    let hits = record_hits.then(|| exec_test_stepping(tx.clone(), storage.clone(), params));
 
     let mut interpreter =
         vm::interpreter::Interpreter::with_storage(storage, params, GasCosts::default());
 
     // Execute and return the result.
     let start = std::time::Instant::now();
-    let transition = interpreter.transact(tx).unwrap();
+    let transition = interpreter
+        .transact(tx)
+        .map_err(|e| anyhow::anyhow!("failed to execute the test transaction: {e}"))?;
     let duration = start.elapsed();
     let state = *transition.state();
     let receipts = transition.receipts().to_vec();
 
-    (state, duration, receipts)
+    // Recover the location of the failure while the state of the VM is still available.
+    let failed = receipts
+        .iter()
+        .find_map(tx::Receipt::result)
+        .map_or(false, |result| {
+            *result != tx::ScriptExecutionResult::Success
+        });
+    let revert_pcs = failed.then(|| {
+        let registers = interpreter.registers().try_into().unwrap();
+        backtrace::program_counters(registers, interpreter.memory())
+    });
+
+    Ok(ExecutedTest {
+        state,
+        duration,
+        receipts,
+        hits,
+        revert_pcs,
+    })
+}
+
+/// The maturity of a test transaction within a block of the given height.
+///
+/// Test transactions have a maturity of 1, unless they are executed within the genesis block, as
+/// a transaction may not mature after the block that includes it.
+pub(crate) fn test_tx_maturity(block_height: u32) -> u32 {
+    block_height.min(1)
+}
+
+/// Check the validity of a test transaction executed within a block of the given height.
+///
+/// The transaction is described by the `TestEnv` of the test, so the reason it is invalid is
+/// reported in terms of the `[test]` entry responsible.
+pub(crate) fn check_test_tx(
+    tx: &mut tx::TransactionBuilder<tx::Script>,
+    block_height: u32,
+    params: &tx::ConsensusParameters,
+) -> anyhow::Result<Checked<tx::Script>> {
+    tx.finalize()
+        .into_checked_basic(block_height.into(), params)
+        .map_err(|err| {
+            let reason = match err {
+                tx::CheckError::TransactionInputsMax => format!(
+                    "the `coins` exceed the maximum of {} transaction inputs",
+                    params.max_inputs
+                ),
+                tx::CheckError::TransactionOutputsMax => format!(
+                    "the `outputs` exceed the maximum of {} transaction outputs",
+                    params.max_outputs
+                ),
+                tx::CheckError::TransactionOutputCoinAssetIdNotFound(asset_id) => {
+                    format!("the `outputs` send asset 0x{asset_id}, which none of the `coins` hold")
+                }
+                tx::CheckError::InsufficientInputAmount {
+                    asset,
+                    expected,
+                    provided,
+                } => format!(
+                    "the `outputs` send {expected} of asset 0x{asset}, but the `coins` only hold \
+                    {provided}"
+                ),
+                tx::CheckError::ArithmeticOverflow => {
+                    "the amounts of the `coins` or `outputs` overflow".to_string()
+                }
+                err => format!("the test transaction is rejected with {err:?}"),
+            };
+            anyhow::anyhow!("invalid test environment: {reason}")
+        })
+}
+
+// Execute the given test transaction one instruction at a time, recording each instruction
+// executed by the test script and the contracts it calls.
+fn exec_test_stepping(
+    tx: vm::checked_transaction::Checked<tx::Script>,
+    storage: vm::storage::MemoryStorage,
+    params: tx::ConsensusParameters,
+) -> InstructionHits {
+    let mut interpreter =
+        vm::interpreter::Interpreter::with_storage(storage, params, GasCosts::default());
+    interpreter.set_single_stepping(true);
+    let mut hits = InstructionHits::default();
+    let mut state = interpreter
+        .transact(tx)
+        .map(|transition| *transition.state());
+    while let Ok(vm::state::ProgramState::RunProgram(vm::state::DebugEval::Breakpoint(
+        breakpoint,
+    ))) = state
+    {
+        let contract_id = Some(*breakpoint.contract()).filter(|id| *id != tx::ContractId::zeroed());
+        let instruction_ix = breakpoint.pc() as usize / Instruction::SIZE;
+        hits.record(contract_id, instruction_ix);
+        state = interpreter.resume();
+    }
+    hits
 }
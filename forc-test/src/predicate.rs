@@ -0,0 +1,102 @@
+//! Verifying the predicate of a package against the predicate data returned by a test decorated
+//! with `#[test(should_accept)]` or `#[test(should_reject)]`.
+//!
+//! The test function is executed as a script, and the value it returns is provided as the
+//! predicate data of each coin input owned by the predicate within a transaction described by the
+//! test's `TestEnv`. The predicate is then verified using the same checks that a node performs
+//! before accepting the transaction.
+
+use crate::{check_test_tx, test_tx_maturity, DEFAULT_TEST_BLOCK_HEIGHT, TEST_METADATA_SEED};
+use forc_pkg as pkg;
+use fuel_tx as tx;
+use fuel_vm::{self as vm, fuel_asm};
+use rand::{Rng, SeedableRng};
+
+/// The result of verifying the predicate of a package against the data returned by a test.
+#[derive(Debug)]
+pub struct PredicateResult {
+    /// Whether the predicate was expected to accept the transaction.
+    pub expected: bool,
+    /// The gas used by the predicate if it accepted the transaction, or the reason the
+    /// transaction was rejected.
+    pub verified: Result<u64, vm::error::PredicateVerificationFailed>,
+}
+
+impl PredicateResult {
+    /// Whether or not the predicate accepted the transaction.
+    pub fn accepted(&self) -> bool {
+        self.verified.is_ok()
+    }
+
+    /// Whether or not the predicate accepted or rejected the transaction as expected.
+    pub fn passed(&self) -> bool {
+        self.accepted() == self.expected
+    }
+}
+
+/// The predicate data returned by a test function, as found within the receipts of its execution.
+///
+/// Values that fit within a word are returned directly and are encoded as a big-endian word,
+/// while larger values are returned by reference and are used as is.
+pub(crate) fn predicate_data(receipts: &[tx::Receipt]) -> Vec<u8> {
+    receipts
+        .iter()
+        .find_map(|receipt| match receipt {
+            tx::Receipt::Return { val, .. } => Some(val.to_be_bytes().to_vec()),
+            tx::Receipt::ReturnData { data, .. } => Some(data.clone()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Verify the given predicate bytecode against a transaction spending the coins of `env` from the
+/// predicate, with the given predicate data.
+pub(crate) fn verify_predicate(
+    predicate: &[u8],
+    predicate_data: Vec<u8>,
+    env: &pkg::TestEnv,
+    expected: bool,
+) -> anyhow::Result<PredicateResult> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(TEST_METADATA_SEED);
+    let block_height = env.block_height.unwrap_or(DEFAULT_TEST_BLOCK_HEIGHT);
+    let maturity = test_tx_maturity(block_height);
+    let owner = tx::Input::predicate_owner(predicate);
+    let coins = match &env.coins {
+        Some(coins) => coins.clone(),
+        None => vec![pkg::TestCoin {
+            amount: 1,
+            asset_id: rng.gen(),
+        }],
+    };
+
+    // The script of the transaction is irrelevant to the predicate, so it simply returns.
+    let script = fuel_asm::op::ret(fuel_asm::RegId::ONE).to_bytes().to_vec();
+    let params = tx::ConsensusParameters::default();
+    let mut tx = tx::TransactionBuilder::script(script, vec![]);
+    tx.gas_limit(tx::ConsensusParameters::DEFAULT.max_gas_per_tx)
+        .maturity(maturity.into());
+    for coin in coins {
+        tx.add_input(tx::Input::coin_predicate(
+            rng.gen(),
+            owner,
+            coin.amount,
+            coin.asset_id,
+            rng.gen(),
+            0,
+            predicate.to_vec(),
+            predicate_data.clone(),
+        ));
+    }
+    for output in env.outputs.iter().flatten() {
+        tx.add_output(tx::Output::coin(output.to, output.amount, output.asset_id));
+    }
+    let tx = check_test_tx(&mut tx, block_height, &params)?;
+
+    let verified = vm::interpreter::Interpreter::<vm::storage::PredicateStorage>::check_predicates(
+        tx,
+        params,
+        vm::gas::GasCosts::default(),
+    )
+    .map(|checked| checked.gas_used());
+    Ok(PredicateResult { expected, verified })
+}
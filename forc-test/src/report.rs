@@ -0,0 +1,401 @@
+//! Machine-readable reports of test results, for consumption by CI systems and other tooling.
+//!
+//! A `TestReport` is collected from the results of a `forc test` invocation and may be written in
+//! either of the supported `ReportFormat`s.
+
+use crate::{IgnoredTest, TestResult, Tested, TestedPackage};
+use anyhow::{bail, Result};
+use forc_util::abi_decode::LogDecoder;
+use serde::Serialize;
+use std::{fmt::Write, fs, path::Path, str::FromStr};
+
+/// The supported formats for writing a `TestReport`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A JSON document containing the results of each package's tests.
+    Json,
+    /// A JUnit XML document with a `testsuite` per package and a `testcase` per test that was run
+    /// or ignored.
+    Junit,
+}
+
+/// The results of all tests within a tested package or workspace.
+#[derive(Debug, Clone, Serialize)]
+pub struct TestReport {
+    pub packages: Vec<PackageReport>,
+}
+
+/// The results of all tests within a single package.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageReport {
+    /// The name of the package.
+    pub name: String,
+    /// The number of tests that passed.
+    pub passed: usize,
+    /// The number of tests that failed.
+    pub failed: usize,
+    /// The number of tests that were skipped as they did not match the test filter.
+    pub filtered: usize,
+    /// The tests that were skipped as they are decorated with `#[test(ignore)]`.
+    pub ignored: Vec<IgnoredTestReport>,
+    pub tests: Vec<TestCaseReport>,
+}
+
+/// A test that was skipped as it is decorated with `#[test(ignore)]`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IgnoredTestReport {
+    /// The name of the test function.
+    pub name: String,
+    /// The name of the package declaring the test.
+    pub package: String,
+    /// The file that contains the test function.
+    pub file: String,
+    /// The line number for the test declaration.
+    pub line: usize,
+}
+
+/// The result of a single test.
+#[derive(Debug, Clone, Serialize)]
+pub struct TestCaseReport {
+    /// The name of the test function.
+    pub name: String,
+    /// The name of the package declaring the test.
+    pub package: String,
+    /// The file that contains the test function.
+    pub file: String,
+    /// The line number for the test declaration.
+    pub line: usize,
+    pub passed: bool,
+    /// The gas consumed by the test.
+    pub gas_used: u64,
+    /// The time taken for the test to execute, in seconds.
+    pub duration_secs: f64,
+    /// The revert code of the test if it reverted.
+    pub revert_code: Option<u64>,
+    /// A description of why the test failed, if it did.
+    pub failure: Option<String>,
+    /// The `Log` and `LogData` receipts emitted by the test, along with their decoded values.
+    pub logs: serde_json::Value,
+}
+
+impl TestReport {
+    /// Collect the report for the given test results.
+    pub fn from_tested(tested: &Tested) -> Result<Self> {
+        let pkgs: Vec<&TestedPackage> = match tested {
+            Tested::Package(pkg) => vec![pkg],
+            Tested::Workspace(pkgs) => pkgs.iter().collect(),
+        };
+        let packages = pkgs
+            .into_iter()
+            .map(PackageReport::from_tested_pkg)
+            .collect::<Result<_>>()?;
+        Ok(TestReport { packages })
+    }
+
+    /// Render the report in the given format.
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ReportFormat::Junit => Ok(self.to_junit()),
+        }
+    }
+
+    /// Write the report in the given format to the file at the given path.
+    pub fn write(&self, format: ReportFormat, path: &Path) -> Result<()> {
+        let contents = self.render(format)?;
+        fs::write(path, contents)
+            .map_err(|e| anyhow::anyhow!("failed to write test report {}: {e}", path.display()))
+    }
+
+    /// Render the report as a JUnit XML document.
+    fn to_junit(&self) -> String {
+        let tests: usize = self.packages.iter().map(PackageReport::junit_tests).sum();
+        let failures: usize = self.packages.iter().map(|pkg| pkg.failed).sum();
+        let time: f64 = self.packages.iter().map(PackageReport::duration_secs).sum();
+
+        // Writing to a `String` cannot fail.
+        let mut xml = String::new();
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(
+            xml,
+            r#"<testsuites name="forc test" tests="{tests}" failures="{failures}" time="{time}">"#
+        )
+        .unwrap();
+        for pkg in &self.packages {
+            writeln!(
+                xml,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" skipped="{}" time="{}">"#,
+                escape_xml(&pkg.name),
+                pkg.junit_tests(),
+                pkg.failed,
+                pkg.ignored.len(),
+                pkg.duration_secs(),
+            )
+            .unwrap();
+            for test in &pkg.tests {
+                test.write_junit(&mut xml);
+            }
+            for test in &pkg.ignored {
+                test.write_junit(&mut xml);
+            }
+            writeln!(xml, "  </testsuite>").unwrap();
+        }
+        writeln!(xml, "</testsuites>").unwrap();
+        xml
+    }
+}
+
+impl PackageReport {
+    fn from_tested_pkg(pkg: &TestedPackage) -> Result<Self> {
+        let name = pkg.built.pkg_name.clone();
+        let tests: Vec<_> = pkg
+            .tests
+            .iter()
+            .map(|test| TestCaseReport::from_test_result(test, &name, &pkg.log_decoder))
+            .collect::<Result<_>>()?;
+        let ignored = pkg
+            .ignored
+            .iter()
+            .map(|test| IgnoredTestReport::from_ignored_test(test, &name))
+            .collect::<Result<_>>()?;
+        let passed = tests.iter().filter(|test| test.passed).count();
+        Ok(PackageReport {
+            name,
+            passed,
+            failed: tests.len() - passed,
+            filtered: pkg.filtered,
+            ignored,
+            tests,
+        })
+    }
+
+    /// The number of `testcase`s within the package's JUnit `testsuite`, which excludes the
+    /// filtered tests as they are not reported.
+    fn junit_tests(&self) -> usize {
+        self.tests.len() + self.ignored.len()
+    }
+
+    /// The total time taken by the package's tests, in seconds.
+    fn duration_secs(&self) -> f64 {
+        self.tests.iter().map(|test| test.duration_secs).sum()
+    }
+}
+
+impl TestCaseReport {
+    fn from_test_result(
+        test: &TestResult,
+        package: &str,
+        log_decoder: &LogDecoder,
+    ) -> Result<Self> {
+        let details = test.details()?;
+        let passed = test.passed();
+        Ok(TestCaseReport {
+            name: test.name.clone(),
+            package: package.to_string(),
+            file: details.file_path.display().to_string(),
+            line: details.line_number,
+            passed,
+            gas_used: test.gas_used,
+            duration_secs: test.duration.as_secs_f64(),
+            revert_code: test.revert_code(),
+            failure: (!passed).then(|| failure_message(test)),
+            logs: forc_util::log_receipts_to_json(&test.logs, log_decoder)?,
+        })
+    }
+
+    fn write_junit(&self, xml: &mut String) {
+        writeln!(
+            xml,
+            r#"    <testcase name="{}" classname="{}" file="{}" line="{}" time="{}">"#,
+            escape_xml(&self.name),
+            escape_xml(&self.package),
+            escape_xml(&self.file),
+            self.line,
+            self.duration_secs,
+        )
+        .unwrap();
+        writeln!(xml, "      <properties>").unwrap();
+        let mut properties = vec![("gas_used", self.gas_used.to_string())];
+        if let Some(revert_code) = self.revert_code {
+            properties.push(("revert_code", revert_code.to_string()));
+        }
+        for (name, value) in properties {
+            writeln!(xml, r#"        <property name="{name}" value="{value}"/>"#).unwrap();
+        }
+        writeln!(xml, "      </properties>").unwrap();
+        if let Some(failure) = &self.failure {
+            writeln!(xml, r#"      <failure message="{}"/>"#, escape_xml(failure)).unwrap();
+        }
+        if self.logs.as_array().map_or(false, |logs| !logs.is_empty()) {
+            writeln!(
+                xml,
+                "      <system-out>{}</system-out>",
+                escape_xml(&self.logs.to_string())
+            )
+            .unwrap();
+        }
+        writeln!(xml, "    </testcase>").unwrap();
+    }
+}
+
+impl IgnoredTestReport {
+    fn from_ignored_test(test: &IgnoredTest, package: &str) -> Result<Self> {
+        let details = test.details()?;
+        Ok(IgnoredTestReport {
+            name: test.name.clone(),
+            package: package.to_string(),
+            file: details.file_path.display().to_string(),
+            line: details.line_number,
+        })
+    }
+
+    fn write_junit(&self, xml: &mut String) {
+        writeln!(
+            xml,
+            r#"    <testcase name="{}" classname="{}" file="{}" line="{}" time="0">"#,
+            escape_xml(&self.name),
+            escape_xml(&self.package),
+            escape_xml(&self.file),
+            self.line,
+        )
+        .unwrap();
+        writeln!(xml, "      <skipped/>").unwrap();
+        writeln!(xml, "    </testcase>").unwrap();
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let format = match s {
+            "json" => ReportFormat::Json,
+            "junit" => ReportFormat::Junit,
+            _ => bail!("invalid test report format {s:?}, expected one of: json, junit"),
+        };
+        Ok(format)
+    }
+}
+
+/// Describe why the given failing test failed.
+fn failure_message(test: &TestResult) -> String {
+    let mut message = match (&test.condition, test.revert_code()) {
+        (forc_pkg::TestPassCondition::ShouldNotRevert, Some(code)) => {
+            format!("test reverted with code {code} ({code:#x})")
+        }
+        (forc_pkg::TestPassCondition::ShouldRevert(None), _) => "test did not revert".to_string(),
+        (forc_pkg::TestPassCondition::ShouldRevert(Some(expected)), Some(actual)) => {
+            format!("expected revert code {expected} ({expected:#x}), found {actual} ({actual:#x})")
+        }
+        (forc_pkg::TestPassCondition::ShouldRevert(Some(expected)), None) => {
+            format!("expected revert code {expected} ({expected:#x}), but the test did not revert")
+        }
+        (forc_pkg::TestPassCondition::ShouldNotRevert, None) => match &test.predicate {
+            Some(predicate) => match &predicate.verified {
+                Ok(_) => "predicate accepted the transaction, expected rejection".to_string(),
+                Err(e) => format!("predicate rejected the transaction: {e}"),
+            },
+            None => "test failed".to_string(),
+        },
+    };
+    if let Some(counterexample) = test
+        .fuzz
+        .as_ref()
+        .and_then(|fuzz| fuzz.counterexample.as_ref())
+    {
+        let inputs = counterexample
+            .iter()
+            .map(|input| format!("{} = {}", input.name, input.value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(message, ", minimal counterexample: {inputs}").unwrap();
+    }
+    message
+}
+
+/// Escape the characters that may not appear verbatim within XML attributes and text.
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> TestReport {
+        let test = |name: &str, passed: bool| TestCaseReport {
+            name: name.to_string(),
+            package: "my_pkg".to_string(),
+            file: "/my_pkg/src/lib.sw".to_string(),
+            line: 4,
+            passed,
+            gas_used: 42,
+            duration_secs: 0.5,
+            revert_code: (!passed).then_some(1),
+            failure: (!passed).then(|| "test reverted with code 1 (0x1)".to_string()),
+            logs: serde_json::json!([]),
+        };
+        TestReport {
+            packages: vec![PackageReport {
+                name: "my_pkg".to_string(),
+                passed: 1,
+                failed: 1,
+                filtered: 1,
+                ignored: vec![IgnoredTestReport {
+                    name: "test_ignored".to_string(),
+                    package: "my_pkg".to_string(),
+                    file: "/my_pkg/src/lib.sw".to_string(),
+                    line: 12,
+                }],
+                tests: vec![test("test_ok", true), test("test_<fail>", false)],
+            }],
+        }
+    }
+
+    #[test]
+    fn junit_report() {
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="forc test" tests="3" failures="1" time="1">
+  <testsuite name="my_pkg" tests="3" failures="1" skipped="1" time="1">
+    <testcase name="test_ok" classname="my_pkg" file="/my_pkg/src/lib.sw" line="4" time="0.5">
+      <properties>
+        <property name="gas_used" value="42"/>
+      </properties>
+    </testcase>
+    <testcase name="test_&lt;fail&gt;" classname="my_pkg" file="/my_pkg/src/lib.sw" line="4" time="0.5">
+      <properties>
+        <property name="gas_used" value="42"/>
+        <property name="revert_code" value="1"/>
+      </properties>
+      <failure message="test reverted with code 1 (0x1)"/>
+    </testcase>
+    <testcase name="test_ignored" classname="my_pkg" file="/my_pkg/src/lib.sw" line="12" time="0">
+      <skipped/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(report().render(ReportFormat::Junit).unwrap(), expected);
+    }
+
+    #[test]
+    fn json_report() {
+        let json: serde_json::Value =
+            serde_json::from_str(&report().render(ReportFormat::Json).unwrap()).unwrap();
+        let test = &json["packages"][0]["tests"][1];
+        assert_eq!(test["name"], "test_<fail>");
+        assert_eq!(test["passed"], false);
+        assert_eq!(test["revert_code"], 1);
+        assert_eq!(test["gas_used"], 42);
+    }
+}
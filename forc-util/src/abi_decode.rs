@@ -0,0 +1,255 @@
+//! Decoding of values encoded according to the Fuel ABI, using the types described by a program's
+//! JSON ABI.
+//!
+//! This is used to present values such as those logged by a program in terms of their Sway types.
+
+use anyhow::Result;
+use fuel_abi_types::program_abi::{TypeApplication, TypeDeclaration};
+use fuel_tx::{ContractId, Receipt};
+use fuels_core::abi_decoder::ABIDecoder;
+use fuels_types::{enum_variants::EnumVariants, param_types::ParamType, Token};
+use std::collections::HashMap;
+use sway_core::{asm_generation::ProgramABI, fuel_prelude::fuel_tx};
+
+/// Decodes the values logged by programs using the `loggedTypes` of their JSON ABI.
+#[derive(Debug, Default, Clone)]
+pub struct LogDecoder {
+    /// The type of the values logged with each log id, keyed by the contract emitting the log.
+    ///
+    /// Logs emitted by scripts and predicates use the zeroed `ContractId`.
+    logged_types: HashMap<(ContractId, u64), ParamType>,
+}
+
+impl LogDecoder {
+    /// Construct a decoder for the values logged by a script or predicate with the given ABI.
+    pub fn new(abi: &ProgramABI) -> Result<Self> {
+        let mut log_decoder = Self::default();
+        log_decoder.insert_program(ContractId::zeroed(), abi)?;
+        Ok(log_decoder)
+    }
+
+    /// Decode the values logged by the program with the given ABI, emitted from the given
+    /// contract.
+    pub fn insert_program(&mut self, contract_id: ContractId, abi: &ProgramABI) -> Result<()> {
+        let abi = match abi {
+            ProgramABI::Fuel(abi) => abi,
+            // Logs are only decoded for the Fuel target.
+            ProgramABI::Evm(_) => return Ok(()),
+        };
+        let type_lookup = type_lookup(&abi.types);
+        for logged_type in abi.logged_types.iter().flatten() {
+            let param_type = resolve_param_type(&logged_type.application, &type_lookup)?;
+            self.logged_types
+                .insert((contract_id, logged_type.log_id), param_type);
+        }
+        Ok(())
+    }
+
+    /// Decode the value logged by the given `Log` or `LogData` receipt, formatted as a Sway
+    /// expression.
+    ///
+    /// Returns `None` if the receipt is not a log or its type is unknown.
+    pub fn decode(&self, receipt: &Receipt) -> Option<Result<String>> {
+        let (contract_id, log_id, data) = match receipt {
+            Receipt::Log { id, ra, rb, .. } => (*id, *rb, ra.to_be_bytes().to_vec()),
+            Receipt::LogData { id, rb, data, .. } => (*id, *rb, data.clone()),
+            _ => return None,
+        };
+        let param_type = self.logged_types.get(&(contract_id, log_id))?;
+        let token = ABIDecoder::decode_single(param_type, &data)
+            .map_err(|e| anyhow::anyhow!("failed to decode log with id {log_id}: {e}"));
+        Some(token.map(|token| format_token(&token, param_type)))
+    }
+}
+
+/// Map the type id of each of the given type declarations to the declaration.
+pub fn type_lookup(types: &[TypeDeclaration]) -> HashMap<usize, TypeDeclaration> {
+    types
+        .iter()
+        .map(|decl| (decl.type_id, decl.clone()))
+        .collect()
+}
+
+/// Resolve the `ParamType` of the given type application, including the names of any structs,
+/// enums and their fields and variants.
+pub fn resolve_param_type(
+    type_application: &TypeApplication,
+    type_lookup: &HashMap<usize, TypeDeclaration>,
+) -> Result<ParamType> {
+    let param_type = ParamType::try_from_type_application(type_application, type_lookup)?;
+    with_names(param_type, type_application, type_lookup, &HashMap::new())
+}
+
+/// Restore the names of the structs, enums and their fields and variants within the given
+/// `ParamType`, which are not retained when resolving a `ParamType` from the JSON ABI.
+///
+/// `generics` maps the type ids of the generic parameters in scope to their type arguments.
+fn with_names(
+    param_type: ParamType,
+    type_application: &TypeApplication,
+    type_lookup: &HashMap<usize, TypeDeclaration>,
+    generics: &HashMap<usize, TypeApplication>,
+) -> Result<ParamType> {
+    let type_decl = &type_lookup[&type_application.type_id];
+    if type_decl.type_field.starts_with("generic ") {
+        return match generics.get(&type_application.type_id) {
+            Some(type_argument) => with_names(param_type, type_argument, type_lookup, generics),
+            None => Ok(param_type),
+        };
+    }
+
+    // Determine the type arguments for the generic parameters declared by this type.
+    let generics = match (&type_decl.type_parameters, &type_application.type_arguments) {
+        (Some(params), Some(args)) => params
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| {
+                let arg = generics.get(&arg.type_id).unwrap_or(arg);
+                (*param, arg.clone())
+            })
+            .collect(),
+        _ => generics.clone(),
+    };
+    let components = type_decl.components.as_deref().unwrap_or_default();
+    let named = |types: Vec<(String, ParamType)>| -> Result<Vec<(String, ParamType)>> {
+        types
+            .into_iter()
+            .zip(components)
+            .map(|((_, param_type), component)| {
+                let param_type = with_names(param_type, component, type_lookup, &generics)?;
+                Ok((component.name.clone(), param_type))
+            })
+            .collect()
+    };
+    let type_name = |prefix: &str| {
+        let name = type_decl.type_field.trim_start_matches(prefix);
+        name.rsplit("::").next().unwrap_or(name).to_string()
+    };
+    let param_type = match param_type {
+        ParamType::Struct {
+            fields, generics, ..
+        } => ParamType::Struct {
+            name: type_name("struct "),
+            fields: named(fields)?,
+            generics,
+        },
+        ParamType::Enum {
+            variants, generics, ..
+        } => ParamType::Enum {
+            name: type_name("enum "),
+            variants: EnumVariants::new(named(variants.variants().clone())?)?,
+            generics,
+        },
+        ParamType::Array(elem, len) => {
+            let elem = match components {
+                [component] => with_names(*elem, component, type_lookup, &generics)?,
+                _ => *elem,
+            };
+            ParamType::Array(Box::new(elem), len)
+        }
+        ParamType::Tuple(elems) => ParamType::Tuple(
+            elems
+                .into_iter()
+                .zip(components)
+                .map(|(elem, component)| with_names(elem, component, type_lookup, &generics))
+                .collect::<Result<_>>()?,
+        ),
+        param_type => param_type,
+    };
+    Ok(param_type)
+}
+
+/// Format the given value of the given type as a Sway expression.
+pub fn format_token(token: &Token, param_type: &ParamType) -> String {
+    fn join<'a>(elems: &[Token], types: impl Iterator<Item = &'a ParamType>) -> String {
+        elems
+            .iter()
+            .zip(types)
+            .map(|(elem, elem_type)| format_token(elem, elem_type))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    match (token, param_type) {
+        (Token::Unit, _) => "()".to_string(),
+        (Token::U8(v), _) | (Token::Byte(v), _) => v.to_string(),
+        (Token::U16(v), _) => v.to_string(),
+        (Token::U32(v), _) => v.to_string(),
+        (Token::U64(v), _) => v.to_string(),
+        (Token::Bool(v), _) => v.to_string(),
+        (Token::B256(v), _) => format!("0x{}", hex::encode(v)),
+        (Token::String(s), _) => format!("{:?}", s.get_encodable_str().unwrap_or_default()),
+        (Token::Array(elems), ParamType::Array(elem, _)) => {
+            format!("[{}]", join(elems, std::iter::repeat(&**elem)))
+        }
+        (Token::Tuple(elems), ParamType::Tuple(types)) => {
+            format!("({})", join(elems, types.iter()))
+        }
+        (Token::Struct(elems), ParamType::Struct { name, fields, .. }) => {
+            let fields = elems
+                .iter()
+                .zip(fields)
+                .map(|(elem, (field_name, field))| {
+                    format!("{field_name}: {}", format_token(elem, field))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{name} {{ {fields} }}")
+        }
+        (Token::Enum(selector), ParamType::Enum { name, variants, .. }) => {
+            let (discriminant, value, _) = &**selector;
+            let (variant_name, variant) = &variants.variants()[usize::from(*discriminant)];
+            match value {
+                Token::Unit => format!("{name}::{variant_name}"),
+                value => format!("{name}::{variant_name}({})", format_token(value, variant)),
+            }
+        }
+        (token, _) => format!("{token:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point_type() -> ParamType {
+        ParamType::Struct {
+            name: "Point".to_string(),
+            fields: vec![
+                ("x".to_string(), ParamType::U64),
+                ("y".to_string(), ParamType::Bool),
+            ],
+            generics: vec![],
+        }
+    }
+
+    #[test]
+    fn format_struct() {
+        let token = Token::Struct(vec![Token::U64(42), Token::Bool(false)]);
+        assert_eq!(
+            format_token(&token, &point_type()),
+            "Point { x: 42, y: false }"
+        );
+    }
+
+    #[test]
+    fn decode_log_data() {
+        let mut log_decoder = LogDecoder::default();
+        log_decoder
+            .logged_types
+            .insert((ContractId::zeroed(), 3), point_type());
+        let mut data = 7u64.to_be_bytes().to_vec();
+        data.extend(1u64.to_be_bytes());
+        let receipt = Receipt::log_data(
+            ContractId::zeroed(),
+            0,
+            3,
+            0,
+            Default::default(),
+            data,
+            0,
+            0,
+        );
+        let decoded = log_decoder.decode(&receipt).unwrap().unwrap();
+        assert_eq!(decoded, "Point { x: 7, y: true }");
+    }
+}
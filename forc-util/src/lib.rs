@@ -1,5 +1,6 @@
 //! Utility items shared between forc crates.
 
+use abi_decode::LogDecoder;
 use annotate_snippets::{
     display_list::{DisplayList, FormatOptions},
     snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
@@ -16,12 +17,34 @@ use sway_error::warning::CompileWarning;
 use sway_types::{LineCol, Spanned};
 use sway_utils::constants;
 
+pub mod abi_decode;
 pub mod restricted;
 
 pub const DEFAULT_OUTPUT_DIRECTORY: &str = "out";
 
 /// Format `Log` and `LogData` receipts.
-pub fn format_log_receipts(receipts: &[fuel_tx::Receipt], pretty_print: bool) -> Result<String> {
+///
+/// The logged values whose types are known to the `log_decoder` are decoded and included as the
+/// `decoded` field of their receipt.
+pub fn format_log_receipts(
+    receipts: &[fuel_tx::Receipt],
+    pretty_print: bool,
+    log_decoder: &LogDecoder,
+) -> Result<String> {
+    let receipt_to_json_array = log_receipts_to_json(receipts, log_decoder)?;
+    if pretty_print {
+        Ok(serde_json::to_string_pretty(&receipt_to_json_array)?)
+    } else {
+        Ok(serde_json::to_string(&receipt_to_json_array)?)
+    }
+}
+
+/// Convert `Log` and `LogData` receipts into a JSON array, with their data encoded as hex and
+/// their logged values decoded using the `log_decoder`.
+pub fn log_receipts_to_json(
+    receipts: &[fuel_tx::Receipt],
+    log_decoder: &LogDecoder,
+) -> Result<serde_json::Value> {
     let mut receipt_to_json_array = serde_json::to_value(receipts)?;
     for (rec_index, receipt) in receipts.iter().enumerate() {
         let rec_value = receipt_to_json_array.get_mut(rec_index).ok_or_else(|| {
@@ -43,12 +66,20 @@ pub fn format_log_receipts(receipts: &[fuel_tx::Receipt], pretty_print: bool) ->
             }
             _ => {}
         }
+        if let Some(decoded) = log_decoder.decode(receipt) {
+            let decoded = match decoded {
+                Ok(decoded) => decoded,
+                Err(e) => format!("<{e}>"),
+            };
+            if let Some(serde_json::Value::Object(fields)) = rec_value
+                .as_object_mut()
+                .and_then(|rec| rec.values_mut().next())
+            {
+                fields.insert("decoded".to_string(), decoded.into());
+            }
+        }
     }
-    if pretty_print {
-        Ok(serde_json::to_string_pretty(&receipt_to_json_array)?)
-    } else {
-        Ok(serde_json::to_string(&receipt_to_json_array)?)
-    }
+    Ok(receipt_to_json_array)
 }
 
 /// Continually go up in the file tree until a specified file is found.
@@ -1,10 +1,16 @@
 use crate::cli;
 use ansi_term::Colour;
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use clap::Parser;
 use forc_pkg as pkg;
+use forc_pkg::TestPassCondition;
+use forc_test::bench::{BenchBaseline, Benched, BenchedPackage, BENCH_BASELINE_FILE_NAME};
+use forc_test::coverage::{Coverage, CoverageOpts, LCOV_FILE_NAME};
+use forc_test::gas_snapshot::{GasSnapshot, GAS_SNAPSHOT_FILE_NAME};
+use forc_test::report::{ReportFormat, TestReport};
 use forc_test::TestedPackage;
-use forc_util::format_log_receipts;
+use forc_util::{find_manifest_dir, format_log_receipts};
+use std::path::{Path, PathBuf};
 use tracing::info;
 
 /// Run the Sway unit tests for the current project.
@@ -24,6 +30,37 @@ use tracing::info;
 /// Upon successful compilation, test scripts are executed to their completion. A test is
 /// considered a failure in the case that a revert (`rvrt`) instruction is encountered during
 /// execution. Otherwise, it is considered a success.
+///
+/// A subset of the tests may be selected by providing a filter. By default, all tests whose name
+/// contains the filter are executed. Use `--exact` to only run the test whose name matches the
+/// filter exactly, or `--regex` to treat the filter as a regular expression.
+///
+/// The gas used by each test may be recorded within a `.gas-snapshot` file in the project
+/// directory using `--gas-snapshot`. Later runs may be checked against the recorded gas using
+/// `--check-snapshot`, which fails if any test uses more gas than the allowed tolerance.
+///
+/// Tests decorated with the `#[test(fuzz)]` attribute may declare parameters. These tests are
+/// executed many times with randomly generated arguments. The inputs are generated from a seed,
+/// so a failing input can be reproduced by providing the same `--fuzz-seed`.
+///
+/// Tests of a predicate decorated with `#[test(should_accept)]` or `#[test(should_reject)]` return
+/// predicate data. The predicate is verified against a transaction providing this data, and the
+/// test fails if the predicate does not accept or reject the transaction as expected.
+///
+/// Tests decorated with `#[test(ignore)]` are skipped, unless `--include-ignored` is provided to
+/// run them along with all other tests, or `--ignored` is provided to only run the ignored tests.
+/// Using `--list`, the tests are listed along with their location instead of being run.
+///
+/// A machine-readable report of the test results may be written to a file using `--format` along
+/// with `--output`.
+///
+/// Using `--coverage`, the source lines executed by the tests are recorded and summarized, and
+/// written to an `lcov.info` file in the project directory.
+///
+/// Functions decorated with the `#[bench]` attribute are benchmarks. Using `--bench`, these are
+/// executed instead of the tests and the gas used and instructions executed by each are reported.
+/// The measurements may be saved to a `.bench-baseline` file in the project directory using
+/// `--save-baseline`, and later runs may be compared against them using `--baseline`.
 #[derive(Debug, Parser)]
 pub struct Command {
     #[clap(flatten)]
@@ -32,6 +69,78 @@ pub struct Command {
     pub test_print: TestPrintOpts,
     /// When specified, only tests containing the given string will be executed.
     pub filter: Option<String>,
+    /// When specified, only the tests exactly matching the given string will be executed.
+    #[clap(long)]
+    pub exact: bool,
+    /// When specified, the filter is interpreted as a regular expression.
+    #[clap(long, conflicts_with = "exact")]
+    pub regex: bool,
+    /// Run the ignored tests, i.e. those decorated with `#[test(ignore)]`, along with all other
+    /// tests.
+    #[clap(long, conflicts_with = "ignored")]
+    pub include_ignored: bool,
+    /// Only run the ignored tests, i.e. those decorated with `#[test(ignore)]`.
+    #[clap(long)]
+    pub ignored: bool,
+    /// List the fully qualified name and location of each test instead of running the tests.
+    #[clap(
+        long,
+        conflicts_with_all = &["gas-snapshot", "check-snapshot", "coverage", "format", "bench"]
+    )]
+    pub list: bool,
+    /// Number of threads to utilize when running the tests. By default, this is the number of
+    /// threads available in your system.
+    #[clap(long)]
+    pub test_threads: Option<usize>,
+    /// Write the gas used by each test to a `.gas-snapshot` file in the project directory. The gas
+    /// already recorded in the file for the tests which aren't run, e.g. as they are filtered
+    /// out, is kept.
+    #[clap(long, conflicts_with = "check-snapshot")]
+    pub gas_snapshot: bool,
+    /// Compare the gas used by each test against the `.gas-snapshot` file in the project
+    /// directory, failing if any test uses more gas than recorded.
+    #[clap(long)]
+    pub check_snapshot: bool,
+    /// The percentage by which the gas used by a test may exceed its recorded gas before
+    /// `--check-snapshot` fails.
+    #[clap(long, default_value = "0")]
+    pub snapshot_tolerance: f64,
+    /// The number of generated inputs to execute each fuzz test with.
+    #[clap(long, default_value_t = forc_test::fuzz::DEFAULT_FUZZ_RUNS)]
+    pub fuzz_runs: usize,
+    /// The seed used to generate the inputs for fuzz tests.
+    #[clap(long, default_value_t = forc_test::fuzz::DEFAULT_FUZZ_SEED)]
+    pub fuzz_seed: u64,
+    /// The format of the test report written to the `--output` path, one of: json, junit.
+    #[clap(long, requires = "output")]
+    pub format: Option<ReportFormat>,
+    /// The path to write the test report to.
+    #[clap(long, requires = "format")]
+    pub output: Option<PathBuf>,
+    /// Record the source lines executed by the tests and write them in the LCOV format.
+    #[clap(long)]
+    pub coverage: bool,
+    /// The path to write the LCOV coverage to. By default, this is `lcov.info` in the project
+    /// directory.
+    #[clap(long, requires = "coverage")]
+    pub coverage_output: Option<PathBuf>,
+    /// Also record the coverage of dependencies such as `std` and `core`.
+    #[clap(long, requires = "coverage")]
+    pub coverage_include_deps: bool,
+    /// Run the benchmarks, i.e. the functions decorated with `#[bench]`, instead of the tests.
+    #[clap(
+        long,
+        conflicts_with_all = &["gas-snapshot", "check-snapshot", "coverage", "format"]
+    )]
+    pub bench: bool,
+    /// Write the gas used and instructions executed by each benchmark to a `.bench-baseline`
+    /// file in the project directory.
+    #[clap(long, requires = "bench", conflicts_with = "baseline")]
+    pub save_baseline: bool,
+    /// Compare the gas used and instructions executed by each benchmark against the
+    /// `.bench-baseline` file in the project directory.
+    #[clap(long, requires = "bench")]
+    pub baseline: bool,
 }
 
 /// The set of options provided for controlling output of a test.
@@ -46,17 +155,58 @@ pub struct TestPrintOpts {
 }
 
 pub(crate) fn exec(cmd: Command) -> Result<()> {
-    if let Some(ref _filter) = cmd.filter {
-        bail!("unit test filter not yet supported");
+    if cmd.bench {
+        return exec_bench(cmd);
     }
-
+    if cmd.list {
+        return exec_list(cmd);
+    }
+    let test_filter = test_filter_from_cmd(&cmd)?;
+    let ignored = ignored_tests_from_cmd(&cmd);
+    let test_runner_count = test_runner_count_from_cmd(&cmd);
+    let fuzz_opts = forc_test::fuzz::FuzzOpts {
+        runs: cmd.fuzz_runs,
+        seed: cmd.fuzz_seed,
+    };
     let test_print_opts = cmd.test_print.clone();
+    let snapshot_path = (cmd.gas_snapshot || cmd.check_snapshot)
+        .then(|| gas_snapshot_path(&cmd))
+        .transpose()?;
+    let (write_snapshot, snapshot_tolerance) = (cmd.gas_snapshot, cmd.snapshot_tolerance);
+    let report_output = cmd.format.zip(cmd.output.clone());
+    let coverage_opts = cmd.coverage.then_some(CoverageOpts {
+        include_dependencies: cmd.coverage_include_deps,
+    });
+    let coverage_path = match (cmd.coverage, &cmd.coverage_output) {
+        (false, _) => None,
+        (true, Some(path)) => Some(path.clone()),
+        (true, None) => Some(manifest_dir(&cmd)?.join(LCOV_FILE_NAME)),
+    };
     let opts = opts_from_cmd(cmd);
     let built_tests = forc_test::build(opts)?;
     let start = std::time::Instant::now();
-    info!("   Running {} tests", built_tests.test_count());
-    let tested = built_tests.run()?;
+    let test_count = built_tests.test_count(test_filter.as_ref(), ignored);
+    let mut running = format!("   Running {} tests", test_count.total);
+    if test_count.filtered != 0 {
+        running += &format!(", filtered {} tests", test_count.filtered);
+    }
+    if test_count.ignored != 0 {
+        running += &format!(", ignored {} tests", test_count.ignored);
+    }
+    info!("{running}");
+    let tested = built_tests.run(
+        test_runner_count,
+        test_filter.as_ref(),
+        ignored,
+        fuzz_opts,
+        coverage_opts,
+    )?;
     let duration = start.elapsed();
+    let gas_snapshot = GasSnapshot::from_tested(&tested);
+    if let Some((format, path)) = report_output {
+        TestReport::from_tested(&tested)?.write(format, &path)?;
+    }
+    let coverage = tested_coverage(&tested);
 
     // Eventually we'll print this in a fancy manner, but this will do for testing.
     match tested {
@@ -71,9 +221,263 @@ pub(crate) fn exec(cmd: Command) -> Result<()> {
         forc_test::Tested::Package(pkg) => print_tested_pkg(&pkg, &test_print_opts)?,
     };
 
+    match snapshot_path {
+        Some(path) if write_snapshot => {
+            let mut snapshot = match path.exists() {
+                true => GasSnapshot::read(&path)?,
+                false => GasSnapshot::default(),
+            };
+            snapshot.merge(gas_snapshot);
+            snapshot.write(&path)?;
+            info!("\n   Wrote gas snapshot to {}", path.display());
+        }
+        Some(path) => check_gas_snapshot(&gas_snapshot, &path, snapshot_tolerance)?,
+        None => (),
+    }
+
+    if let (Some(coverage), Some(path)) = (coverage, coverage_path) {
+        print_coverage(&coverage);
+        coverage.write_lcov(&path)?;
+        info!("\n   Wrote coverage to {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Build the package or workspace and list its tests without running them.
+///
+/// Once the build completes, each test is printed to stdout on its own line as
+/// `<package>::<module path>::<test> <file>:<line>`, followed by `(ignored)` if the test is
+/// ignored.
+fn exec_list(cmd: Command) -> Result<()> {
+    let test_filter = test_filter_from_cmd(&cmd)?;
+    let ignored = ignored_tests_from_cmd(&cmd);
+    let opts = opts_from_cmd(cmd);
+    let built_tests = forc_test::build(opts)?;
+    let listed = built_tests
+        .list(test_filter.as_ref(), ignored)
+        .into_iter()
+        .map(|test| {
+            let details = test.details()?;
+            let ignored = if test.ignored { " (ignored)" } else { "" };
+            Ok(format!(
+                "{} {}:{}{ignored}",
+                test.path(),
+                details.file_path.display(),
+                details.line_number
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    for line in listed {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Build the package or workspace and run its benchmarks.
+fn exec_bench(cmd: Command) -> Result<()> {
+    let filter = test_filter_from_cmd(&cmd)?;
+    let test_runner_count = test_runner_count_from_cmd(&cmd);
+    let test_print_opts = cmd.test_print.clone();
+    let baseline_path = (cmd.save_baseline || cmd.baseline)
+        .then(|| bench_baseline_path(&cmd))
+        .transpose()?;
+    let baseline = match &baseline_path {
+        Some(path) if cmd.baseline => Some(BenchBaseline::read(path)?),
+        _ => None,
+    };
+    let save_baseline = cmd.save_baseline;
+    let opts = opts_from_cmd(cmd);
+    let built_tests = forc_test::build(opts)?;
+    let bench_count = built_tests.bench_count(filter.as_ref());
+    match bench_count.filtered {
+        0 => info!("   Running {} benches", bench_count.total),
+        filtered => info!(
+            "   Running {} benches, filtered {} benches",
+            bench_count.total, filtered
+        ),
+    }
+    let benched = built_tests.bench(test_runner_count, filter.as_ref())?;
+    let latest = BenchBaseline::from_benched(&benched);
+    let pkgs = match &benched {
+        Benched::Package(pkg) => vec![&**pkg],
+        Benched::Workspace(pkgs) => pkgs.iter().collect(),
+    };
+    let mut failed = 0;
+    let is_workspace = matches!(benched, Benched::Workspace(_));
+    for pkg in pkgs {
+        if is_workspace {
+            info!("\n   benched -- {}\n", pkg.built.pkg_name);
+        }
+        failed += print_benched_pkg(pkg, baseline.as_ref(), &test_print_opts)?;
+    }
+
+    if let Some(path) = baseline_path.filter(|_| save_baseline) {
+        latest.write(&path)?;
+        info!("\n   Wrote bench baseline to {}", path.display());
+    }
+    if failed != 0 {
+        bail!("{failed} benchmark(s) failed to execute successfully");
+    }
     Ok(())
 }
 
+/// Print the measurements of each benchmark within the package, along with their change relative
+/// to the `baseline` if provided. Returns the number of benchmarks that failed.
+fn print_benched_pkg(
+    pkg: &BenchedPackage,
+    baseline: Option<&BenchBaseline>,
+    test_print_opts: &TestPrintOpts,
+) -> Result<usize> {
+    let mut failed = 0;
+    for bench in &pkg.benches {
+        let measurement = bench.measurement;
+        let state = match bench.passed() {
+            true => Colour::Green.paint("ok"),
+            false => {
+                failed += 1;
+                Colour::Red.paint("FAILED")
+            }
+        };
+        let name = format!("{}::{}", pkg.built.pkg_name, bench.name);
+        let change = match baseline.map(|baseline| baseline.get(&name)) {
+            None => String::new(),
+            Some(None) => ", new".to_string(),
+            Some(Some(baseline)) => {
+                let change = measurement.change_from(&baseline);
+                format!(
+                    ", gas: {:+.2}%, instructions: {:+.2}%",
+                    change.gas_used, change.instruction_count
+                )
+            }
+        };
+        info!(
+            "      bench {} ... {} ({:?}, {} gas, {} instructions{})",
+            bench.name,
+            state,
+            bench.duration,
+            measurement.gas_used,
+            measurement.instruction_count,
+            change
+        );
+        if test_print_opts.print_logs {
+            let formatted_logs =
+                format_log_receipts(&bench.logs, test_print_opts.pretty_print, &pkg.log_decoder)?;
+            info!("{}", formatted_logs);
+        }
+        if let Some(backtrace) = &bench.backtrace {
+            info!("        backtrace:");
+            for (ix, frame) in backtrace.frames.iter().enumerate() {
+                info!("          {ix}: {frame}");
+            }
+        }
+    }
+    let (state, color) = match failed {
+        0 => ("OK", Colour::Green),
+        _ => ("FAILED", Colour::Red),
+    };
+    info!(
+        "   Result: {}. {} benched. {} failed. {} filtered out.",
+        color.paint(state),
+        pkg.benches.len() - failed,
+        failed,
+        pkg.filtered
+    );
+    Ok(failed)
+}
+
+/// Merge the coverage recorded for each of the tested packages.
+fn tested_coverage(tested: &forc_test::Tested) -> Option<Coverage> {
+    let pkgs: Vec<&TestedPackage> = match tested {
+        forc_test::Tested::Package(pkg) => vec![pkg],
+        forc_test::Tested::Workspace(pkgs) => pkgs.iter().collect(),
+    };
+    pkgs.iter().filter_map(|pkg| pkg.coverage.as_ref()).fold(
+        None,
+        |acc: Option<Coverage>, coverage| {
+            let mut acc = acc.unwrap_or_default();
+            acc.merge(coverage);
+            Some(acc)
+        },
+    )
+}
+
+/// Print the percentage of lines executed within each source file.
+fn print_coverage(coverage: &Coverage) {
+    info!("\n   coverage:");
+    let summary = coverage.summary();
+    for file in &summary {
+        info!(
+            "      {:>6.2}% ({}/{} lines) {}",
+            file.percentage(),
+            file.lines_hit,
+            file.lines_found,
+            file.path.display()
+        );
+    }
+    let lines_hit: usize = summary.iter().map(|file| file.lines_hit).sum();
+    let lines_found: usize = summary.iter().map(|file| file.lines_found).sum();
+    let total = forc_test::coverage::FileCoverage {
+        path: PathBuf::new(),
+        lines_hit,
+        lines_found,
+    };
+    info!(
+        "   Total: {:.2}% ({}/{} lines)",
+        total.percentage(),
+        lines_hit,
+        lines_found
+    );
+}
+
+/// The path to the `.gas-snapshot` file within the directory of the package or workspace.
+fn gas_snapshot_path(cmd: &Command) -> Result<PathBuf> {
+    Ok(manifest_dir(cmd)?.join(GAS_SNAPSHOT_FILE_NAME))
+}
+
+/// The path to the `.bench-baseline` file within the directory of the package or workspace.
+fn bench_baseline_path(cmd: &Command) -> Result<PathBuf> {
+    Ok(manifest_dir(cmd)?.join(BENCH_BASELINE_FILE_NAME))
+}
+
+/// The directory of the package or workspace manifest.
+fn manifest_dir(cmd: &Command) -> Result<PathBuf> {
+    let this_dir = match &cmd.build.pkg.path {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir()?,
+    };
+    let manifest_dir = find_manifest_dir(&this_dir).ok_or_else(|| {
+        anyhow!(
+            "could not find `{}` in {:?} or any parent directory",
+            sway_utils::constants::MANIFEST_FILE_NAME,
+            this_dir
+        )
+    })?;
+    Ok(manifest_dir)
+}
+
+/// Compare the gas used by the tests against the snapshot at the given path.
+fn check_gas_snapshot(gas_snapshot: &GasSnapshot, path: &Path, tolerance: f64) -> Result<()> {
+    let baseline = GasSnapshot::read(path)?;
+    let regressions = gas_snapshot.regressions(&baseline, tolerance);
+    if regressions.is_empty() {
+        info!("\n   Gas snapshot check: {}", Colour::Green.paint("OK"));
+        return Ok(());
+    }
+    info!("\n   gas regressions:");
+    for diff in &regressions {
+        info!(
+            "      - test {}, expected {} gas, used {} gas",
+            diff.test, diff.expected, diff.actual
+        );
+    }
+    bail!(
+        "{} test(s) used more gas than recorded in {}",
+        regressions.len(),
+        path.display()
+    )
+}
+
 fn print_tested_pkg(pkg: &TestedPackage, test_print_opts: &TestPrintOpts) -> Result<()> {
     let succeeded = pkg.tests.iter().filter(|t| t.passed()).count();
     let failed = pkg.tests.len() - succeeded;
@@ -85,23 +489,25 @@ fn print_tested_pkg(pkg: &TestedPackage, test_print_opts: &TestPrintOpts) -> Res
             false => ("FAILED", Colour::Red),
         };
         info!(
-            "      test {} ... {} ({:?})",
+            "      test {} ... {} ({:?}, {} gas)",
             test.name,
             color.paint(state),
-            test.duration
+            test.duration,
+            test.gas_used
         );
 
         // If logs are enabled, print them.
         if test_print_opts.print_logs {
             let logs = &test.logs;
-            let formatted_logs = format_log_receipts(logs, test_print_opts.pretty_print)?;
+            let formatted_logs =
+                format_log_receipts(logs, test_print_opts.pretty_print, &pkg.log_decoder)?;
             info!("{}", formatted_logs);
         }
 
         // If the test is failing, save details.
         if !test_passed {
             let details = test.details()?;
-            failed_test_details.push((test.name.clone(), details));
+            failed_test_details.push((test, details));
         }
     }
     let (state, color) = match succeeded == pkg.tests.len() {
@@ -110,13 +516,50 @@ fn print_tested_pkg(pkg: &TestedPackage, test_print_opts: &TestPrintOpts) -> Res
     };
     if failed != 0 {
         info!("\n   failures:");
-        for (failed_test_name, failed_test_detail) in failed_test_details {
+        for (failed_test, failed_test_detail) in failed_test_details {
             let path = &*failed_test_detail.file_path;
             let line_number = failed_test_detail.line_number;
             info!(
                 "      - test {}, {:?}:{} ",
-                failed_test_name, path, line_number
+                failed_test.name, path, line_number
             );
+            if let Some(fuzz) = &failed_test.fuzz {
+                if let Some(counterexample) = &fuzz.counterexample {
+                    info!(
+                        "        failed after {} run(s) with seed {}, minimal counterexample:",
+                        fuzz.runs, fuzz.seed
+                    );
+                    for input in counterexample {
+                        info!("          {} = {}", input.name, input.value);
+                    }
+                }
+            }
+            if let Some(predicate) = &failed_test.predicate {
+                match &predicate.verified {
+                    Ok(gas_used) => info!(
+                        "        expected the predicate to reject the transaction, but it was accepted using {gas_used} gas"
+                    ),
+                    Err(e) => info!(
+                        "        expected the predicate to accept the transaction, but it was rejected: {e}"
+                    ),
+                }
+            }
+            if let TestPassCondition::ShouldRevert(Some(expected)) = failed_test.condition {
+                match failed_test.revert_code() {
+                    Some(actual) => info!(
+                        "        expected revert code: {expected} ({expected:#x}), found: {actual} ({actual:#x})"
+                    ),
+                    None => info!(
+                        "        expected revert code: {expected} ({expected:#x}), but the test did not revert"
+                    ),
+                }
+            }
+            if let Some(backtrace) = &failed_test.backtrace {
+                info!("        backtrace:");
+                for (ix, frame) in backtrace.frames.iter().enumerate() {
+                    info!("          {ix}: {frame}");
+                }
+            }
         }
         info!("\n");
     }
@@ -127,16 +570,43 @@ fn print_tested_pkg(pkg: &TestedPackage, test_print_opts: &TestPrintOpts) -> Res
         .map(|test_result| test_result.duration)
         .sum();
     info!(
-        "   Result: {}. {} passed. {} failed. Finished in {:?}.",
+        "   Result: {}. {} passed. {} failed. {} ignored. {} filtered out. Finished in {:?}.",
         color.paint(state),
         succeeded,
         failed,
+        pkg.ignored.len(),
+        pkg.filtered,
         pkg_test_durations
     );
 
     Ok(())
 }
 
+fn test_runner_count_from_cmd(cmd: &Command) -> forc_test::TestRunnerCount {
+    match cmd.test_threads {
+        Some(runner_count) => forc_test::TestRunnerCount::Manual(runner_count),
+        None => forc_test::TestRunnerCount::Auto,
+    }
+}
+
+fn ignored_tests_from_cmd(cmd: &Command) -> forc_test::IgnoredTests {
+    match (cmd.include_ignored, cmd.ignored) {
+        (true, _) => forc_test::IgnoredTests::Include,
+        (false, true) => forc_test::IgnoredTests::Only,
+        (false, false) => forc_test::IgnoredTests::Skip,
+    }
+}
+
+fn test_filter_from_cmd(cmd: &Command) -> Result<Option<forc_test::TestFilter>> {
+    let filter = match &cmd.filter {
+        Some(filter) if cmd.regex => forc_test::TestFilter::regex(filter)?,
+        Some(filter) if cmd.exact => forc_test::TestFilter::Exact(filter.clone()),
+        Some(filter) => forc_test::TestFilter::Contains(filter.clone()),
+        None => return Ok(None),
+    };
+    Ok(Some(filter))
+}
+
 fn opts_from_cmd(cmd: Command) -> forc_test::Opts {
     forc_test::Opts {
         pkg: pkg::PkgOpts {
@@ -12,6 +12,10 @@ pub struct Annotated<T> {
 //    #[attribute()]
 //    #[attribute(value)]
 //    #[attribute(value0, value1, value2)]
+//
+// Each argument may optionally be assigned a literal value:
+//
+//    #[attribute(name = "value")]
 
 #[derive(Clone, Debug)]
 pub struct AttributeDecl {
@@ -25,10 +29,26 @@ impl Spanned for AttributeDecl {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct AttributeArg {
+    pub name: Ident,
+    pub value: Option<Literal>,
+}
+
+impl Spanned for AttributeArg {
+    fn span(&self) -> Span {
+        if let Some(value) = &self.value {
+            Span::join(self.name.span(), value.span())
+        } else {
+            self.name.span()
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Attribute {
     pub name: Ident,
-    pub args: Option<Parens<Punctuated<Ident, CommaToken>>>,
+    pub args: Option<Parens<Punctuated<AttributeArg, CommaToken>>>,
 }
 
 impl Spanned for Attribute {
@@ -7,6 +7,8 @@ pub enum Intrinsic {
     SizeOfType,
     SizeOfVal,
     Eq,
+    Gt,
+    Lt,
     Gtf,
     AddrOf,
     StateClear,
@@ -19,6 +21,12 @@ pub enum Intrinsic {
     Sub,
     Mul,
     Div,
+    And,
+    Or,
+    Xor,
+    Mod,
+    Lsh,
+    Rsh,
     Revert,
     PtrAdd,
     PtrSub,
@@ -33,6 +41,8 @@ impl fmt::Display for Intrinsic {
             Intrinsic::SizeOfType => "size_of",
             Intrinsic::SizeOfVal => "size_of_val",
             Intrinsic::Eq => "eq",
+            Intrinsic::Gt => "gt",
+            Intrinsic::Lt => "lt",
             Intrinsic::Gtf => "gtf",
             Intrinsic::AddrOf => "addr_of",
             Intrinsic::StateClear => "state_clear",
@@ -45,6 +55,12 @@ impl fmt::Display for Intrinsic {
             Intrinsic::Sub => "sub",
             Intrinsic::Mul => "mul",
             Intrinsic::Div => "div",
+            Intrinsic::And => "and",
+            Intrinsic::Or => "or",
+            Intrinsic::Xor => "xor",
+            Intrinsic::Mod => "mod",
+            Intrinsic::Lsh => "lsh",
+            Intrinsic::Rsh => "rsh",
             Intrinsic::Revert => "revert",
             Intrinsic::PtrAdd => "ptr_add",
             Intrinsic::PtrSub => "ptr_sub",
@@ -63,6 +79,8 @@ impl Intrinsic {
             "__size_of" => SizeOfType,
             "__size_of_val" => SizeOfVal,
             "__eq" => Eq,
+            "__gt" => Gt,
+            "__lt" => Lt,
             "__gtf" => Gtf,
             "__addr_of" => AddrOf,
             "__state_clear" => StateClear,
@@ -75,6 +93,12 @@ impl Intrinsic {
             "__sub" => Sub,
             "__mul" => Mul,
             "__div" => Div,
+            "__and" => And,
+            "__or" => Or,
+            "__xor" => Xor,
+            "__mod" => Mod,
+            "__lsh" => Lsh,
+            "__rsh" => Rsh,
             "__revert" => Revert,
             "__ptr_add" => PtrAdd,
             "__ptr_sub" => PtrSub,
@@ -43,13 +43,18 @@ pub fn generate_json_abi_program(
                 configurables: Some(configurables),
             }
         }
-        _ => program_abi::ProgramABI {
-            types: vec![],
-            functions: vec![],
-            logged_types: None,
-            messages_types: None,
-            configurables: None,
-        },
+        TyProgramKind::Library { .. } => {
+            // Libraries have no ABI of their own, but the types they log are still required to
+            // decode the logs of their unit tests.
+            let logged_types = generate_json_logged_types(program, type_engine, types);
+            program_abi::ProgramABI {
+                types: types.to_vec(),
+                functions: vec![],
+                logged_types: Some(logged_types),
+                messages_types: None,
+                configurables: None,
+            }
+        }
     }
 }
 
@@ -587,7 +592,7 @@ impl TypeInfo {
 }
 
 impl TyFunctionDeclaration {
-    pub(self) fn generate_json_abi_function(
+    pub fn generate_json_abi_function(
         &self,
         type_engine: &TypeEngine,
         types: &mut Vec<program_abi::TypeDeclaration>,
@@ -674,7 +679,7 @@ fn generate_json_abi_attributes_map(
                 .flat_map(|(_attr_kind, attrs)| {
                     attrs.iter().map(|attr| program_abi::Attribute {
                         name: attr.name.to_string(),
-                        arguments: attr.args.iter().map(|arg| arg.to_string()).collect(),
+                        arguments: attr.args.iter().map(|arg| arg.name.to_string()).collect(),
                     })
                 })
                 .collect(),
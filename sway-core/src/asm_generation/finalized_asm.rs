@@ -184,11 +184,15 @@ fn to_bytecode_mut(
 /// Checks for disallowed opcodes in non-contract code.
 /// i.e., if this is a script or predicate, we can't use certain contract opcodes.
 /// See https://github.com/FuelLabs/sway/issues/350 for details.
-pub fn check_invalid_opcodes(asm: &FinalizedAsm) -> CompileResult<()> {
+///
+/// The test functions of a predicate are executed as scripts, so a predicate built with its tests
+/// included is checked as a script. The predicate itself is checked when built without tests.
+pub fn check_invalid_opcodes(asm: &FinalizedAsm, include_tests: bool) -> CompileResult<()> {
     match &asm.program_section {
         InstructionSet::Fuel { ops } => match asm.program_kind {
             ProgramKind::Contract | ProgramKind::Library => ok((), vec![], vec![]),
             ProgramKind::Script => checks::check_script_opcodes(&ops[..]),
+            ProgramKind::Predicate if include_tests => checks::check_script_opcodes(&ops[..]),
             ProgramKind::Predicate => checks::check_predicate_opcodes(&ops[..]),
         },
         InstructionSet::Evm { ops: _ } => ok((), vec![], vec![]),
@@ -46,8 +46,9 @@ pub fn compile_ir_to_asm(
 
     let final_asm = final_program.finalize();
 
+    let include_tests = build_config.map(|cfg| cfg.include_tests).unwrap_or(false);
     check!(
-        check_invalid_opcodes(&final_asm),
+        check_invalid_opcodes(&final_asm, include_tests),
         return err(warnings, errors),
         warnings,
         errors
@@ -173,14 +174,6 @@ macro_rules! size_bytes_in_words {
     };
 }
 
-// This is a mouthful...
-#[macro_export]
-macro_rules! size_bytes_round_up_to_word_alignment {
-    ($bytes_expr: expr) => {
-        ($bytes_expr + 7) - (($bytes_expr + 7) % 8)
-    };
-}
-
 // NOTE: For stack storage we need to be aware:
 // - sizes are in bytes; CFEI reserves in bytes.
 // - offsets are in 64-bit words; LW/SW reads/writes to word offsets. XXX Wrap in a WordOffset struct.
@@ -197,28 +190,7 @@ pub enum StateAccessType {
 }
 
 pub(crate) fn ir_type_size_in_bytes(context: &Context, ty: &Type) -> u64 {
-    match ty.get_content(context) {
-        TypeContent::Unit | TypeContent::Bool | TypeContent::Uint(_) => 8,
-        TypeContent::Slice => 16,
-        TypeContent::B256 => 32,
-        TypeContent::String(n) => size_bytes_round_up_to_word_alignment!(*n),
-        TypeContent::Array(el_ty, cnt) => cnt * ir_type_size_in_bytes(context, el_ty),
-        TypeContent::Struct(field_tys) => {
-            // Sum up all the field sizes.
-            field_tys
-                .iter()
-                .map(|field_ty| ir_type_size_in_bytes(context, field_ty))
-                .sum()
-        }
-        TypeContent::Union(field_tys) => {
-            // Find the max size for field sizes.
-            field_tys
-                .iter()
-                .map(|field_ty| ir_type_size_in_bytes(context, field_ty))
-                .max()
-                .unwrap_or(0)
-        }
-    }
+    ty.size_in_bytes(context)
 }
 
 // Aggregate (nested) field offset in words and size in bytes.
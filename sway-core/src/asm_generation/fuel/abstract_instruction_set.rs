@@ -1,16 +1,19 @@
 use crate::{
     asm_generation::fuel::{
-        allocated_abstract_instruction_set::AllocatedAbstractInstructionSet, register_allocator,
+        allocated_abstract_instruction_set::AllocatedAbstractInstructionSet,
+        data_section::DataSection, register_allocator,
     },
     asm_lang::{
         allocated_ops::{AllocatedOp, AllocatedOpcode},
-        AllocatedAbstractOp, Op, OrganizationalOp, RealizedOp, VirtualOp, VirtualRegister,
+        AllocatedAbstractOp, ConstantRegister, Op, OrganizationalOp, RealizedOp, VirtualOp,
+        VirtualRegister,
     },
 };
 
 use sway_error::error::CompileError;
 use sway_types::Span;
 
+use rustc_hash::FxHashSet;
 use std::{collections::HashSet, fmt};
 
 use either::Either;
@@ -164,31 +167,66 @@ impl AbstractInstructionSet {
     /// algorithm (https://en.wikipedia.org/wiki/Chaitin%27s_algorithm). The individual steps of
     /// the algorithm are thoroughly explained in register_allocator.rs.
     ///
-    pub(crate) fn allocate_registers(self) -> AllocatedAbstractInstructionSet {
-        // Step 1: Liveness Analysis.
-        let live_out = register_allocator::liveness_analysis(&self.ops);
+    /// If there aren't enough registers then some virtual registers are spilled to the stack and
+    /// the allocation is retried, until it succeeds.  The offsets of spill slots too far from the
+    /// locals base to be addressed directly are added to `data_section`.
+    ///
+    pub(crate) fn allocate_registers(
+        self,
+        data_section: &mut DataSection,
+    ) -> Result<AllocatedAbstractInstructionSet, CompileError> {
+        let mut ops = self.ops;
+        let mut spill_temps = FxHashSet::default();
+        let (reduced_ops, pool) = loop {
+            // Step 1: Liveness Analysis.
+            let live_out = register_allocator::liveness_analysis(&ops);
 
-        // Step 2: Construct the interference graph.
-        let (mut interference_graph, mut reg_to_node_ix) =
-            register_allocator::create_interference_graph(&self.ops, &live_out);
+            // Step 2: Construct the interference graph.
+            let (mut interference_graph, mut reg_to_node_ix) =
+                register_allocator::create_interference_graph(&ops, &live_out);
 
-        // Step 3: Remove redundant MOVE instructions using the interference graph.
-        let reduced_ops = register_allocator::coalesce_registers(
-            &self.ops,
-            &mut interference_graph,
-            &mut reg_to_node_ix,
-        );
+            // Step 3: Remove redundant MOVE instructions using the interference graph.
+            let reduced_ops = register_allocator::coalesce_registers(
+                &ops,
+                &mut interference_graph,
+                &mut reg_to_node_ix,
+            );
 
-        // Step 4: Simplify - i.e. color the interference graph and return a stack that contains
-        // each colorable node and its neighbors.
-        let mut stack = register_allocator::color_interference_graph(&mut interference_graph);
+            // Step 4: Simplify - i.e. color the interference graph and return a stack that
+            // contains each node and its neighbors, ordered by how likely it is to be colorable.
+            let spill_costs = register_allocator::compute_spill_costs(&reduced_ops, &spill_temps);
+            let mut stack =
+                register_allocator::color_interference_graph(&mut interference_graph, &spill_costs);
 
-        // Step 5: Use the stack to assign a register for each virtual register.
-        let pool = register_allocator::assign_registers(&mut stack);
+            // Step 5: Use the stack to assign a register for each virtual register, or if that's
+            // not possible spill the registers which couldn't be assigned and start again.
+            match register_allocator::assign_registers(&mut stack) {
+                Ok(pool) => break (reduced_ops, pool),
+                Err(spills) => {
+                    ops = register_allocator::spill(
+                        &reduced_ops,
+                        &spills,
+                        &mut spill_temps,
+                        data_section,
+                    )?;
+                }
+            }
+        };
+
+        // Step 6: The locals base is set in every function but is only needed if there are locals
+        // or spilled registers, so remove it if it's unused.
+        let locals_base = VirtualRegister::Constant(ConstantRegister::LocalsBase);
+        let is_locals_base_used = reduced_ops
+            .iter()
+            .any(|op| op.use_registers().contains(&&locals_base));
+        let reduced_ops = reduced_ops.into_iter().filter(|op| {
+            is_locals_base_used
+                || !matches!(&op.opcode, Either::Left(VirtualOp::MOVE(dst, _)) if *dst == locals_base)
+        });
 
-        // Step 6: Update all instructions to use the resulting register pool.
+        // Step 7: Update all instructions to use the resulting register pool.
         let mut buf = vec![];
-        for op in &reduced_ops {
+        for op in reduced_ops {
             buf.push(AllocatedAbstractOp {
                 opcode: op.allocate_registers(&pool),
                 comment: op.comment.clone(),
@@ -196,7 +234,7 @@ impl AbstractInstructionSet {
             })
         }
 
-        AllocatedAbstractInstructionSet { ops: buf }
+        Ok(AllocatedAbstractInstructionSet { ops: buf })
     }
 }
 
@@ -8,6 +8,7 @@ use super::{
     abstract_instruction_set::RealizedAbstractInstructionSet,
     compiler_constants as consts,
     data_section::{DataSection, Entry},
+    peephole,
 };
 
 use sway_types::span::Span;
@@ -16,6 +17,15 @@ use std::collections::{BTreeSet, HashMap, HashSet};
 
 use either::Either;
 
+/// Whether a register defined within a function must be saved by PUSHA and restored by POPA.  As
+/// well as the allocated registers, each function sets its own locals base.
+fn is_saved_register(reg: &AllocatedRegister) -> bool {
+    matches!(
+        reg,
+        AllocatedRegister::Allocated(_) | AllocatedRegister::Constant(ConstantRegister::LocalsBase)
+    )
+}
+
 // Convenience type for representing a map from a label to its offset and number of instructions
 // following it until the next label (i.e., the length of the basic block).
 pub(crate) type LabeledBlocks = HashMap<Label, BasicBlock>;
@@ -33,6 +43,32 @@ pub struct AllocatedAbstractInstructionSet {
 }
 
 impl AllocatedAbstractInstructionSet {
+    /// Apply the peephole optimizations, which are only possible once the registers are
+    /// allocated, e.g., folding a constant into the instruction which uses it.
+    pub(crate) fn optimize(self, data_section: &DataSection) -> Self {
+        AllocatedAbstractInstructionSet {
+            ops: peephole::optimize(self.ops, data_section),
+        }
+    }
+
+    /// The indices of the data section entries loaded by this function.
+    pub(crate) fn referenced_data(&self) -> impl Iterator<Item = u32> + '_ {
+        self.ops.iter().filter_map(|op| match &op.opcode {
+            Either::Left(AllocatedOpcode::LWDataId(_, data_id)) => Some(data_id.0),
+            _ => None,
+        })
+    }
+
+    /// Update the data section entries loaded by this function to their new indices, as returned
+    /// by [DataSection::remove_unreferenced].
+    pub(crate) fn renumber_data(&mut self, new_ids: &[Option<u32>]) {
+        for op in &mut self.ops {
+            if let Either::Left(AllocatedOpcode::LWDataId(_, data_id)) = &mut op.opcode {
+                data_id.0 = new_ids[data_id.0 as usize].expect("referenced data is never removed");
+            }
+        }
+    }
+
     /// Replace each PUSHA instruction with stores of all used registers to the stack, and each
     /// POPA with respective loads from the stack.
     ///
@@ -88,7 +124,7 @@ impl AllocatedAbstractInstructionSet {
                         .get(label)
                         .expect("Have collected registers above.")
                         .iter()
-                        .filter(|reg| matches!(reg, AllocatedRegister::Allocated(_)))
+                        .filter(|reg| is_saved_register(reg))
                         .collect::<Vec<_>>();
 
                     let stack_use_bytes = regs.len() as u64 * 8;
@@ -127,7 +163,7 @@ impl AllocatedAbstractInstructionSet {
                         .get(label)
                         .expect("Have collected registers above.")
                         .iter()
-                        .filter(|reg| matches!(reg, AllocatedRegister::Allocated(_)))
+                        .filter(|reg| is_saved_register(reg))
                         .collect::<Vec<_>>();
 
                     let stack_use_bytes = regs.len() as u64 * 8;
@@ -10,21 +10,24 @@ const NUM_FREE_REGISTERS: u8 = 48;
 /// 2. RETURN_ADDRESS - where a function must return to.
 /// 3. RETURN_VALUE - the value returned by a _function_ call.
 /// 4. SCRATCH - used for certain operations which need a register temporarily, such as JMP.
-/// 5. ARGS - for passing arguments to function calls.
-const NUM_COMPILER_RESERVED_REGISTERS: u8 = 4 + NUM_ARG_REGISTERS;
+/// 5. LOCALS_BASE - the base of the current function's locals on the stack, including any
+///    registers spilled by the register allocator.
+/// 6. ARGS - for passing arguments to function calls.
+const NUM_COMPILER_RESERVED_REGISTERS: u8 = 5 + NUM_ARG_REGISTERS;
 
 pub(crate) const DATA_SECTION_REGISTER: u8 = NUM_TOTAL_REGISTERS - 1;
 pub(crate) const RETURN_ADDRESS_REGISTER: u8 = NUM_TOTAL_REGISTERS - 2;
 pub(crate) const RETURN_VALUE_REGISTER: u8 = NUM_TOTAL_REGISTERS - 3;
 pub(crate) const SCRATCH_REGISTER: u8 = NUM_TOTAL_REGISTERS - 4;
+pub(crate) const LOCALS_BASE_REGISTER: u8 = NUM_TOTAL_REGISTERS - 5;
 
 pub(crate) const NUM_ARG_REGISTERS: u8 = 6;
-pub(crate) const ARG_REG0: u8 = NUM_TOTAL_REGISTERS - 5;
-pub(crate) const ARG_REG1: u8 = NUM_TOTAL_REGISTERS - 6;
-pub(crate) const ARG_REG2: u8 = NUM_TOTAL_REGISTERS - 7;
-pub(crate) const ARG_REG3: u8 = NUM_TOTAL_REGISTERS - 8;
-pub(crate) const ARG_REG4: u8 = NUM_TOTAL_REGISTERS - 9;
-pub(crate) const ARG_REG5: u8 = NUM_TOTAL_REGISTERS - 10;
+pub(crate) const ARG_REG0: u8 = NUM_TOTAL_REGISTERS - 6;
+pub(crate) const ARG_REG1: u8 = NUM_TOTAL_REGISTERS - 7;
+pub(crate) const ARG_REG2: u8 = NUM_TOTAL_REGISTERS - 8;
+pub(crate) const ARG_REG3: u8 = NUM_TOTAL_REGISTERS - 9;
+pub(crate) const ARG_REG4: u8 = NUM_TOTAL_REGISTERS - 10;
+pub(crate) const ARG_REG5: u8 = NUM_TOTAL_REGISTERS - 11;
 
 pub(crate) const NUM_ALLOCATABLE_REGISTERS: u8 =
     NUM_FREE_REGISTERS - NUM_COMPILER_RESERVED_REGISTERS;
@@ -3,7 +3,7 @@ use crate::asm_generation::from_ir::ir_type_size_in_bytes;
 use sway_ir::{Constant, ConstantValue, Context};
 
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
     fmt::{self, Write},
 };
 
@@ -218,6 +218,17 @@ impl DataSection {
             .map(|entry| entry.has_copy_type())
     }
 
+    /// Returns the value of a specific [DataId] if it is a word which is fixed at compile time,
+    /// i.e., not a configuration-time constant which may be overridden in the final binary.
+    pub(crate) fn get_fixed_word(&self, id: &DataId) -> Option<u64> {
+        self.value_pairs
+            .get(id.0 as usize)
+            .and_then(|entry| match entry.value {
+                Datum::Word(value) if entry.name.is_none() => Some(value),
+                _ => None,
+            })
+    }
+
     /// When generating code, sometimes a hard-coded data pointer is needed to reference
     /// static values that have a length longer than one word.
     /// This method appends pointers to the end of the data section (thus, not altering the data
@@ -229,6 +240,25 @@ impl DataSection {
         self.insert_data_value(Entry::new_word(pointer_value, None, None))
     }
 
+    /// Remove the entries which aren't `referenced`, other than the configuration-time constants,
+    /// which remain so that they may be overridden in the final binary.  Returns the new index of
+    /// each entry by its previous index, or `None` if the entry was removed.
+    pub(crate) fn remove_unreferenced(&mut self, referenced: &HashSet<u32>) -> Vec<Option<u32>> {
+        let mut new_ids = Vec::with_capacity(self.value_pairs.len());
+        for (id, entry) in std::mem::take(&mut self.value_pairs).into_iter().enumerate() {
+            if entry.name.is_some() || referenced.contains(&(id as u32)) {
+                new_ids.push(Some(self.value_pairs.len() as u32));
+                self.value_pairs.push(entry);
+            } else {
+                new_ids.push(None);
+            }
+        }
+        for id in self.config_map.values_mut() {
+            *id = new_ids[*id as usize].expect("configurables are never removed");
+        }
+        new_ids
+    }
+
     /// Given any data in the form of a [Literal] (using this type mainly because it includes type
     /// information and debug spans), insert it into the data section and return its offset as a
     /// [DataId].
@@ -47,10 +47,10 @@ pub struct FuelAsmBuilder<'ir> {
     pub(super) ptr_map: HashMap<LocalVar, Storage>,
 
     // The currently compiled function has an end label which is at the end of the function body
-    // but before the call cleanup, and a copy of the $retv for when the return value is a reference
-    // type and must be copied in memory.  Unless we have nested function declarations this vector
+    // but before the call cleanup, and the 'out' arg for when the return value is a reference type
+    // and must be written to memory.  Unless we have nested function declarations this vector
     // will usually have 0 or 1 entry.
-    pub(super) return_ctxs: Vec<(Label, VirtualRegister)>,
+    pub(super) return_ctxs: Vec<(Label, Option<Value>)>,
 
     // Stack size and base register for locals.
     pub(super) locals_ctxs: Vec<(u64, VirtualRegister)>,
@@ -540,6 +540,12 @@ impl<'ir> FuelAsmBuilder<'ir> {
             BinaryOpKind::Sub => Either::Left(VirtualOp::SUB(res_reg.clone(), val1_reg, val2_reg)),
             BinaryOpKind::Mul => Either::Left(VirtualOp::MUL(res_reg.clone(), val1_reg, val2_reg)),
             BinaryOpKind::Div => Either::Left(VirtualOp::DIV(res_reg.clone(), val1_reg, val2_reg)),
+            BinaryOpKind::And => Either::Left(VirtualOp::AND(res_reg.clone(), val1_reg, val2_reg)),
+            BinaryOpKind::Or => Either::Left(VirtualOp::OR(res_reg.clone(), val1_reg, val2_reg)),
+            BinaryOpKind::Xor => Either::Left(VirtualOp::XOR(res_reg.clone(), val1_reg, val2_reg)),
+            BinaryOpKind::Mod => Either::Left(VirtualOp::MOD(res_reg.clone(), val1_reg, val2_reg)),
+            BinaryOpKind::Lsh => Either::Left(VirtualOp::SLL(res_reg.clone(), val1_reg, val2_reg)),
+            BinaryOpKind::Rsh => Either::Left(VirtualOp::SRL(res_reg.clone(), val1_reg, val2_reg)),
         };
         self.cur_bytecode.push(Op {
             opcode,
@@ -616,15 +622,16 @@ impl<'ir> FuelAsmBuilder<'ir> {
         let lhs_reg = self.value_to_register(lhs_value);
         let rhs_reg = self.value_to_register(rhs_value);
         let res_reg = self.reg_seqr.next();
-        match pred {
-            Predicate::Equal => {
-                self.cur_bytecode.push(Op {
-                    opcode: Either::Left(VirtualOp::EQ(res_reg.clone(), lhs_reg, rhs_reg)),
-                    comment: String::new(),
-                    owning_span: self.md_mgr.val_to_span(self.context, *instr_val),
-                });
-            }
-        }
+        let opcode = match pred {
+            Predicate::Equal => VirtualOp::EQ(res_reg.clone(), lhs_reg, rhs_reg),
+            Predicate::LessThan => VirtualOp::LT(res_reg.clone(), lhs_reg, rhs_reg),
+            Predicate::GreaterThan => VirtualOp::GT(res_reg.clone(), lhs_reg, rhs_reg),
+        };
+        self.cur_bytecode.push(Op {
+            opcode: Either::Left(opcode),
+            comment: String::new(),
+            owning_span: self.md_mgr.val_to_span(self.context, *instr_val),
+        });
         self.reg_map.insert(*instr_val, res_reg);
     }
 
@@ -1219,6 +1226,11 @@ impl<'ir> FuelAsmBuilder<'ir> {
     }
 
     fn compile_load(&mut self, instr_val: &Value, src_val: &Value) -> CompileResult<()> {
+        if self.is_ptr_arg(src_val) {
+            self.compile_load_from_arg(instr_val, src_val);
+            return ok((), Vec::new(), Vec::new());
+        }
+
         let local_var = self.resolve_ptr(src_val);
         if local_var.value.is_none() {
             return local_var.map(|_| ());
@@ -1315,7 +1327,29 @@ impl<'ir> FuelAsmBuilder<'ir> {
         ok((), Vec::new(), Vec::new())
     }
 
-    fn compile_mem_copy(
+    // Copy types are loaded from a pointer arg, while the value of a ref type is the pointer
+    // itself.
+    fn compile_load_from_arg(&mut self, instr_val: &Value, src_val: &Value) {
+        let ptr_reg = self.value_to_register(src_val);
+        let load_type = src_val.get_type(self.context).unwrap();
+        if self.is_copy_type(&load_type) {
+            let instr_reg = self.reg_seqr.next();
+            self.cur_bytecode.push(Op {
+                opcode: Either::Left(VirtualOp::LW(
+                    instr_reg.clone(),
+                    ptr_reg,
+                    VirtualImmediate12 { value: 0 },
+                )),
+                comment: "load value".into(),
+                owning_span: self.md_mgr.val_to_span(self.context, *instr_val),
+            });
+            self.reg_map.insert(*instr_val, instr_reg);
+        } else {
+            self.reg_map.insert(*instr_val, ptr_reg);
+        }
+    }
+
+    pub(super) fn compile_mem_copy(
         &mut self,
         instr_val: &Value,
         dst_val: &Value,
@@ -1806,6 +1840,11 @@ impl<'ir> FuelAsmBuilder<'ir> {
         dst_val: &Value,
         stored_val: &Value,
     ) -> CompileResult<()> {
+        if self.is_ptr_arg(dst_val) {
+            self.compile_store_to_arg(instr_val, dst_val, stored_val);
+            return ok((), Vec::new(), Vec::new());
+        }
+
         let local_var = self.resolve_ptr(dst_val);
         if local_var.value.is_none() {
             return local_var.map(|_| ());
@@ -1952,6 +1991,55 @@ impl<'ir> FuelAsmBuilder<'ir> {
         ok((), Vec::new(), Vec::new())
     }
 
+    // Store to the memory pointed to by a pointer arg.
+    fn compile_store_to_arg(&mut self, instr_val: &Value, dst_val: &Value, stored_val: &Value) {
+        let owning_span = self.md_mgr.val_to_span(self.context, *instr_val);
+        let ptr_reg = self.value_to_register(dst_val);
+        let stored_reg = self.value_to_register(stored_val);
+        let store_type = dst_val.get_type(self.context).unwrap();
+        if self.is_copy_type(&store_type) {
+            self.cur_bytecode.push(Op {
+                opcode: Either::Left(VirtualOp::SW(
+                    ptr_reg,
+                    stored_reg,
+                    VirtualImmediate12 { value: 0 },
+                )),
+                comment: "store value".into(),
+                owning_span,
+            });
+        } else {
+            let store_size = ir_type_size_in_bytes(self.context, &store_type);
+            if store_size > compiler_constants::TWELVE_BITS {
+                let size_reg = self.reg_seqr.next();
+                self.number_to_reg(store_size, &size_reg, owning_span.clone());
+                self.cur_bytecode.push(Op {
+                    opcode: Either::Left(VirtualOp::MCP(ptr_reg, stored_reg, size_reg)),
+                    comment: "store value".into(),
+                    owning_span,
+                });
+            } else {
+                self.cur_bytecode.push(Op {
+                    opcode: Either::Left(VirtualOp::MCPI(
+                        ptr_reg,
+                        stored_reg,
+                        VirtualImmediate12 {
+                            value: store_size as u16,
+                        },
+                    )),
+                    comment: "store value".into(),
+                    owning_span,
+                });
+            }
+        }
+    }
+
+    // Args which are by-ref or have a ref type are pointers, held in a register.
+    fn is_ptr_arg(&self, ptr_val: &Value) -> bool {
+        ptr_val
+            .get_argument_type_and_byref(self.context)
+            .map_or(false, |(ty, by_ref)| by_ref || !self.is_copy_type(&ty))
+    }
+
     pub(crate) fn is_copy_type(&self, ty: &Type) -> bool {
         ty.is_unit(self.context) || ty.is_bool(self.context) | ty.is_uint(self.context)
     }
@@ -11,7 +11,7 @@ use crate::{
     decl_engine::DeclId,
     error::*,
     fuel_prelude::fuel_asm::GTFArgs,
-    size_bytes_in_words, size_bytes_round_up_to_word_alignment,
+    size_bytes_in_words,
 };
 
 use sway_ir::*;
@@ -28,8 +28,12 @@ use sway_types::Ident;
 /// - All other general purpose registers must be preserved.
 ///
 /// If the return value has a copy-type it can be returned in $retv directly.  If the return
-/// value is a ref-type its space must be allocated by the caller and its address passed into
-/// (and out of) the callee using $retv.
+/// value is a ref-type its space must be allocated by the caller and its address passed into the
+/// callee as the final 'out' argument, which the callee writes the value to.  $retv isn't used
+/// at all in this case, the caller already has the address of the returned value.
+///
+/// Ref-type arguments are passed by reference too, as the address of the caller's copy of the
+/// value.  Neither ref-type arguments nor return values are copied as part of the call.
 ///
 /// The general process for a call is therefore the following.  Not all steps are necessary,
 /// depending on how many args and local variables the callee has, and whether the callee makes
@@ -37,16 +41,17 @@ use sway_types::Ident;
 ///
 /// - Caller:
 ///   - Place function args into $rarg0 - $rargN and if necessary the stack.
-///   - Allocate the return value on the stack if it's a reference type.
+///   - Allocate the return value on the stack if it's a reference type and pass its address as
+///     the final arg.
 ///   - Place the return address into $reta
 ///   - Jump to function address.
 ///   - If necessary restore the stack to free args.
 /// - Callee:
 ///   - Save general purpose registers to the stack.
-///   - Save the args registers, return value pointer and return address.
+///   - Save the args registers and return address.
 ///   - Save room on the stack for locals.
 ///   - (Do work.)
-///   - Put the result in return value.
+///   - Put the result in $retv, or write it to the 'out' arg if it's a reference type.
 ///   - Restore the stack to free locals.
 ///   - Restore the return address.
 ///   - Restore the general purpose registers from the stack.
@@ -90,36 +95,56 @@ impl<'ir> FuelAsmBuilder<'ir> {
         });
         self.cur_bytecode.push(Op::unowned_jump_label(ret_label));
 
-        // Save the return value.
-        let ret_reg = self.reg_seqr.next();
-        self.cur_bytecode.push(Op {
-            opcode: Either::Left(VirtualOp::MOVE(
-                ret_reg.clone(),
-                VirtualRegister::Constant(ConstantRegister::CallReturnValue),
-            )),
-            comment: "copy the return value".into(),
-            owning_span: None,
-        });
-        self.reg_map.insert(*instr_val, ret_reg);
+        if function.get_ret_value_arg(self.context).is_some() {
+            // The return value was written to the 'out' arg, which is the value of the call.
+            let ret_reg = self.value_to_register(args.last().unwrap());
+            self.reg_map.insert(*instr_val, ret_reg);
+        } else {
+            // Save the return value.
+            let ret_reg = self.reg_seqr.next();
+            self.cur_bytecode.push(Op {
+                opcode: Either::Left(VirtualOp::MOVE(
+                    ret_reg.clone(),
+                    VirtualRegister::Constant(ConstantRegister::CallReturnValue),
+                )),
+                comment: "copy the return value".into(),
+                owning_span: None,
+            });
+            self.reg_map.insert(*instr_val, ret_reg);
+        }
     }
 
     pub(super) fn compile_ret_from_call(&mut self, instr_val: &Value, ret_val: &Value) {
-        // Move the result into the return value register.
-        let owning_span = self.md_mgr.val_to_span(self.context, *instr_val);
-        let ret_reg = self.value_to_register(ret_val);
-        self.cur_bytecode.push(Op::register_move(
-            VirtualRegister::Constant(ConstantRegister::CallReturnValue),
-            ret_reg,
-            "set return value",
-            owning_span,
-        ));
-
-        // Jump to the end of the function.
-        let end_label = self
+        let (end_label, ret_value_arg) = *self
             .return_ctxs
             .last()
-            .expect("Calls guaranteed to save return context.")
-            .0;
+            .expect("Calls guaranteed to save return context.");
+
+        match ret_value_arg {
+            // The result is already in the 'out' arg, nothing to do.
+            Some(ret_value_arg) if ret_value_arg == *ret_val => (),
+
+            // Copy the result to the 'out' arg.
+            Some(ret_value_arg) => {
+                let ret_ty = ret_value_arg.get_type(self.context).unwrap();
+                let byte_len = ir_type_size_in_bytes(self.context, &ret_ty);
+                self.compile_mem_copy(instr_val, &ret_value_arg, ret_val, byte_len);
+            }
+
+            // Move the result into the return value register.
+            None => {
+                let owning_span = self.md_mgr.val_to_span(self.context, *instr_val);
+                let ret_reg = self.value_to_register(ret_val);
+                self.cur_bytecode.push(Op::register_move(
+                    VirtualRegister::Constant(ConstantRegister::CallReturnValue),
+                    ret_reg,
+                    "set return value",
+                    owning_span,
+                ));
+            }
+        }
+
+        // Jump to the end of the function.
         self.cur_bytecode.push(Op::jump_to_label(end_label));
     }
 
@@ -178,7 +203,7 @@ impl<'ir> FuelAsmBuilder<'ir> {
         }
 
         if func_is_entry {
-            self.compile_external_args(function)
+            self.compile_external_args(function, test_decl_id.is_some())
         } else {
             // Make copies of the arg registers.
             self.compile_fn_call_args(function)
@@ -186,23 +211,17 @@ impl<'ir> FuelAsmBuilder<'ir> {
 
         let reta = self.reg_seqr.next(); // XXX only do this if this function makes calls
         if !func_is_entry {
-            // Save $reta and $retv
+            // Save $reta.
             self.cur_bytecode.push(Op::register_move(
                 reta.clone(),
                 VirtualRegister::Constant(ConstantRegister::CallReturnAddress),
                 "save reta",
                 None,
             ));
-            let retv = self.reg_seqr.next();
-            self.cur_bytecode.push(Op::register_move(
-                retv.clone(),
-                VirtualRegister::Constant(ConstantRegister::CallReturnValue),
-                "save retv",
-                None,
-            ));
 
             // Store some info describing the call frame.
-            self.return_ctxs.push((end_label, retv));
+            self.return_ctxs
+                .push((end_label, function.get_ret_value_arg(self.context)));
         }
 
         self.init_locals(function);
@@ -298,7 +317,13 @@ impl<'ir> FuelAsmBuilder<'ir> {
     }
 
     // Handle loading the arguments of a contract call
-    fn compile_external_args(&mut self, function: Function) {
+    fn compile_external_args(&mut self, function: Function, is_test: bool) {
+        // Tests are always executed as scripts, regardless of the kind of program declaring them,
+        // so their arguments are always read from the script data.
+        let program_kind = match is_test {
+            true => ProgramKind::Script,
+            false => self.program_kind,
+        };
         match function.args_iter(self.context).count() {
             // Nothing to do if there are no arguments
             0 => (),
@@ -308,11 +333,11 @@ impl<'ir> FuelAsmBuilder<'ir> {
             1 => {
                 let (_, val) = function.args_iter(self.context).next().unwrap();
                 let single_arg_reg = self.value_to_register(val);
-                match self.program_kind {
+                match program_kind {
                     ProgramKind::Contract => self.read_args_base_from_frame(&single_arg_reg),
                     ProgramKind::Library => (), // Nothing to do here
                     ProgramKind::Script | ProgramKind::Predicate => {
-                        if let ProgramKind::Predicate = self.program_kind {
+                        if let ProgramKind::Predicate = program_kind {
                             self.read_args_base_from_predicate_data(&single_arg_reg);
                         } else {
                             self.read_args_base_from_script_data(&single_arg_reg);
@@ -340,7 +365,7 @@ impl<'ir> FuelAsmBuilder<'ir> {
             // Otherwise, the args are bundled together and pointed to by the base register.
             _ => {
                 let args_base_reg = self.reg_seqr.next();
-                match self.program_kind {
+                match program_kind {
                     ProgramKind::Contract => self.read_args_base_from_frame(&args_base_reg),
                     ProgramKind::Library => return, // Nothing to do here
                     ProgramKind::Predicate => {
@@ -643,8 +668,9 @@ impl<'ir> FuelAsmBuilder<'ir> {
         }
 
         // Reserve space on the stack (in bytes) for all our locals which require it.  Firstly save
-        // the current $sp.
-        let locals_base_reg = self.reg_seqr.next();
+        // the current $sp.  The locals base has its own reserved register so the register
+        // allocator may also use the stack frame for spilling.
+        let locals_base_reg = VirtualRegister::Constant(ConstantRegister::LocalsBase);
         self.cur_bytecode.push(Op::register_move(
             locals_base_reg.clone(),
             VirtualRegister::Constant(ConstantRegister::StackPointer),
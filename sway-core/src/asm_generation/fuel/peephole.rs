@@ -0,0 +1,615 @@
+//! A rule based peephole optimizer for the ops of a function once its registers are allocated.
+//!
+//! Each rule is a function given a [Window] onto the ops, which if it matches the ops at the start
+//! of the window returns how many of them it matched and the ops to replace them with.  The rules
+//! are listed in [RULES] and new ones may be added there.
+//!
+//! The ops are swept repeatedly, trying the rules in order at each op, until no rule applies.  The
+//! rules may query which registers are live after each op, which is recalculated for each sweep.
+//! So a rule must not change which registers are live outside of the ops it matches, nor which
+//! registers are live after its last op.
+
+use crate::asm_lang::{
+    allocated_ops::{AllocatedOpcode, AllocatedRegister},
+    AllocatedAbstractOp, ControlFlowOp, Label, VirtualImmediate12, VirtualImmediate18,
+};
+
+use super::{compiler_constants, data_section::DataSection};
+
+use either::Either;
+use std::collections::{HashMap, HashSet};
+
+/// The ops matched by a rule, from the start of the window, and their replacement.
+pub(crate) type Rewrite = (usize, Vec<AllocatedAbstractOp>);
+
+/// A peephole rule.
+pub(crate) type Rule = fn(&Window) -> Option<Rewrite>;
+
+/// The peephole rules, in the order they are tried.
+pub(crate) const RULES: &[Rule] = &[
+    remove_self_move,
+    fold_immediate_operand,
+    forward_stored_word,
+    remove_store_of_loaded_word,
+    load_small_constant_immediately,
+    thread_jumps,
+    remove_jump_to_next,
+];
+
+/// Apply the peephole [RULES] to `ops` until none apply.
+pub(crate) fn optimize(
+    mut ops: Vec<AllocatedAbstractOp>,
+    data_section: &DataSection,
+) -> Vec<AllocatedAbstractOp> {
+    loop {
+        let labels = get_label_indices(&ops);
+        let live_out = liveness_analysis(&ops, &labels);
+        let mut new_ops = Vec::with_capacity(ops.len());
+        let mut modified = false;
+
+        let mut idx = 0;
+        while idx < ops.len() {
+            let window = Window {
+                ops: &ops,
+                idx,
+                labels: &labels,
+                live_out: &live_out,
+                data_section,
+            };
+            match RULES.iter().find_map(|rule| rule(&window)) {
+                Some((len, replacement)) => {
+                    new_ops.extend(replacement);
+                    idx += len;
+                    modified = true;
+                }
+                None => {
+                    new_ops.push(ops[idx].clone());
+                    idx += 1;
+                }
+            }
+        }
+
+        ops = new_ops;
+        if !modified {
+            return ops;
+        }
+    }
+}
+
+/// A view of the ops of a function from a particular op, given to each rule.
+pub(crate) struct Window<'a> {
+    ops: &'a [AllocatedAbstractOp],
+    idx: usize,
+    labels: &'a HashMap<Label, usize>,
+    live_out: &'a [RegisterSet],
+    data_section: &'a DataSection,
+}
+
+impl<'a> Window<'a> {
+    /// The op `offset` ops from the start of the window.
+    fn op(&self, offset: usize) -> Option<&'a AllocatedAbstractOp> {
+        self.ops.get(self.idx + offset)
+    }
+
+    /// The opcode of the op `offset` ops from the start of the window, if it is a VM instruction.
+    fn opcode(&self, offset: usize) -> Option<&'a AllocatedOpcode> {
+        match self.op(offset).map(|op| &op.opcode) {
+            Some(Either::Left(opcode)) => Some(opcode),
+            _ => None,
+        }
+    }
+
+    /// Whether the value of `reg` is unused after the op `offset` ops from the start of the window.
+    fn is_dead_after(&self, reg: &AllocatedRegister, offset: usize) -> bool {
+        match reg {
+            AllocatedRegister::Allocated(reg) => self
+                .live_out
+                .get(self.idx + offset)
+                .map_or(false, |live| live & (1 << reg) == 0),
+            // The constant registers may be read implicitly by calls and the VM.
+            AllocatedRegister::Constant(_) => false,
+        }
+    }
+
+    /// The first op at or following `label` which isn't another label or a comment.
+    fn op_at_label(&self, label: &Label) -> Option<&'a AllocatedAbstractOp> {
+        self.ops[*self.labels.get(label)?..].iter().find(|op| {
+            !matches!(
+                op.opcode,
+                Either::Right(ControlFlowOp::Label(_) | ControlFlowOp::Comment)
+            )
+        })
+    }
+}
+
+/// Replace a single op with a new opcode, keeping its comment and span.
+fn replace_opcode(op: &AllocatedAbstractOp, opcode: AllocatedOpcode) -> Rewrite {
+    (
+        1,
+        vec![AllocatedAbstractOp {
+            opcode: Either::Left(opcode),
+            comment: op.comment.clone(),
+            owning_span: op.owning_span.clone(),
+        }],
+    )
+}
+
+// -------------------------------------------------------------------------------------------------
+// The rules.
+
+/// Remove a `MOVE` of a register to itself.
+fn remove_self_move(window: &Window) -> Option<Rewrite> {
+    match window.opcode(0)? {
+        AllocatedOpcode::MOVE(dst, src) if dst == src => Some((1, vec![])),
+        _ => None,
+    }
+}
+
+/// Fold a `MOVI` of a small immediate, used only by the following instruction, into that
+/// instruction if it has a form taking an immediate operand, e.g.:
+///
+/// ```text
+/// movi $r1 i5                 ->  addi $r0 $r2 i5
+/// add  $r0 $r2 $r1
+/// ```
+fn fold_immediate_operand(window: &Window) -> Option<Rewrite> {
+    use AllocatedOpcode::*;
+
+    let (tmp, imm) = match window.opcode(0)? {
+        MOVI(tmp, imm) if u64::from(imm.value) <= compiler_constants::TWELVE_BITS => (tmp, imm),
+        _ => return None,
+    };
+    let imm = VirtualImmediate12 {
+        value: imm.value as u16,
+    };
+
+    // The operand may be on either side for commutative instructions.
+    let commutative =
+        |dst: &AllocatedRegister, lhs: &AllocatedRegister, rhs: &AllocatedRegister| {
+            if rhs == tmp && lhs != tmp {
+                Some((dst.clone(), lhs.clone()))
+            } else if lhs == tmp && rhs != tmp {
+                Some((dst.clone(), rhs.clone()))
+            } else {
+                None
+            }
+        };
+    let rhs_only = |dst: &AllocatedRegister, lhs: &AllocatedRegister, rhs: &AllocatedRegister| {
+        (rhs == tmp && lhs != tmp).then(|| (dst.clone(), lhs.clone()))
+    };
+
+    let folded = match window.opcode(1)? {
+        ADD(d, l, r) => commutative(d, l, r).map(|(d, s)| (d.clone(), ADDI(d, s, imm))),
+        AND(d, l, r) => commutative(d, l, r).map(|(d, s)| (d.clone(), ANDI(d, s, imm))),
+        MUL(d, l, r) => commutative(d, l, r).map(|(d, s)| (d.clone(), MULI(d, s, imm))),
+        OR(d, l, r) => commutative(d, l, r).map(|(d, s)| (d.clone(), ORI(d, s, imm))),
+        XOR(d, l, r) => commutative(d, l, r).map(|(d, s)| (d.clone(), XORI(d, s, imm))),
+        DIV(d, l, r) => rhs_only(d, l, r).map(|(d, s)| (d.clone(), DIVI(d, s, imm))),
+        EXP(d, l, r) => rhs_only(d, l, r).map(|(d, s)| (d.clone(), EXPI(d, s, imm))),
+        MOD(d, l, r) => rhs_only(d, l, r).map(|(d, s)| (d.clone(), MODI(d, s, imm))),
+        SLL(d, l, r) => rhs_only(d, l, r).map(|(d, s)| (d.clone(), SLLI(d, s, imm))),
+        SRL(d, l, r) => rhs_only(d, l, r).map(|(d, s)| (d.clone(), SRLI(d, s, imm))),
+        SUB(d, l, r) => rhs_only(d, l, r).map(|(d, s)| (d.clone(), SUBI(d, s, imm))),
+        _ => None,
+    };
+
+    // The immediate must not be needed once the instruction has used it, which is the case if the
+    // instruction overwrites it.
+    let (dst, opcode) = folded?;
+    if dst != *tmp && !window.is_dead_after(tmp, 1) {
+        return None;
+    }
+    Some((2, replace_opcode(window.op(1)?, opcode).1))
+}
+
+/// Replace a `LW` of a word just stored by a `SW` with a `MOVE` of the stored register, e.g.:
+///
+/// ```text
+/// sw   $r0 $r1 i3             ->  sw   $r0 $r1 i3
+/// lw   $r2 $r0 i3                 move $r2 $r1
+/// ```
+fn forward_stored_word(window: &Window) -> Option<Rewrite> {
+    use AllocatedOpcode::*;
+
+    match (window.opcode(0)?, window.opcode(1)?) {
+        (SW(st_base, src, st_offset), LW(dst, ld_base, ld_offset))
+            if st_base == ld_base && st_offset.value == ld_offset.value =>
+        {
+            let mut ops = vec![window.op(0)?.clone()];
+            if dst != src {
+                ops.extend(replace_opcode(window.op(1)?, MOVE(dst.clone(), src.clone())).1);
+            }
+            Some((2, ops))
+        }
+        _ => None,
+    }
+}
+
+/// Remove a `SW` of a word just loaded by a `LW` from the same address, e.g.:
+///
+/// ```text
+/// lw   $r2 $r0 i3             ->  lw   $r2 $r0 i3
+/// sw   $r0 $r2 i3
+/// ```
+fn remove_store_of_loaded_word(window: &Window) -> Option<Rewrite> {
+    use AllocatedOpcode::*;
+
+    match (window.opcode(0)?, window.opcode(1)?) {
+        (LW(dst, ld_base, ld_offset), SW(st_base, src, st_offset))
+            if dst == src
+                && ld_base == st_base
+                && dst != ld_base
+                && ld_offset.value == st_offset.value =>
+        {
+            Some((2, vec![window.op(0)?.clone()]))
+        }
+        _ => None,
+    }
+}
+
+/// Replace a load of a small constant from the data section with a `MOVI`, e.g.:
+///
+/// ```text
+/// lw   $r0 data_3             ->  movi $r0 i42
+/// ```
+fn load_small_constant_immediately(window: &Window) -> Option<Rewrite> {
+    match window.opcode(0)? {
+        AllocatedOpcode::LWDataId(dst, data_id) => {
+            let value = window.data_section.get_fixed_word(data_id)?;
+            (value <= compiler_constants::EIGHTEEN_BITS).then(|| {
+                replace_opcode(
+                    window.op(0).unwrap(),
+                    AllocatedOpcode::MOVI(
+                        dst.clone(),
+                        VirtualImmediate18 {
+                            value: value as u32,
+                        },
+                    ),
+                )
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Retarget a jump to a label which is immediately followed by an unconditional jump, to the
+/// destination of that jump, e.g.:
+///
+/// ```text
+/// jnzi $r0 .1                 ->  jnzi $r0 .2
+/// ...
+/// .1
+/// ji   .2
+/// ```
+fn thread_jumps(window: &Window) -> Option<Rewrite> {
+    use ControlFlowOp::*;
+
+    let op = window.op(0)?;
+    let label = match &op.opcode {
+        Either::Right(Jump(label) | JumpIfNotZero(_, label) | JumpIfNotEq(_, _, label)) => label,
+        _ => return None,
+    };
+
+    // Follow the chain of jumps, giving up on a cycle.
+    let mut visited = HashSet::from([*label]);
+    let mut dest = *label;
+    while let Some(Either::Right(Jump(next))) = window.op_at_label(&dest).map(|op| &op.opcode) {
+        if !visited.insert(*next) {
+            return None;
+        }
+        dest = *next;
+    }
+    if dest == *label {
+        return None;
+    }
+
+    let opcode = match &op.opcode {
+        Either::Right(Jump(_)) => Jump(dest),
+        Either::Right(JumpIfNotZero(reg, _)) => JumpIfNotZero(reg.clone(), dest),
+        Either::Right(JumpIfNotEq(lhs, rhs, _)) => JumpIfNotEq(lhs.clone(), rhs.clone(), dest),
+        _ => unreachable!(),
+    };
+    Some((
+        1,
+        vec![AllocatedAbstractOp {
+            opcode: Either::Right(opcode),
+            comment: op.comment.clone(),
+            owning_span: op.owning_span.clone(),
+        }],
+    ))
+}
+
+/// Remove an unconditional jump to a label which immediately follows it, or is only preceded by
+/// other labels or comments.
+fn remove_jump_to_next(window: &Window) -> Option<Rewrite> {
+    let label = match &window.op(0)?.opcode {
+        Either::Right(ControlFlowOp::Jump(label)) => label,
+        _ => return None,
+    };
+    window.ops[window.idx + 1..]
+        .iter()
+        .map_while(|op| match &op.opcode {
+            Either::Right(ControlFlowOp::Label(next)) => Some(Some(next)),
+            Either::Right(ControlFlowOp::Comment) => Some(None),
+            _ => None,
+        })
+        .flatten()
+        .any(|next| next == label)
+        .then(|| (1, vec![]))
+}
+
+// -------------------------------------------------------------------------------------------------
+// Liveness analysis.
+
+/// A set of allocated registers, as a bit per register.
+type RegisterSet = u64;
+
+const ALL_REGISTERS: RegisterSet = RegisterSet::MAX;
+
+fn to_register_set<'a>(regs: impl IntoIterator<Item = &'a AllocatedRegister>) -> RegisterSet {
+    regs.into_iter().fold(0, |set, reg| match reg {
+        AllocatedRegister::Allocated(reg) => set | (1 << reg),
+        AllocatedRegister::Constant(_) => set,
+    })
+}
+
+fn get_label_indices(ops: &[AllocatedAbstractOp]) -> HashMap<Label, usize> {
+    ops.iter()
+        .enumerate()
+        .filter_map(|(idx, op)| match op.opcode {
+            Either::Right(ControlFlowOp::Label(label)) => Some((label, idx)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Find the registers which are live after each op.
+///
+/// Calls don't use or define the allocated registers, as they are saved and restored by the
+/// callee, and neither do returns as there are none left once the function's own saved registers
+/// are restored by its POPA.  Any other jumps to a register or to an immediate offset, from an
+/// `asm` block, are assumed to need every register.
+fn liveness_analysis(
+    ops: &[AllocatedAbstractOp],
+    labels: &HashMap<Label, usize>,
+) -> Vec<RegisterSet> {
+    use AllocatedOpcode::*;
+    use ControlFlowOp::*;
+
+    // The successors of each op, or `None` if they're unknown.
+    let successors = ops
+        .iter()
+        .enumerate()
+        .map(|(idx, op)| {
+            let next = (idx + 1 < ops.len()).then_some(idx + 1);
+            match &op.opcode {
+                Either::Left(RET(_) | RETD(..) | RVRT(_)) => Some(vec![]),
+                Either::Left(JMP(_) | JI(_) | JNE(..) | JNEI(..) | JNZI(..)) => None,
+                Either::Left(_) => Some(next.into_iter().collect()),
+                Either::Right(Jump(label)) => labels.get(label).map(|dest| vec![*dest]),
+                Either::Right(JumpIfNotZero(_, label) | JumpIfNotEq(_, _, label)) => labels
+                    .get(label)
+                    .map(|dest| next.into_iter().chain(Some(*dest)).collect()),
+                Either::Right(_) => Some(next.into_iter().collect()),
+            }
+        })
+        .collect::<Vec<_>>();
+    let (uses, defs): (Vec<_>, Vec<_>) = ops
+        .iter()
+        .map(|op| match &op.opcode {
+            Either::Left(opcode) => (
+                to_register_set(opcode.use_registers()),
+                to_register_set(opcode.def_registers()),
+            ),
+            // A POPA overwrites all the registers it restores.
+            Either::Right(PopAll(_)) => (0, ALL_REGISTERS),
+            Either::Right(opcode) => (
+                to_register_set(opcode.use_registers()),
+                to_register_set(opcode.def_registers()),
+            ),
+        })
+        .unzip();
+
+    let mut live_in = vec![0; ops.len()];
+    let mut live_out = vec![0; ops.len()];
+    let mut modified = true;
+    while modified {
+        modified = false;
+        for idx in (0..ops.len()).rev() {
+            let out = match &successors[idx] {
+                Some(succs) => succs.iter().fold(0, |out, succ| out | live_in[*succ]),
+                None => ALL_REGISTERS,
+            };
+            let r#in = uses[idx] | (out & !defs[idx]);
+            if out != live_out[idx] || r#in != live_in[idx] {
+                live_out[idx] = out;
+                live_in[idx] = r#in;
+                modified = true;
+            }
+        }
+    }
+
+    live_out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm_generation::fuel::data_section::Entry;
+    use crate::asm_lang::ConstantRegister;
+
+    fn reg(idx: u8) -> AllocatedRegister {
+        AllocatedRegister::Allocated(idx)
+    }
+
+    fn op(opcode: AllocatedOpcode) -> AllocatedAbstractOp {
+        AllocatedAbstractOp {
+            opcode: Either::Left(opcode),
+            comment: String::new(),
+            owning_span: None,
+        }
+    }
+
+    fn ctrl_op(opcode: ControlFlowOp<AllocatedRegister>) -> AllocatedAbstractOp {
+        AllocatedAbstractOp {
+            opcode: Either::Right(opcode),
+            comment: String::new(),
+            owning_span: None,
+        }
+    }
+
+    fn imm12(value: u16) -> VirtualImmediate12 {
+        VirtualImmediate12 { value }
+    }
+
+    fn imm18(value: u32) -> VirtualImmediate18 {
+        VirtualImmediate18 { value }
+    }
+
+    fn ret(reg: AllocatedRegister) -> AllocatedAbstractOp {
+        op(AllocatedOpcode::RET(reg))
+    }
+
+    fn check(ops: Vec<AllocatedAbstractOp>, data_section: &DataSection, expected: &[&str]) {
+        let optimized = optimize(ops, data_section)
+            .iter()
+            .map(|op| op.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(optimized, expected);
+    }
+
+    #[test]
+    fn fold_immediate() {
+        use AllocatedOpcode::*;
+
+        // The immediate is dead after the add.
+        check(
+            vec![
+                op(MOVI(reg(1), imm18(5))),
+                op(ADD(reg(0), reg(1), reg(2))),
+                ret(reg(0)),
+            ],
+            &DataSection::default(),
+            &["addi $r0 $r2 i5", "ret  $r0"],
+        );
+
+        // The immediate is overwritten by the sub.
+        check(
+            vec![
+                op(MOVI(reg(1), imm18(5))),
+                op(SUB(reg(1), reg(2), reg(1))),
+                ret(reg(1)),
+            ],
+            &DataSection::default(),
+            &["subi $r1 $r2 i5", "ret  $r1"],
+        );
+
+        // The immediate is used again, is too large, or is the wrong operand.
+        for ops in [
+            vec![
+                op(MOVI(reg(1), imm18(5))),
+                op(ADD(reg(0), reg(1), reg(2))),
+                op(ADD(reg(0), reg(0), reg(1))),
+                ret(reg(0)),
+            ],
+            vec![
+                op(MOVI(reg(1), imm18(5000))),
+                op(ADD(reg(0), reg(1), reg(2))),
+                ret(reg(0)),
+            ],
+            vec![
+                op(MOVI(reg(1), imm18(5))),
+                op(SUB(reg(0), reg(1), reg(2))),
+                ret(reg(0)),
+            ],
+        ] {
+            let expected = ops.iter().map(|op| op.to_string()).collect::<Vec<_>>();
+            let expected = expected.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+            check(ops, &DataSection::default(), &expected);
+        }
+    }
+
+    #[test]
+    fn store_and_load() {
+        use AllocatedOpcode::*;
+
+        check(
+            vec![
+                op(SW(reg(0), reg(1), imm12(3))),
+                op(LW(reg(2), reg(0), imm12(3))),
+                op(LW(reg(1), reg(0), imm12(4))),
+                op(SW(reg(0), reg(1), imm12(4))),
+                op(ADD(reg(0), reg(1), reg(2))),
+                ret(reg(0)),
+            ],
+            &DataSection::default(),
+            &[
+                "sw   $r0 $r1 i3",
+                "move $r2 $r1",
+                "lw   $r1 $r0 i4",
+                "add  $r0 $r1 $r2",
+                "ret  $r0",
+            ],
+        );
+    }
+
+    #[test]
+    fn small_constants() {
+        use AllocatedOpcode::*;
+
+        let mut data_section = DataSection::default();
+        let small = data_section.insert_data_value(Entry::new_word(42, None, None));
+        let large = data_section.insert_data_value(Entry::new_word(1 << 20, None, None));
+        let configurable =
+            data_section.insert_data_value(Entry::new_word(7, None, Some("C".to_string())));
+        check(
+            vec![
+                op(LWDataId(reg(0), small)),
+                op(LWDataId(reg(1), large)),
+                op(LWDataId(reg(2), configurable)),
+                op(RET(AllocatedRegister::Constant(ConstantRegister::Zero))),
+            ],
+            &data_section,
+            &[
+                "movi $r0 i42",
+                "lw   $r1 data_1",
+                "lw   $r2 data_2",
+                "ret  $zero",
+            ],
+        );
+    }
+
+    #[test]
+    fn jumps() {
+        check(
+            vec![
+                ctrl_op(ControlFlowOp::JumpIfNotZero(reg(0), Label(1))),
+                ctrl_op(ControlFlowOp::Jump(Label(2))),
+                ctrl_op(ControlFlowOp::Label(Label(2))),
+                ret(reg(0)),
+                ctrl_op(ControlFlowOp::Label(Label(1))),
+                ctrl_op(ControlFlowOp::Jump(Label(3))),
+                ctrl_op(ControlFlowOp::Label(Label(3))),
+                ctrl_op(ControlFlowOp::Label(Label(4))),
+                ret(reg(1)),
+                ctrl_op(ControlFlowOp::Label(Label(5))),
+                ctrl_op(ControlFlowOp::Jump(Label(6))),
+                ctrl_op(ControlFlowOp::Label(Label(6))),
+                ctrl_op(ControlFlowOp::Jump(Label(5))),
+            ],
+            &DataSection::default(),
+            &[
+                "jnzi $r0 .3",
+                ".2",
+                "ret  $r0",
+                ".1",
+                ".3",
+                ".4",
+                "ret  $r1",
+                ".5",
+                ".6",
+                "ji  .5",
+            ],
+        );
+    }
+}
@@ -1,14 +1,20 @@
 use crate::{
-    asm_generation::fuel::compiler_constants,
+    asm_generation::fuel::{
+        compiler_constants,
+        data_section::{DataSection, Entry},
+    },
     asm_lang::{
-        allocated_ops::AllocatedRegister, virtual_register::*, ControlFlowOp, Label, Op, VirtualOp,
+        allocated_ops::AllocatedRegister, virtual_register::*, ControlFlowOp, Label, Op,
+        VirtualImmediate12, VirtualImmediate24, VirtualOp,
     },
 };
 
 use either::Either;
-use petgraph::graph::{node_index, NodeIndex};
-use rustc_hash::FxHashSet;
-use std::collections::{BTreeSet, HashMap};
+use petgraph::graph::NodeIndex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use sway_error::error::CompileError;
+use sway_types::Span;
 
 pub type InterferenceGraph =
     petgraph::stable_graph::StableGraph<Option<VirtualRegister>, (), petgraph::Undirected>;
@@ -386,45 +392,88 @@ pub(crate) fn coalesce_registers(
 /// 1. Pick any node n such that degree(n) < k and put it on the stack along with its neighbors.
 /// 2. Remove node n and all its edges from the graph
 ///    - This may make some new nodes have fewer than k neighbours which is nice.
-/// 3. If some vertex n still has k or more neighbors, then the graph is not k colorable, and we
-///    have to spill. For now, we will just error out, but we may be able to do better in the
-///    future.
+/// 3. If every remaining node has k or more neighbors, then the graph may not be k colorable, and
+///    we pick the node which is cheapest to spill, relative to its number of neighbours, and put
+///    it on the stack anyway, then continue from step 1.
 /// ===============================================================================================
 ///
-/// As we don't implement spilling just yet, I've modified the algorithm above to assume k=infinity
-/// and moving the colorability checking until the register assignment phase. The reason for this
-/// is that the algorithm above can be too conservative and may bail our early even though a valid
-/// assignment is actually available. We can revisit this decision when decide to implement
-/// spilling.
+/// The spill candidates are pushed optimistically, as they may still be assigned a register if
+/// enough of their neighbors end up sharing registers.  Only those which can't be are actually
+/// spilled, by `assign_registers()`.
 ///
 pub(crate) fn color_interference_graph(
     interference_graph: &mut InterferenceGraph,
+    spill_costs: &FxHashMap<VirtualRegister, f64>,
 ) -> Vec<(VirtualRegister, BTreeSet<VirtualRegister>)> {
-    let mut stack = Vec::with_capacity(interference_graph.node_count());
-
-    // Raw for loop here is safe because we are not actually removing any nodes from the graph at
-    // any point (i.e. we're never calling `remove_node()`). This means that each `index` below
-    // correspond to a valid `NodeIndex` in the graph.
-    for index in 0..interference_graph.node_count() {
-        // Convert to a `NodeIndex`
-        let node = node_index(index);
-
-        // Nodes with weight `None` are dead
-        if interference_graph[node].is_none() {
-            continue;
+    let k = compiler_constants::NUM_ALLOCATABLE_REGISTERS as usize;
+
+    // Nodes with weight `None` are dead, having been coalesced into another node.  Coalescing may
+    // also have left duplicate edges, so gather the distinct live neighbors of each live node.
+    let neighbors: FxHashMap<NodeIndex, BTreeSet<NodeIndex>> = interference_graph
+        .node_indices()
+        .filter(|node| interference_graph[*node].is_some())
+        .map(|node| {
+            let node_neighbors = interference_graph
+                .neighbors(node)
+                .filter(|n| *n != node && interference_graph[*n].is_some())
+                .collect();
+            (node, node_neighbors)
+        })
+        .collect();
+    let mut degrees: FxHashMap<NodeIndex, usize> = neighbors
+        .iter()
+        .map(|(node, node_neighbors)| (*node, node_neighbors.len()))
+        .collect();
+    let mut remaining: BTreeSet<NodeIndex> = neighbors.keys().cloned().collect();
+    let mut colorable: Vec<NodeIndex> = remaining
+        .iter()
+        .filter(|node| degrees[node] < k)
+        .cloned()
+        .collect();
+
+    let spill_cost = |node: &NodeIndex, degree: usize| {
+        let reg = interference_graph[*node].as_ref().unwrap();
+        spill_costs.get(reg).cloned().unwrap_or(0.0) / degree as f64
+    };
+
+    let mut stack = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let node = colorable
+            .pop()
+            .or_else(|| {
+                remaining
+                    .iter()
+                    .min_by(|a, b| {
+                        spill_cost(a, degrees[a])
+                            .partial_cmp(&spill_cost(b, degrees[b]))
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .cloned()
+            })
+            .unwrap();
+
+        remaining.remove(&node);
+        for neighbor in &neighbors[&node] {
+            if remaining.contains(neighbor) {
+                let degree = degrees.get_mut(neighbor).unwrap();
+                *degree -= 1;
+                if *degree == k - 1 {
+                    colorable.push(*neighbor);
+                }
+            }
         }
 
-        // Grab all neighbors with node weight not equal to `None`
-        let neighbors = interference_graph
-            .neighbors(node)
-            .filter_map(|n| interference_graph[n].clone())
-            .collect();
-
         // Build the stack
-        stack.push((interference_graph[node].clone().unwrap(), neighbors));
+        let node_neighbors = neighbors[&node]
+            .iter()
+            .map(|neighbor| interference_graph[*neighbor].clone().unwrap())
+            .collect();
+        stack.push((interference_graph[node].clone().unwrap(), node_neighbors));
+    }
 
-        // Remove `node` by setting its weight to `None`.
-        interference_graph[node] = None;
+    // Remove the colored nodes by setting their weight to `None`.
+    for node in neighbors.keys() {
+        interference_graph[*node] = None;
     }
 
     stack
@@ -436,10 +485,14 @@ pub(crate) fn color_interference_graph(
 /// the neighbors of v (available from the stack) and the list of virtual registers already used by
 /// r (available in the used_by field) is empty.
 ///
+/// If no register is available for some virtual registers then they must be spilled, and they are
+/// returned as the error.
+///
 pub(crate) fn assign_registers(
     stack: &mut Vec<(VirtualRegister, BTreeSet<VirtualRegister>)>,
-) -> RegisterPool {
+) -> Result<RegisterPool, BTreeSet<VirtualRegister>> {
     let mut pool = RegisterPool::init();
+    let mut spills = BTreeSet::new();
     while let Some((reg, neighbors)) = stack.pop() {
         if matches!(reg, VirtualRegister::Virtual(_)) {
             let available =
@@ -452,15 +505,326 @@ pub(crate) fn assign_registers(
             if let Some(RegisterAllocationStatus { reg: _, used_by }) = available {
                 used_by.insert(reg.clone());
             } else {
-                // Error out for now if no available register is found
-                unimplemented!(
-                    "The allocator cannot resolve a register mapping for this program. \
-                     This is a temporary artifact of the extremely early stage version \
-                     of this language. Try to lower the number of variables you use."
-                );
+                spills.insert(reg);
+            }
+        }
+    }
+
+    if spills.is_empty() {
+        Ok(pool)
+    } else {
+        Err(spills)
+    }
+}
+
+/// The relative cost of each access to a register within a loop, over an access outside of any
+/// loop.
+const LOOP_ACCESS_WEIGHT: f64 = 10.0;
+
+/// Estimate the cost of spilling each virtual register used by `ops`, as the number of loads and
+/// stores it would take, weighted by the loop depth at which they are made.  The loops are found
+/// from the backward jumps; an op is within a loop if it is between a jump and a label before it
+/// which it jumps to.
+///
+/// The temporaries replacing registers which have already been spilled are given an infinite
+/// cost, as spilling them again wouldn't help.  Rematerializable registers are cheaper, as they
+/// don't need to be stored.
+///
+pub(crate) fn compute_spill_costs(
+    ops: &[Op],
+    spill_temps: &FxHashSet<VirtualRegister>,
+) -> FxHashMap<VirtualRegister, f64> {
+    let label_to_index: HashMap<Label, usize> = ops
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, op)| match op.opcode {
+            Either::Right(ControlFlowOp::Label(label)) => Some((label, idx)),
+            _ => None,
+        })
+        .collect();
+
+    // Count the loops containing each op, by marking where each starts and ends.
+    let mut depth_deltas = vec![0_i32; ops.len() + 1];
+    for (idx, op) in ops.iter().enumerate() {
+        for succ in op.successors(idx, ops, &label_to_index) {
+            if succ <= idx {
+                depth_deltas[succ] += 1;
+                depth_deltas[idx + 1] -= 1;
             }
         }
     }
 
-    pool
+    let remats = get_rematerializable_registers(ops);
+    let mut costs: FxHashMap<VirtualRegister, f64> = FxHashMap::default();
+    let mut depth = 0;
+    for (op, depth_delta) in ops.iter().zip(depth_deltas) {
+        depth += depth_delta;
+        let weight = LOOP_ACCESS_WEIGHT.powi(depth);
+        let defs = op.def_registers();
+        for reg in op.registers() {
+            if !matches!(reg, VirtualRegister::Virtual(_)) {
+                continue;
+            }
+            // A rematerializable register isn't stored, only recomputed where it's used.
+            let cost = if remats.contains_key(reg) && defs.contains(reg) {
+                0.0
+            } else {
+                weight
+            };
+            *costs.entry(reg.clone()).or_default() += cost;
+        }
+    }
+
+    for reg in spill_temps {
+        costs.insert(reg.clone(), f64::INFINITY);
+    }
+    costs
+}
+
+/// Find the virtual registers which are defined only once, to a constant value, and so may be
+/// spilled by recomputing them where they're used rather than storing them.
+fn get_rematerializable_registers(ops: &[Op]) -> FxHashMap<&VirtualRegister, &Op> {
+    let mut defs: FxHashMap<&VirtualRegister, Vec<&Op>> = FxHashMap::default();
+    for op in ops {
+        for reg in op.def_registers() {
+            defs.entry(reg).or_default().push(op);
+        }
+    }
+    defs.into_iter()
+        .filter_map(|(reg, reg_defs)| match reg_defs.as_slice() {
+            [def @ Op {
+                opcode: Either::Left(VirtualOp::MOVI(_, _) | VirtualOp::LWDataId(_, _)),
+                ..
+            }] if matches!(reg, VirtualRegister::Virtual(_)) => Some((reg, *def)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rewrite `ops` so that each of the `spills` registers is kept in the stack rather than a
+/// register between its uses.  Each is stored to its own slot in the function's stack frame after
+/// it is defined and loaded back before it is used, or if it is a constant it is rematerialized
+/// before each use instead.  Each op accessing a spilled register is given a new temporary
+/// register in its place, with a very short live range, and these are added to `temps`.
+///
+/// The slots are placed after the locals, addressed from the locals base register, and the stack
+/// frame is grown to include them.  As established by `init_locals()` and `drop_locals()`, the
+/// locals base is set once, followed by the reservation of the locals if there are any, and the
+/// locals are freed, if there are any, just before `$reta` is restored at the end of a non-entry
+/// function, which is then followed by a POPA.  Registers defined before the frame is set up, such
+/// as the copies of the arguments, are stored as soon as it is.
+///
+/// Fails if the stack frame including the slots is too large to be reserved by a CFEI.
+///
+pub(crate) fn spill(
+    ops: &[Op],
+    spills: &BTreeSet<VirtualRegister>,
+    temps: &mut FxHashSet<VirtualRegister>,
+    data_section: &mut DataSection,
+) -> Result<Vec<Op>, CompileError> {
+    let remats = get_rematerializable_registers(ops);
+    let slots: FxHashMap<&VirtualRegister, u64> = spills
+        .iter()
+        .filter(|reg| !remats.contains_key(reg))
+        .enumerate()
+        .map(|(slot, reg)| (reg, slot as u64))
+        .collect();
+
+    // Find the frame.
+    let locals_base = VirtualRegister::Constant(ConstantRegister::LocalsBase);
+    let base_idx = ops
+        .iter()
+        .position(
+            |op| matches!(&op.opcode, Either::Left(VirtualOp::MOVE(dst, _)) if *dst == locals_base),
+        )
+        .expect("Functions always set their locals base.");
+    let (locals_size, cfe_idx) = match ops.get(base_idx + 1).map(|op| &op.opcode) {
+        Some(Either::Left(VirtualOp::CFEI(size))) => (size.value as u64, Some(base_idx + 1)),
+        _ => (0, None),
+    };
+    let cfs_idx = ops
+        .iter()
+        .position(|op| matches!(op.opcode, Either::Right(ControlFlowOp::PopAll(_))))
+        .and_then(|popa_idx| {
+            (popa_idx.saturating_sub(2)..popa_idx)
+                .rev()
+                .find(|idx| matches!(ops[*idx].opcode, Either::Left(VirtualOp::CFSI(_))))
+        });
+
+    let frame_size = locals_size + slots.len() as u64 * 8;
+    let frame_size_imm = VirtualImmediate24::new(frame_size, Span::dummy()).map_err(|_| {
+        CompileError::InternalOwned(
+            format!(
+                "The stack frame of {frame_size} bytes needed for the locals and spilled \
+                registers is too large."
+            ),
+            Span::dummy(),
+        )
+    })?;
+
+    let mut spilled = Vec::with_capacity(ops.len() * 2);
+    let mut new_temp = |reg: &VirtualRegister| {
+        let name = match reg {
+            VirtualRegister::Virtual(name) => name,
+            VirtualRegister::Constant(_) => unreachable!("Only virtual registers are spilled."),
+        };
+        let temp = VirtualRegister::Virtual(format!("{name}_spill{}", temps.len()));
+        temps.insert(temp.clone());
+        temp
+    };
+    let mut pre_frame_temps: BTreeMap<&VirtualRegister, VirtualRegister> = BTreeMap::new();
+    for (idx, op) in ops.iter().enumerate() {
+        if Some(idx) == cfe_idx || Some(idx) == cfs_idx {
+            // These are replaced below.
+            continue;
+        }
+
+        if matches!(op.opcode, Either::Right(ControlFlowOp::PopAll(_))) {
+            // Free the whole frame only now, after any reloads needed at the end of the function.
+            spilled.push(Op::unowned_new_with_comment(
+                VirtualOp::CFSI(frame_size_imm.clone()),
+                format!("free {frame_size} bytes for locals and spills"),
+            ));
+            spilled.push(op.clone());
+            continue;
+        }
+
+        // Remove the definitions of rematerialized registers.
+        let defs = op.def_registers();
+        if !defs.is_empty()
+            && defs
+                .iter()
+                .all(|reg| remats.contains_key(reg) && spills.contains(reg))
+        {
+            continue;
+        }
+
+        // Replace the spilled registers with temporaries, loading or recomputing those used.
+        let mut reg_map = HashMap::new();
+        for reg in op.registers() {
+            if !spills.contains(reg) {
+                continue;
+            }
+            let temp = if idx < base_idx && slots.contains_key(reg) {
+                // Until the frame is set up the same temporary is used throughout.
+                pre_frame_temps
+                    .entry(reg)
+                    .or_insert_with(|| new_temp(reg))
+                    .clone()
+            } else {
+                new_temp(reg)
+            };
+            if op.use_registers().contains(reg) {
+                match remats.get(reg) {
+                    Some(def) => {
+                        let def_map = HashMap::from([(reg, &temp)]);
+                        spilled.push(Op {
+                            comment: "rematerialize spilled register".into(),
+                            ..def.update_register(&def_map)
+                        });
+                    }
+                    None if idx > base_idx => {
+                        spilled.extend(spill_access(
+                            &locals_base,
+                            locals_size,
+                            slots[reg],
+                            temp.clone(),
+                            false,
+                            || new_temp(reg),
+                            data_section,
+                        ));
+                    }
+                    None => (),
+                }
+            }
+            reg_map.insert(reg, temp);
+        }
+        let reg_map = reg_map.iter().map(|(reg, temp)| (*reg, temp)).collect();
+        spilled.push(op.update_register(&reg_map));
+
+        // Store the spilled registers defined by this op.
+        if idx > base_idx {
+            for reg in defs {
+                if let Some(slot) = slots.get(reg) {
+                    spilled.extend(spill_access(
+                        &locals_base,
+                        locals_size,
+                        *slot,
+                        reg_map[reg].clone(),
+                        true,
+                        || new_temp(reg),
+                        data_section,
+                    ));
+                }
+            }
+        }
+
+        if idx == base_idx {
+            spilled.push(Op::unowned_new_with_comment(
+                VirtualOp::CFEI(frame_size_imm.clone()),
+                format!("allocate {frame_size} bytes for locals and spills"),
+            ));
+            for (reg, temp) in &pre_frame_temps {
+                spilled.extend(spill_access(
+                    &locals_base,
+                    locals_size,
+                    slots[reg],
+                    temp.clone(),
+                    true,
+                    || new_temp(reg),
+                    data_section,
+                ));
+            }
+        }
+    }
+
+    Ok(spilled)
+}
+
+/// Create the ops to store or load `reg` to or from a spill slot.
+///
+/// Slots beyond the reach of the LW/SW immediate are addressed via a register holding their
+/// address, computed from their offset which is loaded from the data section.  A reload computes
+/// the address in `reg` itself, which it then overwrites, while a store computes it in a new
+/// temporary from `new_temp`, so that no register which may be live is clobbered.
+fn spill_access(
+    locals_base: &VirtualRegister,
+    locals_size: u64,
+    slot: u64,
+    reg: VirtualRegister,
+    is_store: bool,
+    new_temp: impl FnOnce() -> VirtualRegister,
+    data_section: &mut DataSection,
+) -> Vec<Op> {
+    let mut ops = Vec::new();
+
+    let offset = locals_size / 8 + slot;
+    let (addr, imm) = if offset <= compiler_constants::TWELVE_BITS {
+        (
+            locals_base.clone(),
+            VirtualImmediate12 {
+                value: offset as u16,
+            },
+        )
+    } else {
+        let addr = if is_store { new_temp() } else { reg.clone() };
+        let offset_data_id =
+            data_section.insert_data_value(Entry::new_word(offset * 8, None, None));
+        ops.push(Op::unowned_new_with_comment(
+            VirtualOp::LWDataId(addr.clone(), offset_data_id),
+            "spill slot offset",
+        ));
+        ops.push(Op::unowned_new_with_comment(
+            VirtualOp::ADD(addr.clone(), locals_base.clone(), addr.clone()),
+            "spill slot address",
+        ));
+        (addr, VirtualImmediate12 { value: 0 })
+    };
+
+    ops.push(if is_store {
+        Op::unowned_new_with_comment(VirtualOp::SW(addr, reg, imm), "spill register")
+    } else {
+        Op::unowned_new_with_comment(VirtualOp::LW(reg, addr, imm), "reload spilled register")
+    });
+    ops
 }
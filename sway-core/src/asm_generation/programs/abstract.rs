@@ -18,6 +18,7 @@ use crate::{
 use sway_error::error::CompileError;
 
 use either::Either;
+use std::collections::HashSet;
 
 impl AbstractProgram {
     pub(crate) fn new(
@@ -69,13 +70,28 @@ impl AbstractProgram {
             .map(AbstractInstructionSet::verify)
             .collect::<Result<Vec<_>, _>>()?;
 
-        // Allocate the registers for each function.
-        let functions = abstract_functions
+        // Allocate the registers for each function and then optimise the allocated instructions.
+        let mut functions = abstract_functions
             .into_iter()
-            .map(|fn_ops| fn_ops.allocate_registers())
+            .map(|fn_ops| fn_ops.allocate_registers(&mut self.data_section))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(|fn_ops| fn_ops.optimize(&self.data_section))
             .map(AllocatedAbstractInstructionSet::emit_pusha_popa)
             .collect::<Vec<_>>();
 
+        // The optimisations may have replaced every load of some data section entries, e.g. with
+        // immediate values, so remove the entries which are no longer referenced.
+        let referenced = prologue
+            .referenced_data()
+            .chain(functions.iter().flat_map(|fn_ops| fn_ops.referenced_data()))
+            .collect::<HashSet<_>>();
+        let new_ids = self.data_section.remove_unreferenced(&referenced);
+        prologue.renumber_data(&new_ids);
+        for fn_ops in &mut functions {
+            fn_ops.renumber_data(&new_ids);
+        }
+
         // XXX need to verify that the stack use for each function is balanced.
 
         Ok(AllocatedProgram {
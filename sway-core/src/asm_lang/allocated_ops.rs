@@ -204,6 +204,114 @@ pub(crate) enum AllocatedOpcode {
 }
 
 impl AllocatedOpcode {
+    /// Returns a list of all registers *read* by instruction `self`.
+    pub(crate) fn use_registers(&self) -> BTreeSet<&AllocatedRegister> {
+        use AllocatedOpcode::*;
+        (match self {
+            /* Arithmetic/Logic (ALU) Instructions */
+            ADD(_r1, r2, r3) => vec![r2, r3],
+            ADDI(_r1, r2, _i) => vec![r2],
+            AND(_r1, r2, r3) => vec![r2, r3],
+            ANDI(_r1, r2, _i) => vec![r2],
+            DIV(_r1, r2, r3) => vec![r2, r3],
+            DIVI(_r1, r2, _i) => vec![r2],
+            EQ(_r1, r2, r3) => vec![r2, r3],
+            EXP(_r1, r2, r3) => vec![r2, r3],
+            EXPI(_r1, r2, _i) => vec![r2],
+            GT(_r1, r2, r3) => vec![r2, r3],
+            LT(_r1, r2, r3) => vec![r2, r3],
+            MLOG(_r1, r2, r3) => vec![r2, r3],
+            MOD(_r1, r2, r3) => vec![r2, r3],
+            MODI(_r1, r2, _i) => vec![r2],
+            MOVE(_r1, r2) => vec![r2],
+            MOVI(_r1, _i) => vec![],
+            MROO(_r1, r2, r3) => vec![r2, r3],
+            MUL(_r1, r2, r3) => vec![r2, r3],
+            MULI(_r1, r2, _i) => vec![r2],
+            NOOP => vec![],
+            NOT(_r1, r2) => vec![r2],
+            OR(_r1, r2, r3) => vec![r2, r3],
+            ORI(_r1, r2, _i) => vec![r2],
+            SLL(_r1, r2, r3) => vec![r2, r3],
+            SLLI(_r1, r2, _i) => vec![r2],
+            SRL(_r1, r2, r3) => vec![r2, r3],
+            SRLI(_r1, r2, _i) => vec![r2],
+            SUB(_r1, r2, r3) => vec![r2, r3],
+            SUBI(_r1, r2, _i) => vec![r2],
+            XOR(_r1, r2, r3) => vec![r2, r3],
+            XORI(_r1, r2, _i) => vec![r2],
+
+            /* Control Flow Instructions */
+            JMP(r1) => vec![r1],
+            JI(_im) => vec![],
+            JNE(r1, r2, r3) => vec![r1, r2, r3],
+            JNEI(r1, r2, _i) => vec![r1, r2],
+            JNZI(r1, _i) => vec![r1],
+            RET(r1) => vec![r1],
+
+            /* Memory Instructions */
+            ALOC(r1) => vec![r1],
+            CFEI(_imm) => vec![],
+            CFSI(_imm) => vec![],
+            LB(_r1, r2, _i) => vec![r2],
+            LW(_r1, r2, _i) => vec![r2],
+            MCL(r1, r2) => vec![r1, r2],
+            MCLI(r1, _imm) => vec![r1],
+            MCP(r1, r2, r3) => vec![r1, r2, r3],
+            MCPI(r1, r2, _imm) => vec![r1, r2],
+            MEQ(_r1, r2, r3, r4) => vec![r2, r3, r4],
+            SB(r1, r2, _i) => vec![r1, r2],
+            SW(r1, r2, _i) => vec![r1, r2],
+
+            /* Contract Instructions */
+            BAL(_r1, r2, r3) => vec![r2, r3],
+            BHEI(_r1) => vec![],
+            BHSH(r1, r2) => vec![r1, r2],
+            BURN(r1) => vec![r1],
+            CALL(r1, r2, r3, r4) => vec![r1, r2, r3, r4],
+            CB(r1) => vec![r1],
+            CCP(r1, r2, r3, r4) => vec![r1, r2, r3, r4],
+            CROO(r1, r2) => vec![r1, r2],
+            CSIZ(_r1, r2) => vec![r2],
+            LDC(r1, r2, r3) => vec![r1, r2, r3],
+            LOG(r1, r2, r3, r4) => vec![r1, r2, r3, r4],
+            LOGD(r1, r2, r3, r4) => vec![r1, r2, r3, r4],
+            MINT(r1) => vec![r1],
+            RETD(r1, r2) => vec![r1, r2],
+            RVRT(r1) => vec![r1],
+            SMO(r1, r2, r3, r4) => vec![r1, r2, r3, r4],
+            SCWQ(r1, _r2, r3) => vec![r1, r3],
+            SRW(_r1, _r2, r3) => vec![r3],
+            SRWQ(r1, _r2, r3, r4) => vec![r1, r3, r4],
+            SWW(r1, _r2, r3) => vec![r1, r3],
+            SWWQ(r1, _r2, r3, r4) => vec![r1, r3, r4],
+            TIME(_r1, r2) => vec![r2],
+            TR(r1, r2, r3) => vec![r1, r2, r3],
+            TRO(r1, r2, r3, r4) => vec![r1, r2, r3, r4],
+
+            /* Cryptographic Instructions */
+            ECR(r1, r2, r3) => vec![r1, r2, r3],
+            K256(r1, r2, r3) => vec![r1, r2, r3],
+            S256(r1, r2, r3) => vec![r1, r2, r3],
+
+            /* Other Instructions */
+            FLAG(r1) => vec![r1],
+            GM(_r1, _imm) => vec![],
+            GTF(_r1, r2, _i) => vec![r2],
+
+            /* Non-VM Instructions */
+            BLOB(_imm) => vec![],
+            DataSectionOffsetPlaceholder => vec![],
+            DataSectionRegisterLoadPlaceholder => vec![&AllocatedRegister::Constant(
+                ConstantRegister::InstructionStart,
+            )],
+            LWDataId(_r1, _i) => vec![],
+            Undefined => vec![],
+        })
+        .into_iter()
+        .collect()
+    }
+
     pub(crate) fn def_registers(&self) -> BTreeSet<&AllocatedRegister> {
         use AllocatedOpcode::*;
         (match self {
@@ -1,6 +1,6 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{fmt, path::PathBuf, str::FromStr, sync::Arc};
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(
     Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize, clap::ValueEnum,
@@ -11,6 +11,87 @@ pub enum BuildTarget {
     EVM,
 }
 
+/// The level of optimization applied to the IR of a program.
+///
+/// Levels are written as in the `optimization-level` field of a build profile, i.e. `0`, `1`, `2`,
+/// `"s"` or `"z"`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum OptLevel {
+    /// No optimizations. Only the inlining required to compile the program is performed.
+    Opt0,
+    /// The default set of optimizations.
+    #[default]
+    Opt1,
+    /// Optimize for speed, inlining larger functions at the expense of a larger program.
+    Opt2,
+    /// Optimize for size.
+    OptSize,
+    /// Optimize aggressively for size, only inlining functions which are called once.
+    OptMinSize,
+}
+
+impl fmt::Display for OptLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            OptLevel::Opt0 => "0",
+            OptLevel::Opt1 => "1",
+            OptLevel::Opt2 => "2",
+            OptLevel::OptSize => "s",
+            OptLevel::OptMinSize => "z",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for OptLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(OptLevel::Opt0),
+            "1" => Ok(OptLevel::Opt1),
+            "2" => Ok(OptLevel::Opt2),
+            "s" => Ok(OptLevel::OptSize),
+            "z" => Ok(OptLevel::OptMinSize),
+            _ => Err(format!(
+                "invalid optimization level `{s}`, expected one of 0, 1, 2, \"s\" or \"z\""
+            )),
+        }
+    }
+}
+
+impl Serialize for OptLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            OptLevel::Opt0 => serializer.serialize_u64(0),
+            OptLevel::Opt1 => serializer.serialize_u64(1),
+            OptLevel::Opt2 => serializer.serialize_u64(2),
+            OptLevel::OptSize | OptLevel::OptMinSize => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OptLevel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OptLevelVisitor;
+        impl<'de> de::Visitor<'de> for OptLevelVisitor {
+            type Value = OptLevel;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an optimization level of 0, 1, 2, \"s\" or \"z\"")
+            }
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<OptLevel, E> {
+                v.to_string().parse().map_err(E::custom)
+            }
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<OptLevel, E> {
+                v.to_string().parse().map_err(E::custom)
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<OptLevel, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_any(OptLevelVisitor)
+    }
+}
+
 /// Configuration for the overall build and compilation process.
 #[derive(Clone)]
 pub struct BuildConfig {
@@ -24,6 +105,7 @@ pub struct BuildConfig {
     pub(crate) print_finalized_asm: bool,
     pub(crate) print_ir: bool,
     pub(crate) include_tests: bool,
+    pub(crate) optimization_level: OptLevel,
 }
 
 impl BuildConfig {
@@ -65,6 +147,7 @@ impl BuildConfig {
             print_finalized_asm: false,
             print_ir: false,
             include_tests: false,
+            optimization_level: OptLevel::default(),
         }
     }
 
@@ -108,7 +191,67 @@ impl BuildConfig {
         }
     }
 
+    pub fn optimization_level(self, optimization_level: OptLevel) -> Self {
+        Self {
+            optimization_level,
+            ..self
+        }
+    }
+
     pub fn canonical_root_module(&self) -> Arc<PathBuf> {
         self.canonical_root_module.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opt_level_from_str() {
+        assert_eq!("0".parse(), Ok(OptLevel::Opt0));
+        assert_eq!("1".parse(), Ok(OptLevel::Opt1));
+        assert_eq!("2".parse(), Ok(OptLevel::Opt2));
+        assert_eq!("s".parse(), Ok(OptLevel::OptSize));
+        assert_eq!("z".parse(), Ok(OptLevel::OptMinSize));
+        assert!("3".parse::<OptLevel>().is_err());
+        assert!("S".parse::<OptLevel>().is_err());
+        assert!("".parse::<OptLevel>().is_err());
+    }
+
+    #[test]
+    fn opt_level_display_round_trips() {
+        for level in [
+            OptLevel::Opt0,
+            OptLevel::Opt1,
+            OptLevel::Opt2,
+            OptLevel::OptSize,
+            OptLevel::OptMinSize,
+        ] {
+            assert_eq!(level.to_string().parse(), Ok(level));
+        }
+    }
+
+    #[test]
+    fn opt_level_deserialize() {
+        let de = |s: &str| serde_json::from_str::<OptLevel>(s);
+        assert_eq!(de("0").unwrap(), OptLevel::Opt0);
+        assert_eq!(de("1").unwrap(), OptLevel::Opt1);
+        assert_eq!(de("2").unwrap(), OptLevel::Opt2);
+        assert_eq!(de("\"s\"").unwrap(), OptLevel::OptSize);
+        assert_eq!(de("\"z\"").unwrap(), OptLevel::OptMinSize);
+        assert!(de("3").is_err());
+        assert!(de("-1").is_err());
+        assert!(de("\"fast\"").is_err());
+    }
+
+    #[test]
+    fn opt_level_serialize() {
+        let ser = |level: OptLevel| serde_json::to_string(&level).unwrap();
+        assert_eq!(ser(OptLevel::Opt0), "0");
+        assert_eq!(ser(OptLevel::Opt1), "1");
+        assert_eq!(ser(OptLevel::Opt2), "2");
+        assert_eq!(ser(OptLevel::OptSize), "\"s\"");
+        assert_eq!(ser(OptLevel::OptMinSize), "\"z\"");
+    }
+}
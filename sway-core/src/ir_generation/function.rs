@@ -521,7 +521,13 @@ impl<'eng> FnCompiler<'eng> {
                     .get_storage_key()
                     .add_metadatum(context, span_md_idx))
             }
-            Intrinsic::Eq => {
+            Intrinsic::Eq | Intrinsic::Gt | Intrinsic::Lt => {
+                let pred = match kind {
+                    Intrinsic::Eq => Predicate::Equal,
+                    Intrinsic::Gt => Predicate::GreaterThan,
+                    Intrinsic::Lt => Predicate::LessThan,
+                    _ => unreachable!(),
+                };
                 let lhs = &arguments[0];
                 let rhs = &arguments[1];
                 let lhs_value = self.compile_expression(context, md_mgr, lhs)?;
@@ -529,7 +535,7 @@ impl<'eng> FnCompiler<'eng> {
                 Ok(self
                     .current_block
                     .ins(context)
-                    .cmp(Predicate::Equal, lhs_value, rhs_value))
+                    .cmp(pred, lhs_value, rhs_value))
             }
             Intrinsic::Gtf => {
                 // The index is just a Value
@@ -718,12 +724,27 @@ impl<'eng> FnCompiler<'eng> {
                     }
                 }
             }
-            Intrinsic::Add | Intrinsic::Sub | Intrinsic::Mul | Intrinsic::Div => {
+            Intrinsic::Add
+            | Intrinsic::Sub
+            | Intrinsic::Mul
+            | Intrinsic::Div
+            | Intrinsic::And
+            | Intrinsic::Or
+            | Intrinsic::Xor
+            | Intrinsic::Mod
+            | Intrinsic::Lsh
+            | Intrinsic::Rsh => {
                 let op = match kind {
                     Intrinsic::Add => BinaryOpKind::Add,
                     Intrinsic::Sub => BinaryOpKind::Sub,
                     Intrinsic::Mul => BinaryOpKind::Mul,
                     Intrinsic::Div => BinaryOpKind::Div,
+                    Intrinsic::And => BinaryOpKind::And,
+                    Intrinsic::Or => BinaryOpKind::Or,
+                    Intrinsic::Xor => BinaryOpKind::Xor,
+                    Intrinsic::Mod => BinaryOpKind::Mod,
+                    Intrinsic::Lsh => BinaryOpKind::Lsh,
+                    Intrinsic::Rsh => BinaryOpKind::Rsh,
                     _ => unreachable!(),
                 };
                 let lhs = &arguments[0];
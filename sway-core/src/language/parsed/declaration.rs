@@ -33,7 +33,7 @@ pub enum Declaration {
 }
 
 impl Declaration {
-    /// Checks if this `Declaration` is a test.
+    /// Checks if this `Declaration` is a test or a benchmark.
     pub(crate) fn is_test(&self) -> bool {
         if let Declaration::FunctionDeclaration(fn_decl) = self {
             fn_decl.is_test()
@@ -43,10 +43,10 @@ impl PartialEqWithEngines for FunctionParameter {
 }
 
 impl FunctionDeclaration {
-    /// Checks if this `FunctionDeclaration` is a test.
+    /// Checks if this `FunctionDeclaration` is a test or a benchmark.
     pub(crate) fn is_test(&self) -> bool {
         self.attributes
             .keys()
-            .any(|k| matches!(k, AttributeKind::Test))
+            .any(|k| matches!(k, AttributeKind::Test | AttributeKind::Bench))
     }
 }
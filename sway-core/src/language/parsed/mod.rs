@@ -62,14 +62,14 @@ pub enum AstNodeContent {
 }
 
 impl ParseTree {
-    /// Excludes all test functions from the parse tree.
+    /// Excludes all test and benchmark functions from the parse tree.
     pub(crate) fn exclude_tests(&mut self) {
         self.root_nodes.retain(|node| !node.is_test());
     }
 }
 
 impl AstNode {
-    /// Checks if this `AstNode` is a test.
+    /// Checks if this `AstNode` is a test or a benchmark.
     pub(crate) fn is_test(&self) -> bool {
         if let AstNodeContent::Declaration(decl) = &self.content {
             decl.is_test()
@@ -194,7 +194,8 @@ impl TyAstNode {
         }
     }
 
-    /// Check to see if this node is a function declaration of a function annotated as test.
+    /// Check to see if this node is a function declaration of a function annotated as test or
+    /// bench.
     pub(crate) fn is_test_function(&self, decl_engine: &DeclEngine) -> CompileResult<bool> {
         let mut warnings = vec![];
         let mut errors = vec![];
@@ -211,7 +212,8 @@ impl TyAstNode {
                     errors
                 );
                 ok(
-                    attributes.contains_key(&AttributeKind::Test),
+                    attributes.contains_key(&AttributeKind::Test)
+                        || attributes.contains_key(&AttributeKind::Bench),
                     warnings,
                     errors,
                 )
@@ -248,7 +250,7 @@ impl TyAstNode {
                     ..
                 } => {
                     let decl = decl_engine.get_function(decl_id.clone(), &decl_id.span())?;
-                    Ok(decl.visibility == Visibility::Public || decl.is_test())
+                    Ok(decl.visibility == Visibility::Public || decl.is_test() || decl.is_bench())
                 }
                 TyAstNode {
                     content: TyAstNodeContent::Declaration(TyDeclaration::TraitDeclaration(decl_id)),
@@ -338,6 +338,12 @@ impl TyFunctionDeclaration {
             .contains_key(&transform::AttributeKind::Test)
     }
 
+    /// Whether or not this function is a benchmark, i.e. decorated with `#[bench]`.
+    pub fn is_bench(&self) -> bool {
+        self.attributes
+            .contains_key(&transform::AttributeKind::Bench)
+    }
+
     pub fn inline(&self) -> Option<Inline> {
         match self
             .attributes
@@ -345,6 +351,7 @@ impl TyFunctionDeclaration {
             .last()?
             .args
             .first()?
+            .name
             .as_str()
         {
             INLINE_NEVER_NAME => Some(Inline::Never),
@@ -355,7 +362,7 @@ impl TyFunctionDeclaration {
 
     /// Whether or not this function describes a program entry point.
     pub fn is_entry(&self) -> bool {
-        self.is_main_entry() || self.is_test()
+        self.is_main_entry() || self.is_test() || self.is_bench()
     }
 }
 
@@ -41,7 +41,7 @@ impl TyModule {
         }
     }
 
-    /// All test functions within this module.
+    /// All test and benchmark functions within this module.
     pub fn test_fns<'a: 'b, 'b>(
         &'b self,
         decl_engine: &'a DeclEngine,
@@ -53,7 +53,7 @@ impl TyModule {
                 let fn_decl = decl_engine
                     .get_function(decl_id.clone(), &node.span)
                     .expect("no function declaration for ID");
-                if fn_decl.is_test() {
+                if fn_decl.is_test() || fn_decl.is_bench() {
                     return Some((fn_decl, decl_id.clone()));
                 }
             }
@@ -296,7 +296,7 @@ impl TyProgram {
         )
     }
 
-    /// All test function declarations within the program.
+    /// All test and benchmark function declarations within the program.
     pub fn test_fns<'a: 'b, 'b>(
         &'b self,
         decl_engine: &'a DeclEngine,
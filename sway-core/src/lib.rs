@@ -25,7 +25,8 @@ use crate::{error::*, source_map::SourceMap};
 pub use asm_generation::from_ir::compile_ir_to_asm;
 use asm_generation::FinalizedAsm;
 pub use asm_generation::{CompiledBytecode, FinalizedEntry};
-pub use build_config::{BuildConfig, BuildTarget};
+pub use build_config::{BuildConfig, BuildTarget, OptLevel};
+pub use sway_ir::size_bytes_round_up_to_word_alignment;
 use control_flow_analysis::ControlFlowGraph;
 use metadata::MetadataManager;
 use std::collections::HashMap;
@@ -33,9 +34,13 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use sway_error::handler::{ErrorEmitted, Handler};
 use sway_ir::{
-    create_const_combine_pass, create_dce_pass, create_func_dce_pass,
-    create_inline_in_non_predicate_pass, create_inline_in_predicate_pass, create_mem2reg_pass,
-    create_simplify_cfg_pass, Context, Kind, Module, PassManager, PassManagerConfig,
+    create_const_combine_pass, create_cse_pass, create_dce_pass, create_func_dce_pass,
+    create_inline_aggressively_in_non_predicate_pass, create_inline_in_non_predicate_pass,
+    create_inline_in_predicate_pass, create_inline_required_in_non_predicate_pass,
+    create_inline_single_calls_in_non_predicate_pass, create_licm_pass, create_loop_unroll_pass,
+    create_mem2reg_pass, create_ret_demotion_pass, create_simplify_cfg_pass, create_sroa_pass,
+    create_storage_forward_pass, merge_identical_functions, Context, Kind, Module, PassManager,
+    PassManagerConfig,
 };
 
 pub use semantic_analysis::namespace::{self, Namespace};
@@ -475,29 +480,8 @@ pub(crate) fn compile_ast_to_ir_to_asm(
 
     // Initialize the pass manager and a config for it.
     let mut pass_mgr = PassManager::default();
-    let mut pmgr_config = PassManagerConfig { to_run: vec![] };
-
-    // Register required passes.
-    let mem2reg = pass_mgr.register(create_mem2reg_pass());
-    let inline = if matches!(tree_type, TreeType::Predicate) {
-        pass_mgr.register(create_inline_in_predicate_pass())
-    } else {
-        pass_mgr.register(create_inline_in_non_predicate_pass())
-    };
-    let const_combine = pass_mgr.register(create_const_combine_pass());
-    let simplify_cfg = pass_mgr.register(create_simplify_cfg_pass());
-    let func_dce = pass_mgr.register(create_func_dce_pass());
-    let dce = pass_mgr.register(create_dce_pass());
-
-    // Configure to run our passes.
-    pmgr_config.to_run.push(mem2reg.to_string());
-    pmgr_config.to_run.push(inline.to_string());
-    pmgr_config.to_run.push(const_combine.to_string());
-    pmgr_config.to_run.push(simplify_cfg.to_string());
-    pmgr_config.to_run.push(const_combine.to_string());
-    pmgr_config.to_run.push(simplify_cfg.to_string());
-    pmgr_config.to_run.push(func_dce.to_string());
-    pmgr_config.to_run.push(dce.to_string());
+    let pmgr_config =
+        register_optimization_passes(&mut pass_mgr, &tree_type, build_config.optimization_level);
 
     // Run the passes.
     let res = CompileResult::with_handler(|handler| {
@@ -512,6 +496,28 @@ pub(crate) fn compile_ast_to_ir_to_asm(
     });
     check!(res, return err(warnings, errors), warnings, errors);
 
+    // Merge the functions left identical by the optimizations, reporting the code size saved.
+    if build_config.optimization_level != OptLevel::Opt0 {
+        for module in ir.module_iter() {
+            let report = merge_identical_functions(&mut ir, module);
+            for merged in &report.merged {
+                tracing::trace!(
+                    "Merged function {} into identical function {}.",
+                    merged.name,
+                    merged.merged_into
+                );
+            }
+            if !report.merged.is_empty() {
+                tracing::debug!(
+                    "  Merged {} identical function{}, saving an estimated {} bytes.",
+                    report.merged.len(),
+                    if report.merged.len() == 1 { "" } else { "s" },
+                    report.estimated_bytes_saved()
+                );
+            }
+        }
+    }
+
     if build_config.print_ir {
         tracing::info!("{}", ir);
     }
@@ -526,6 +532,76 @@ pub(crate) fn compile_ast_to_ir_to_asm(
     ok(final_asm, warnings, errors)
 }
 
+/// Register the IR passes run at the given optimization level with the pass manager, returning
+/// the configuration to run them with.
+fn register_optimization_passes(
+    pass_mgr: &mut PassManager,
+    tree_type: &TreeType,
+    optimization_level: OptLevel,
+) -> PassManagerConfig {
+    // Predicates cannot jump backwards, so all calls within them are inlined at every level.
+    let inline = if matches!(tree_type, TreeType::Predicate) {
+        create_inline_in_predicate_pass()
+    } else {
+        match optimization_level {
+            OptLevel::Opt0 => create_inline_required_in_non_predicate_pass(),
+            OptLevel::Opt1 | OptLevel::OptSize => create_inline_in_non_predicate_pass(),
+            OptLevel::Opt2 => create_inline_aggressively_in_non_predicate_pass(),
+            OptLevel::OptMinSize => create_inline_single_calls_in_non_predicate_pass(),
+        }
+    };
+    let inline = pass_mgr.register(inline);
+    let func_dce = pass_mgr.register(create_func_dce_pass());
+    if optimization_level == OptLevel::Opt0 {
+        return PassManagerConfig {
+            to_run: vec![inline.to_string(), func_dce.to_string()],
+        };
+    }
+
+    let mem2reg = pass_mgr.register(create_mem2reg_pass());
+    let sroa = pass_mgr.register(create_sroa_pass());
+    let ret_demotion = pass_mgr.register(create_ret_demotion_pass());
+    let storage_forward = pass_mgr.register(create_storage_forward_pass());
+    let const_combine = pass_mgr.register(create_const_combine_pass());
+    let cse = pass_mgr.register(create_cse_pass());
+    let licm = pass_mgr.register(create_licm_pass());
+    let simplify_cfg = pass_mgr.register(create_simplify_cfg_pass());
+    let dce = pass_mgr.register(create_dce_pass());
+
+    // Constant folding, common subexpression elimination, loop invariant code motion and CFG
+    // simplification each expose opportunities for the others, so they are run over a number of
+    // rounds.
+    let rounds = match optimization_level {
+        OptLevel::Opt1 => 2,
+        _ => 3,
+    };
+    // Splitting aggregates after inlining exposes their scalar fields to another round of
+    // promotion to registers.  The returned aggregates which remain whole are then written
+    // straight to their destinations by the calls which aren't inlined.  Storage accesses are
+    // forwarded and merged once the inlined accessors are in the same function.
+    let mut to_run = vec![
+        mem2reg,
+        inline,
+        sroa,
+        mem2reg,
+        ret_demotion,
+        storage_forward,
+    ];
+    for _ in 0..rounds {
+        to_run.extend([const_combine, cse, licm, simplify_cfg]);
+    }
+    // Unrolling trades code size for speed, so is only done at the highest level, followed by
+    // another round to clean up the unrolled iterations.
+    if optimization_level == OptLevel::Opt2 {
+        let loop_unroll = pass_mgr.register(create_loop_unroll_pass());
+        to_run.extend([loop_unroll, const_combine, cse, simplify_cfg]);
+    }
+    to_run.extend([func_dce, dce]);
+    PassManagerConfig {
+        to_run: to_run.into_iter().map(str::to_string).collect(),
+    }
+}
+
 /// Given input Sway source code, compile to [CompiledBytecode], containing the asm in bytecode form.
 pub fn compile_to_bytecode(
     engines: Engines<'_>,
@@ -830,6 +906,51 @@ fn test_unary_ordering() {
     };
 }
 
+#[test]
+fn test_optimization_passes() {
+    let passes = |tree_type: TreeType, optimization_level: OptLevel| {
+        let mut pass_mgr = PassManager::default();
+        let config = register_optimization_passes(&mut pass_mgr, &tree_type, optimization_level);
+        (config.to_run, pass_mgr.help_text())
+    };
+    let early = [
+        "mem2reg",
+        "inline",
+        "sroa",
+        "mem2reg",
+        "ret_demotion",
+        "storage_forward",
+    ];
+    let rounds = |n| ["constcombine", "cse", "licm", "simplifycfg"].repeat(n);
+    let unroll = ["loop_unroll", "constcombine", "cse", "simplifycfg"];
+    let late = ["func_dce", "dce"];
+
+    let (to_run, help) = passes(TreeType::Contract, OptLevel::Opt0);
+    assert_eq!(to_run, ["inline", "func_dce"]);
+    assert!(help.contains("inline only the function calls which cannot be compiled as calls."));
+
+    let (to_run, help) = passes(TreeType::Contract, OptLevel::Opt1);
+    assert_eq!(to_run, [&early[..], &rounds(2), &late].concat());
+    assert!(help.contains("inline function calls."));
+
+    let (to_run, help) = passes(TreeType::Contract, OptLevel::Opt2);
+    assert_eq!(to_run, [&early[..], &rounds(3), &unroll, &late].concat());
+    assert!(help.contains("inline function calls, including calls to larger functions."));
+
+    let (to_run, help) = passes(TreeType::Contract, OptLevel::OptSize);
+    assert_eq!(to_run, [&early[..], &rounds(3), &late].concat());
+    assert!(help.contains("inline function calls."));
+
+    let (to_run, help) = passes(TreeType::Contract, OptLevel::OptMinSize);
+    assert_eq!(to_run, [&early[..], &rounds(3), &late].concat());
+    assert!(help.contains("inline function calls to functions which are called only once."));
+
+    // Predicates can't jump backwards, so all calls are inlined even without optimizations.
+    let (to_run, help) = passes(TreeType::Predicate, OptLevel::Opt0);
+    assert_eq!(to_run, ["inline", "func_dce"]);
+    assert!(!help.contains("inline only the function calls which cannot be compiled as calls."));
+}
+
 /// Return an irrecoverable compile result deduping any errors and warnings.
 fn deduped_err<T>(warnings: Vec<CompileWarning>, errors: Vec<CompileError>) -> CompileResult<T> {
     err(dedup_unsorted(warnings), dedup_unsorted(errors))
@@ -37,7 +37,9 @@ impl ty::TyIntrinsicFunctionKind {
             Intrinsic::GetStorageKey => {
                 type_check_get_storage_key(ctx, kind, arguments, type_arguments, span)
             }
-            Intrinsic::Eq => type_check_eq(ctx, kind, arguments, span),
+            Intrinsic::Eq | Intrinsic::Gt | Intrinsic::Lt => {
+                type_check_cmp(ctx, kind, arguments, span)
+            }
             Intrinsic::Gtf => type_check_gtf(ctx, kind, arguments, type_arguments, span),
             Intrinsic::AddrOf => type_check_addr_of(ctx, kind, arguments, span),
             Intrinsic::StateClear => type_check_state_clear(ctx, kind, arguments, span),
@@ -49,9 +51,16 @@ impl ty::TyIntrinsicFunctionKind {
                 type_check_state_quad(ctx, kind, arguments, type_arguments, span)
             }
             Intrinsic::Log => type_check_log(ctx, kind, arguments, span),
-            Intrinsic::Add | Intrinsic::Sub | Intrinsic::Mul | Intrinsic::Div => {
-                type_check_binary_op(ctx, kind, arguments, type_arguments, span)
-            }
+            Intrinsic::Add
+            | Intrinsic::Sub
+            | Intrinsic::Mul
+            | Intrinsic::Div
+            | Intrinsic::And
+            | Intrinsic::Or
+            | Intrinsic::Xor
+            | Intrinsic::Mod
+            | Intrinsic::Lsh
+            | Intrinsic::Rsh => type_check_binary_op(ctx, kind, arguments, type_arguments, span),
             Intrinsic::Revert => type_check_revert(ctx, kind, arguments, type_arguments, span),
             Intrinsic::PtrAdd | Intrinsic::PtrSub => {
                 type_check_ptr_ops(ctx, kind, arguments, type_arguments, span)
@@ -270,7 +279,15 @@ fn type_check_get_storage_key(
 /// Signature: `__eq<T>(lhs: T, rhs: T) -> bool`
 /// Description: Returns whether `lhs` and `rhs` are equal.
 /// Constraints: `T` is `bool`, `u8`, `u16`, `u32`, `u64`, or `raw_ptr`.
-fn type_check_eq(
+///
+/// Signature: `__gt<T>(lhs: T, rhs: T) -> bool`
+/// Description: Returns whether `lhs` is greater than `rhs`.
+/// Constraints: `T` is `u8`, `u16`, `u32`, `u64`, or `raw_ptr`.
+///
+/// Signature: `__lt<T>(lhs: T, rhs: T) -> bool`
+/// Description: Returns whether `lhs` is less than `rhs`.
+/// Constraints: `T` is `u8`, `u16`, `u32`, `u64`, or `raw_ptr`.
+fn type_check_cmp(
     mut ctx: TypeCheckContext,
     kind: sway_ast::Intrinsic,
     arguments: Vec<Expression>,
@@ -312,10 +329,11 @@ fn type_check_eq(
         warnings,
         errors
     );
-    let is_valid_arg_ty = matches!(
-        arg_ty,
-        TypeInfo::UnsignedInteger(_) | TypeInfo::Boolean | TypeInfo::RawUntypedPtr
-    );
+    let is_valid_arg_ty = match arg_ty {
+        TypeInfo::UnsignedInteger(_) | TypeInfo::RawUntypedPtr => true,
+        TypeInfo::Boolean => matches!(kind, Intrinsic::Eq),
+        _ => false,
+    };
     if !is_valid_arg_ty {
         errors.push(CompileError::IntrinsicUnsupportedArgType {
             name: kind.to_string(),
@@ -987,6 +1005,30 @@ fn type_check_log(
 /// Signature: `__div<T>(lhs: T, rhs: T) -> T`
 /// Description: Divides `lhs` and `rhs` and returns the result.
 /// Constraints: `T` is an integer type, i.e. `u8`, `u16`, `u32`, `u64`.
+///
+/// Signature: `__and<T>(lhs: T, rhs: T) -> T`
+/// Description: Bitwise AND `lhs` and `rhs` and returns the result.
+/// Constraints: `T` is an integer type, i.e. `u8`, `u16`, `u32`, `u64`.
+///
+/// Signature: `__or<T>(lhs: T, rhs: T) -> T`
+/// Description: Bitwise OR `lhs` and `rhs` and returns the result.
+/// Constraints: `T` is an integer type, i.e. `u8`, `u16`, `u32`, `u64`.
+///
+/// Signature: `__xor<T>(lhs: T, rhs: T) -> T`
+/// Description: Bitwise XOR `lhs` and `rhs` and returns the result.
+/// Constraints: `T` is an integer type, i.e. `u8`, `u16`, `u32`, `u64`.
+///
+/// Signature: `__mod<T>(lhs: T, rhs: T) -> T`
+/// Description: Returns the remainder of dividing `lhs` by `rhs`.
+/// Constraints: `T` is an integer type, i.e. `u8`, `u16`, `u32`, `u64`.
+///
+/// Signature: `__lsh<T>(lhs: T, rhs: u64) -> T`
+/// Description: Logical left shift of `lhs` by `rhs` bits and returns the result.
+/// Constraints: `T` is an integer type, i.e. `u8`, `u16`, `u32`, `u64`.
+///
+/// Signature: `__rsh<T>(lhs: T, rhs: u64) -> T`
+/// Description: Logical right shift of `lhs` by `rhs` bits and returns the result.
+/// Constraints: `T` is an integer type, i.e. `u8`, `u16`, `u32`, `u64`.
 fn type_check_binary_op(
     mut ctx: TypeCheckContext,
     kind: sway_ast::Intrinsic,
@@ -1050,11 +1092,20 @@ fn type_check_binary_op(
         return err(warnings, errors);
     }
 
+    // The shift amount of a shift is always a `u64`, while the operands of all other binary ops
+    // are of the same type.
+    let rhs_type = match kind {
+        Intrinsic::Lsh | Intrinsic::Rsh => type_engine.insert(
+            decl_engine,
+            TypeInfo::UnsignedInteger(IntegerBits::SixtyFour),
+        ),
+        _ => lhs.return_type,
+    };
     let rhs = arguments[1].clone();
     let ctx = ctx
         .by_ref()
         .with_help_text("Incorrect argument type")
-        .with_type_annotation(lhs.return_type);
+        .with_type_annotation(rhs_type);
     let rhs = check!(
         ty::TyExpression::type_check(ctx, rhs),
         return err(warnings, errors),
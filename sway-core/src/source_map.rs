@@ -79,6 +79,11 @@ impl SourceMap {
             (p.to_owned(), sms.range)
         })
     }
+
+    /// Iterate over the opcode indices that map to a source location.
+    pub fn addrs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.map.keys().copied()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
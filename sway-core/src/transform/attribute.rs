@@ -19,18 +19,38 @@
 //! is
 //!
 //!   #[foo(bar, bar)]
+//!
+//! Each arg may optionally be assigned a literal value, e.g. `#[foo(bar = 42)]`.
+
+use sway_types::{Ident, Span, Spanned};
 
-use sway_types::{Ident, Span};
+use crate::language::Literal;
 
 use std::{collections::HashMap, hash::Hash, sync::Arc};
 
+/// An attribute argument has a name (i.e "read", "should_revert"),
+/// an optional literal value and
+/// a span from its declaration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttributeArg {
+    pub name: Ident,
+    pub value: Option<Literal>,
+    pub span: Span,
+}
+
+impl Spanned for AttributeArg {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
 /// An attribute has a name (i.e "doc", "storage"),
 /// a vector of possible arguments and
 /// a span from its declaration.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Attribute {
     pub name: Ident,
-    pub args: Vec<Ident>,
+    pub args: Vec<AttributeArg>,
     pub span: Span,
 }
 
@@ -42,6 +62,7 @@ pub enum AttributeKind {
     Storage,
     Inline,
     Test,
+    Bench,
     Payable,
 }
 
@@ -23,10 +23,10 @@ use sway_error::handler::{ErrorEmitted, Handler};
 use sway_error::warning::{CompileWarning, Warning};
 use sway_types::{
     constants::{
-        DESTRUCTURE_PREFIX, DOC_ATTRIBUTE_NAME, DOC_COMMENT_ATTRIBUTE_NAME, INLINE_ATTRIBUTE_NAME,
-        MATCH_RETURN_VAR_NAME_PREFIX, PAYABLE_ATTRIBUTE_NAME, STORAGE_PURITY_ATTRIBUTE_NAME,
-        STORAGE_PURITY_READ_NAME, STORAGE_PURITY_WRITE_NAME, TEST_ATTRIBUTE_NAME,
-        TUPLE_NAME_PREFIX, VALID_ATTRIBUTE_NAMES,
+        BENCH_ATTRIBUTE_NAME, DESTRUCTURE_PREFIX, DOC_ATTRIBUTE_NAME, DOC_COMMENT_ATTRIBUTE_NAME,
+        INLINE_ATTRIBUTE_NAME, MATCH_RETURN_VAR_NAME_PREFIX, PAYABLE_ATTRIBUTE_NAME,
+        STORAGE_PURITY_ATTRIBUTE_NAME, STORAGE_PURITY_READ_NAME, STORAGE_PURITY_WRITE_NAME,
+        TEST_ATTRIBUTE_NAME, TUPLE_NAME_PREFIX, VALID_ATTRIBUTE_NAMES,
     },
     integer_bits::IntegerBits,
 };
@@ -85,7 +85,7 @@ pub fn module_to_sway_parse_tree(
 
 fn ast_node_is_test_fn(node: &AstNode) -> bool {
     if let AstNodeContent::Declaration(Declaration::FunctionDeclaration(ref decl)) = node.content {
-        if decl.attributes.contains_key(&AttributeKind::Test) {
+        if decl.is_test() {
             return true;
         }
     }
@@ -472,7 +472,7 @@ fn get_attributed_purity(
     match attributes.get(&AttributeKind::Storage) {
         Some(attrs) if !attrs.is_empty() => {
             for arg in attrs.iter().flat_map(|attr| &attr.args) {
-                match arg.as_str() {
+                match arg.name.as_str() {
                     STORAGE_PURITY_READ_NAME => add_impurity(Purity::Reads, Purity::Writes),
                     STORAGE_PURITY_WRITE_NAME => add_impurity(Purity::Writes, Purity::Reads),
                     _otherwise => {
@@ -3574,7 +3574,7 @@ where
 }
 
 fn item_attrs_to_map(
-    _context: &mut Context,
+    context: &mut Context,
     handler: &Handler,
     attribute_list: &[AttributeDecl],
 ) -> Result<AttributesMap, ErrorEmitted> {
@@ -3595,7 +3595,25 @@ fn item_attrs_to_map(
             let args = attr
                 .args
                 .as_ref()
-                .map(|parens| parens.get().into_iter().cloned().collect())
+                .map(|parens| {
+                    parens
+                        .get()
+                        .into_iter()
+                        .map(|arg| {
+                            let value = arg
+                                .value
+                                .clone()
+                                .map(|value| literal_to_literal(context, handler, value))
+                                .transpose()?;
+                            Ok(AttributeArg {
+                                name: arg.name.clone(),
+                                value,
+                                span: arg.span(),
+                            })
+                        })
+                        .collect::<Result<_, _>>()
+                })
+                .transpose()?
                 .unwrap_or_else(Vec::new);
 
             let attribute = Attribute {
@@ -3610,6 +3628,7 @@ fn item_attrs_to_map(
                 STORAGE_PURITY_ATTRIBUTE_NAME => Some(AttributeKind::Storage),
                 INLINE_ATTRIBUTE_NAME => Some(AttributeKind::Inline),
                 TEST_ATTRIBUTE_NAME => Some(AttributeKind::Test),
+                BENCH_ATTRIBUTE_NAME => Some(AttributeKind::Bench),
                 PAYABLE_ATTRIBUTE_NAME => Some(AttributeKind::Payable),
                 _ => None,
             } {
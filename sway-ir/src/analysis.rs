@@ -2,3 +2,5 @@ pub mod call_graph;
 pub use call_graph::*;
 pub mod dominator;
 pub use dominator::*;
+pub mod loops;
+pub use loops::*;
@@ -155,6 +155,19 @@ pub fn compute_dom_tree(context: &Context, function: &Function) -> (DomTree, Pos
     (dom_tree, po)
 }
 
+/// Whether `dominator` dominates `block`.  Both blocks must be reachable.
+pub fn dominates(dom_tree: &DomTree, dominator: Block, mut block: Block) -> bool {
+    loop {
+        if block == dominator {
+            return true;
+        }
+        match dom_tree[&block].parent {
+            Some(idom) => block = idom,
+            None => return false,
+        }
+    }
+}
+
 /// Compute dominance frontiers set for each block.
 pub fn compute_dom_fronts(context: &Context, dom_tree: &DomTree) -> DomFronts {
     let mut res = DomFronts::default();
@@ -0,0 +1,114 @@
+//! Natural loop analysis.
+//!
+//! A natural loop is identified by a _back edge_, an edge from a block to one of its dominators,
+//! the loop _header_.  The loop is made up of the header and every block which can reach the
+//! source of a back edge, a _latch_, without passing through the header.  All the back edges to
+//! the same header form a single loop.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{block::Block, BranchToWithArgs, Context, DomTree, Instruction, PostOrder};
+
+use super::dominator::dominates;
+
+/// A natural loop in the CFG.
+pub struct Loop {
+    /// The single entry to the loop, which dominates all of its blocks.
+    pub header: Block,
+    /// The blocks in the loop which branch back to the header.
+    pub latches: Vec<Block>,
+    /// All the blocks in the loop, including the header and the latches.
+    pub blocks: FxHashSet<Block>,
+}
+
+impl Loop {
+    pub fn contains(&self, block: &Block) -> bool {
+        self.blocks.contains(block)
+    }
+
+    /// The blocks in the loop which may branch to a block outside of it.
+    pub fn exiting_blocks(&self, context: &Context) -> Vec<Block> {
+        self.blocks
+            .iter()
+            .filter(|block| {
+                block
+                    .successors(context)
+                    .iter()
+                    .any(|BranchToWithArgs { block: succ, .. }| !self.contains(succ))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The loop preheader, which is the only predecessor of the header outside of the loop,
+    /// and which unconditionally branches to the header.
+    pub fn get_preheader(&self, context: &Context) -> Option<Block> {
+        let mut outside_preds = self
+            .header
+            .pred_iter(context)
+            .filter(|pred| !self.contains(pred));
+        match (outside_preds.next(), outside_preds.next()) {
+            (Some(pred), None) => match pred.get_terminator(context) {
+                Some(Instruction::Branch(BranchToWithArgs { block, .. }))
+                    if *block == self.header =>
+                {
+                    Some(*pred)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Compute the natural loops of a function, from its dominator tree and post order.  Only
+/// reachable blocks are considered.  Inner loops are returned before the loops containing them.
+pub fn compute_loops(context: &Context, dom_tree: &DomTree, po: &PostOrder) -> Vec<Loop> {
+    // Find the back edges, grouped by their header, in reverse post order of the headers.
+    let mut latches: FxHashMap<Block, Vec<Block>> = FxHashMap::default();
+    let mut headers = Vec::new();
+    for block in po.po_to_block.iter().rev() {
+        for BranchToWithArgs { block: succ, .. } in block.successors(context) {
+            // A dominator of the block must come after it in post order.
+            if po.block_to_po[&succ] >= po.block_to_po[block] && dominates(dom_tree, succ, *block) {
+                let header_latches = latches.entry(succ).or_default();
+                if header_latches.is_empty() {
+                    headers.push(succ);
+                }
+                if !header_latches.contains(block) {
+                    header_latches.push(*block);
+                }
+            }
+        }
+    }
+
+    let mut loops = headers
+        .into_iter()
+        .map(|header| {
+            let latches = latches.remove(&header).unwrap();
+
+            // Walk backwards from the latches until the header is reached.
+            let mut blocks = FxHashSet::from_iter([header]);
+            let mut worklist = latches.clone();
+            while let Some(block) = worklist.pop() {
+                if blocks.insert(block) {
+                    worklist.extend(
+                        block
+                            .pred_iter(context)
+                            .filter(|pred| po.block_to_po.contains_key(pred)),
+                    );
+                }
+            }
+
+            Loop {
+                header,
+                latches,
+                blocks,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // A loop nested within another has fewer blocks.
+    loops.sort_by_key(|lp| lp.blocks.len());
+    loops
+}
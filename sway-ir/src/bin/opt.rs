@@ -5,8 +5,10 @@ use std::{
 
 use anyhow::anyhow;
 use sway_ir::{
-    create_const_combine_pass, create_dce_pass, create_inline_pass, create_mem2reg_pass,
-    create_simplify_cfg_pass, PassManager, PassManagerConfig,
+    create_const_combine_pass, create_cse_pass, create_dce_pass, create_fn_dedup_pass,
+    create_inline_pass, create_licm_pass, create_loop_unroll_pass, create_mem2reg_pass,
+    create_ret_demotion_pass, create_simplify_cfg_pass, create_sroa_pass,
+    create_storage_forward_pass, PassManager, PassManagerConfig,
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -16,10 +18,17 @@ fn main() -> Result<(), anyhow::Error> {
     let mut pass_mgr = PassManager::default();
 
     pass_mgr.register(create_const_combine_pass());
+    pass_mgr.register(create_cse_pass());
     pass_mgr.register(create_inline_pass());
+    pass_mgr.register(create_licm_pass());
+    pass_mgr.register(create_loop_unroll_pass());
     pass_mgr.register(create_simplify_cfg_pass());
     pass_mgr.register(create_dce_pass());
+    pass_mgr.register(create_fn_dedup_pass());
     pass_mgr.register(create_mem2reg_pass());
+    pass_mgr.register(create_sroa_pass());
+    pass_mgr.register(create_ret_demotion_pass());
+    pass_mgr.register(create_storage_forward_pass());
 
     // Build the config from the command line.
     let config = ConfigBuilder::build(&pass_mgr, std::env::args())?;
@@ -277,6 +277,29 @@ impl Function {
         context.functions[self.0].return_type
     }
 
+    /// Get the 'out' parameter the return value is written to, if the function returns by
+    /// reference.
+    ///
+    /// Non-entry functions returning a ref type have a final by-ref argument of the return type,
+    /// which the caller points at the memory for the returned value.
+    pub fn get_ret_value_arg(&self, context: &Context) -> Option<Value> {
+        if self.is_entry(context) {
+            return None;
+        }
+        let ret_ty = self.get_return_type(context);
+        if ret_ty.is_unit(context) || ret_ty.is_bool(context) || ret_ty.is_uint(context) {
+            return None;
+        }
+        self.args_iter(context)
+            .last()
+            .map(|(_name, arg_val)| *arg_val)
+            .filter(|arg_val| {
+                arg_val
+                    .get_argument_type_and_byref(context)
+                    .map_or(false, |(arg_ty, by_ref)| by_ref && arg_ty == ret_ty)
+            })
+    }
+
     /// Get the number of args.
     pub fn num_args(&self, context: &Context) -> usize {
         context.functions[self.0].arguments.len()
@@ -177,19 +177,30 @@ pub enum FuelVmInstruction {
     },
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Predicate {
     /// Equivalence.
     Equal,
-    // More soon.  NotEqual, LessThan, LessThanOrEqual, GreaterThan, GreaterThanOrEqual.
+    /// Unsigned less than.
+    LessThan,
+    /// Unsigned greater than.
+    GreaterThan,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BinaryOpKind {
     Add,
     Sub,
     Mul,
     Div,
+    And,
+    Or,
+    Xor,
+    Mod,
+    /// Shift left. The shift amount may be an integer of any width.
+    Lsh,
+    /// Shift right. The shift amount may be an integer of any width.
+    Rsh,
 }
 
 /// Special registers in the Fuel Virtual Machine.
@@ -11,6 +11,14 @@
 
 use crate::{context::Context, pretty::DebugWithContext};
 
+// This is a mouthful...
+#[macro_export]
+macro_rules! size_bytes_round_up_to_word_alignment {
+    ($bytes_expr: expr) => {
+        ($bytes_expr + 7) - (($bytes_expr + 7) % 8)
+    };
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, DebugWithContext)]
 pub struct Type(pub generational_arena::Index);
 
@@ -311,6 +319,33 @@ impl Type {
             _ => vec![],
         }
     }
+
+    /// Get the size of a value of this type in memory, in bytes.  Everything is word aligned, so
+    /// strings are padded and the size is always a multiple of 8.
+    pub fn size_in_bytes(&self, context: &Context) -> u64 {
+        match self.get_content(context) {
+            TypeContent::Unit | TypeContent::Bool | TypeContent::Uint(_) => 8,
+            TypeContent::Slice => 16,
+            TypeContent::B256 => 32,
+            TypeContent::String(n) => crate::size_bytes_round_up_to_word_alignment!(n),
+            TypeContent::Array(el_ty, cnt) => cnt * el_ty.size_in_bytes(context),
+            TypeContent::Struct(field_tys) => {
+                // Sum up all the field sizes.
+                field_tys
+                    .iter()
+                    .map(|field_ty| field_ty.size_in_bytes(context))
+                    .sum()
+            }
+            TypeContent::Union(field_tys) => {
+                // Find the max size for field sizes.
+                field_tys
+                    .iter()
+                    .map(|field_ty| field_ty.size_in_bytes(context))
+                    .max()
+                    .unwrap_or(0)
+            }
+        }
+    }
 }
 
 /// A helper to check if an Option<Type> value is of a particular Type.
@@ -15,11 +15,25 @@
 
 pub mod constants;
 pub use constants::*;
+pub mod cse;
+pub use cse::*;
 pub mod inline;
 pub use inline::*;
+pub mod licm;
+pub use licm::*;
+pub mod loop_unroll;
+pub use loop_unroll::*;
 pub mod simplify_cfg;
 pub use simplify_cfg::*;
 pub mod dce;
 pub use dce::*;
+pub mod fn_dedup;
+pub use fn_dedup::*;
 pub mod mem2reg;
 pub use mem2reg::*;
+pub mod ret_demotion;
+pub use ret_demotion::*;
+pub mod sroa;
+pub use sroa::*;
+pub mod storage_forward;
+pub use storage_forward::*;
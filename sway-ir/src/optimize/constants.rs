@@ -3,6 +3,7 @@
 //! - combining - compile time evaluation of constant expressions.
 //!   - combine insert_values - reduce expressions which insert a constant value into a constant
 //!     struct.
+//!   - combine cmps and binary ops - evaluate comparisons and arithmetic of constant integers.
 
 use crate::{
     constant::{Constant, ConstantValue},
@@ -11,7 +12,7 @@ use crate::{
     function::Function,
     instruction::Instruction,
     value::{Value, ValueContent, ValueDatum},
-    AnalysisResults, BranchToWithArgs, Pass, PassMutability, Predicate, ScopedPass,
+    AnalysisResults, BinaryOpKind, BranchToWithArgs, Pass, PassMutability, Predicate, ScopedPass,
 };
 
 pub fn create_const_combine_pass() -> Pass {
@@ -40,6 +41,11 @@ pub fn combine_constants(
             continue;
         }
 
+        if combine_binary_op(context, &function) {
+            modified = true;
+            continue;
+        }
+
         if combine_cbr(context, &function)? {
             modified = true;
             continue;
@@ -119,6 +125,18 @@ fn combine_cmp(context: &mut Context, function: &Function) -> bool {
                                 Some((inst_val, block, false))
                             }
                         }
+                        Predicate::LessThan | Predicate::GreaterThan => {
+                            match (&val1.value, &val2.value) {
+                                (ConstantValue::Uint(l), ConstantValue::Uint(r)) => {
+                                    let cn_replace = match pred {
+                                        Predicate::LessThan => l < r,
+                                        _ => l > r,
+                                    };
+                                    Some((inst_val, block, cn_replace))
+                                }
+                                _ => None,
+                            }
+                        }
                     }
                 }
                 _ => None,
@@ -136,6 +154,54 @@ fn combine_cmp(context: &mut Context, function: &Function) -> bool {
     })
 }
 
+fn combine_binary_op(context: &mut Context, function: &Function) -> bool {
+    let candidate = function
+        .instruction_iter(context)
+        .find_map(
+            |(block, inst_val)| match &context.values[inst_val.0].value {
+                ValueDatum::Instruction(Instruction::BinaryOp { op, arg1, arg2 })
+                    if arg1.is_constant(context) && arg2.is_constant(context) =>
+                {
+                    let val1 = arg1.get_constant(context).unwrap();
+                    let val2 = arg2.get_constant(context).unwrap();
+                    let (l, r) = match (&val1.value, &val2.value) {
+                        (ConstantValue::Uint(l), ConstantValue::Uint(r)) => (*l, *r),
+                        _ => return None,
+                    };
+                    // Operations which would panic at runtime, i.e. overflows and divisions by
+                    // zero, are left alone.
+                    let result = match op {
+                        BinaryOpKind::Add => l.checked_add(r),
+                        BinaryOpKind::Sub => l.checked_sub(r),
+                        BinaryOpKind::Mul => l.checked_mul(r),
+                        BinaryOpKind::Div => l.checked_div(r),
+                        BinaryOpKind::Mod => l.checked_rem(r),
+                        BinaryOpKind::And => Some(l & r),
+                        BinaryOpKind::Or => Some(l | r),
+                        BinaryOpKind::Xor => Some(l ^ r),
+                        BinaryOpKind::Lsh => u32::try_from(r).ok().and_then(|r| l.checked_shl(r)),
+                        BinaryOpKind::Rsh => u32::try_from(r).ok().and_then(|r| l.checked_shr(r)),
+                    };
+                    result.map(|result| (inst_val, block, val1.ty, result))
+                }
+                _ => None,
+            },
+        );
+
+    candidate.map_or(false, |(inst_val, block, ty, result)| {
+        // Replace this binary op instruction with a constant.
+        inst_val.replace(
+            context,
+            ValueDatum::Constant(Constant {
+                ty,
+                value: ConstantValue::Uint(result),
+            }),
+        );
+        block.remove_instruction(context, inst_val);
+        true
+    })
+}
+
 fn combine_const_insert_values(context: &mut Context, function: &Function) -> bool {
     // Find a candidate `insert_value` instruction.
     let candidate = function
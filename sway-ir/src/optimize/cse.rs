@@ -0,0 +1,231 @@
+//! ## Common Subexpression Elimination
+//!
+//! This optimization replaces instructions which recompute a value already computed by a
+//! dominating instruction with that value.  It is a simple dominator based global value
+//! numbering: the dominator tree is walked in pre-order, and a scoped table maps each instruction,
+//! with its operands already numbered, to the first value computing it.
+//!
+//! Instructions which read memory or storage are numbered along with the _memory state_ they read
+//! from.  A new memory state begins after any instruction which may have a side effect, and at any
+//! block which isn't reached solely from its immediate dominator.
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    dominator::compute_dom_tree, AnalysisResults, BinaryOpKind, Block, ConstantValue, Context,
+    DomTree, FuelVmInstruction, Function, Instruction, IrError, Pass, PassMutability, Predicate,
+    ScopedPass, Type, Value,
+};
+
+pub fn create_cse_pass() -> Pass {
+    Pass {
+        name: "cse",
+        descr: "Common subexpression elimination.",
+        runner: ScopedPass::FunctionPass(PassMutability::Transform(cse)),
+    }
+}
+
+/// An instruction operand.  Constants are numbered by their value, as equal constants are usually
+/// distinct values.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Operand {
+    Value(Value),
+    Unit,
+    Bool(bool),
+    Uint(Type, u64),
+}
+
+/// The memory state read by an instruction.
+type MemoryState = u64;
+
+/// The computation of an instruction which can be numbered.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Expr {
+    AddrOf(Operand),
+    BinaryOp(BinaryOpKind, Operand, Operand),
+    BitCast(Operand, Type),
+    Cmp(Predicate, Operand, Operand),
+    ExtractElement(Operand, Type, Operand, MemoryState),
+    ExtractValue(Operand, Type, Vec<u64>, MemoryState),
+    Gtf(Operand, u64),
+    IntToPtr(Operand, Type),
+    Load(Operand, MemoryState),
+    StateLoadWord(Operand, MemoryState),
+}
+
+impl Expr {
+    /// Return the same computation with its operands swapped, if it is commutative.
+    fn commuted(&self) -> Option<Expr> {
+        match self {
+            Expr::BinaryOp(
+                op @ (BinaryOpKind::Add
+                | BinaryOpKind::Mul
+                | BinaryOpKind::And
+                | BinaryOpKind::Or
+                | BinaryOpKind::Xor),
+                lhs,
+                rhs,
+            ) => Some(Expr::BinaryOp(*op, *rhs, *lhs)),
+            Expr::Cmp(Predicate::Equal, lhs, rhs) => Some(Expr::Cmp(Predicate::Equal, *rhs, *lhs)),
+            _ => None,
+        }
+    }
+}
+
+/// Replace the instructions which recompute a value computed by a dominating instruction, and
+/// return true if the function was modified.
+pub fn cse(
+    context: &mut Context,
+    _: &AnalysisResults,
+    function: Function,
+) -> Result<bool, IrError> {
+    let (dom_tree, _) = compute_dom_tree(context, &function);
+
+    let mut numbering = Numbering {
+        context,
+        dom_tree: &dom_tree,
+        exprs: FxHashMap::default(),
+        replacements: FxHashMap::default(),
+        next_memory_state: 0,
+    };
+    numbering.number_block(function.get_entry_block(numbering.context), None);
+    let Numbering { replacements, .. } = numbering;
+
+    if replacements.is_empty() {
+        return Ok(false);
+    }
+
+    function.replace_values(context, &replacements, None);
+    for block in function.block_iter(context) {
+        context.blocks[block.0]
+            .instructions
+            .retain(|inst| !replacements.contains_key(inst));
+    }
+
+    Ok(true)
+}
+
+struct Numbering<'a> {
+    context: &'a Context,
+    dom_tree: &'a DomTree,
+    // The values computing each expression in the dominating blocks of the current block.
+    exprs: FxHashMap<Expr, Value>,
+    // The instructions to remove, mapped to the values replacing them.
+    replacements: FxHashMap<Value, Value>,
+    next_memory_state: MemoryState,
+}
+
+impl Numbering<'_> {
+    fn new_memory_state(&mut self) -> MemoryState {
+        self.next_memory_state += 1;
+        self.next_memory_state
+    }
+
+    /// Number the instructions of the block and then those of the blocks it dominates.
+    /// `memory_state` is the memory state at the end of the immediate dominator of the block.
+    fn number_block(&mut self, block: Block, memory_state: Option<MemoryState>) {
+        // The memory state carries over from the immediate dominator only if it is the sole
+        // predecessor of this block.
+        let parent = self.dom_tree[&block].parent;
+        let preds = block.pred_iter(self.context).collect::<Vec<_>>();
+        let mut memory_state = match (memory_state, preds.as_slice()) {
+            (Some(memory_state), [pred]) if Some(**pred) == parent => memory_state,
+            _ => self.new_memory_state(),
+        };
+
+        let mut new_exprs = Vec::new();
+        for inst in block.instruction_iter(self.context) {
+            let instruction = inst.get_instruction(self.context).unwrap();
+            if instruction.may_have_side_effect() {
+                memory_state = self.new_memory_state();
+                continue;
+            }
+            let expr = match self.get_expr(instruction, memory_state) {
+                Some(expr) => expr,
+                None => continue,
+            };
+            let existing = self.exprs.get(&expr).copied().or_else(|| {
+                expr.commuted()
+                    .and_then(|commuted| self.exprs.get(&commuted).copied())
+            });
+            match existing {
+                Some(value) => {
+                    self.replacements.insert(inst, value);
+                }
+                None => {
+                    self.exprs.insert(expr.clone(), inst);
+                    new_exprs.push(expr);
+                }
+            }
+        }
+
+        for child in self.dom_tree[&block].children.iter() {
+            self.number_block(*child, Some(memory_state));
+        }
+
+        // The expressions computed in this block are not available outside of the blocks it
+        // dominates.
+        for expr in new_exprs {
+            self.exprs.remove(&expr);
+        }
+    }
+
+    fn get_operand(&self, value: &Value) -> Operand {
+        let value = self.replacements.get(value).unwrap_or(value);
+        match value.get_constant(self.context) {
+            Some(constant) => match constant.value {
+                ConstantValue::Unit => Operand::Unit,
+                ConstantValue::Bool(b) => Operand::Bool(b),
+                ConstantValue::Uint(n) => Operand::Uint(constant.ty, n),
+                _ => Operand::Value(*value),
+            },
+            None => Operand::Value(*value),
+        }
+    }
+
+    fn get_expr(&self, instruction: &Instruction, memory_state: MemoryState) -> Option<Expr> {
+        let expr = match instruction {
+            Instruction::AddrOf(value) => Expr::AddrOf(self.get_operand(value)),
+            Instruction::BinaryOp { op, arg1, arg2 } => {
+                Expr::BinaryOp(*op, self.get_operand(arg1), self.get_operand(arg2))
+            }
+            Instruction::BitCast(value, ty) => Expr::BitCast(self.get_operand(value), *ty),
+            Instruction::Cmp(pred, lhs, rhs) => {
+                Expr::Cmp(*pred, self.get_operand(lhs), self.get_operand(rhs))
+            }
+            Instruction::ExtractElement {
+                array,
+                ty,
+                index_val,
+            } => Expr::ExtractElement(
+                self.get_operand(array),
+                *ty,
+                self.get_operand(index_val),
+                memory_state,
+            ),
+            Instruction::ExtractValue {
+                aggregate,
+                ty,
+                indices,
+            } => Expr::ExtractValue(
+                self.get_operand(aggregate),
+                *ty,
+                indices.clone(),
+                memory_state,
+            ),
+            Instruction::FuelVm(FuelVmInstruction::Gtf { index, tx_field_id }) => {
+                Expr::Gtf(self.get_operand(index), *tx_field_id)
+            }
+            Instruction::FuelVm(FuelVmInstruction::StateLoadWord(key)) => {
+                Expr::StateLoadWord(self.get_operand(key), memory_state)
+            }
+            Instruction::IntToPtr(value, ty) => Expr::IntToPtr(self.get_operand(value), *ty),
+            Instruction::Load(ptr) => Expr::Load(self.get_operand(ptr), memory_state),
+            // `get_local` isn't numbered, as for copy types it also loads the local's value
+            // when compiled to asm, and neither are the other instructions which are either
+            // terminators, impure or refer to an unknown register state.
+            _ => return None,
+        };
+        Some(expr)
+    }
+}
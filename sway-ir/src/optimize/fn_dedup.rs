@@ -0,0 +1,431 @@
+//! ## Function Deduplication
+//!
+//! Monomorphization and inlining often leave a module with functions whose bodies are identical,
+//! such as a generic function instantiated for two types with the same layout.  This pass finds
+//! functions which are structurally identical, ignoring their names and metadata, redirects all
+//! calls to each of them to a single copy and removes the others.
+//!
+//! Functions are compared block by block and instruction by instruction, with the arguments,
+//! blocks, locals and instruction values of one mapped to those of the other by position.  Calls
+//! to functions which are themselves being compared are considered identical, so recursive
+//! functions may be merged too.  As merging redirects calls, which may in turn make their callers
+//! identical, the module is searched again until no more functions are merged.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    AnalysisResults, AsmArg, AsmBlock, Block, Constant, ConstantValue, Context, FuelVmInstruction,
+    Function, Instruction, IrError, LocalVar, Module, Pass, PassMutability, ScopedPass, Value,
+    ValueDatum,
+};
+
+pub fn create_fn_dedup_pass() -> Pass {
+    Pass {
+        name: "fn_dedup",
+        descr: "Merge identical functions.",
+        runner: ScopedPass::ModulePass(PassMutability::Transform(fn_dedup)),
+    }
+}
+
+/// A function removed from a module as it was identical to another.
+#[derive(Debug, Clone)]
+pub struct MergedFunction {
+    /// The name of the removed function.
+    pub name: String,
+    /// The name of the function its calls were redirected to.
+    pub merged_into: String,
+    /// The number of instructions in the removed function.
+    pub num_instructions: usize,
+}
+
+/// The functions removed from a module by [`merge_identical_functions`].
+#[derive(Debug, Clone, Default)]
+pub struct FnDedupReport {
+    pub merged: Vec<MergedFunction>,
+}
+
+impl FnDedupReport {
+    /// Each IR instruction compiles to at least one 4 byte VM instruction, so this is a lower
+    /// bound on the bytecode saved by removing the merged functions.
+    pub fn estimated_bytes_saved(&self) -> u64 {
+        self.merged
+            .iter()
+            .map(|merged| merged.num_instructions as u64 * 4)
+            .sum()
+    }
+}
+
+/// Merge the identical functions in the module, returning true if any were.
+pub fn fn_dedup(
+    context: &mut Context,
+    _: &AnalysisResults,
+    module: Module,
+) -> Result<bool, IrError> {
+    Ok(!merge_identical_functions(context, module).merged.is_empty())
+}
+
+/// Merge the identical functions in the module and report those which were removed.
+///
+/// Entry functions are never merged, as they're referred to by their name or selector.
+pub fn merge_identical_functions(context: &mut Context, module: Module) -> FnDedupReport {
+    let mut report = FnDedupReport::default();
+    loop {
+        let merges = find_identical_functions(context, module);
+        if merges.is_empty() {
+            return report;
+        }
+
+        let redirects = merges.iter().copied().collect::<FxHashMap<_, _>>();
+        for function in module.function_iter(context) {
+            for block in function.block_iter(context) {
+                for inst in block.instruction_iter(context).collect::<Vec<_>>() {
+                    if let Some(Instruction::Call(callee, _)) = inst.get_instruction_mut(context) {
+                        if let Some(kept) = redirects.get(callee) {
+                            *callee = *kept;
+                        }
+                    }
+                }
+            }
+        }
+
+        for (merged, kept) in merges {
+            report.merged.push(MergedFunction {
+                name: merged.get_name(context).to_string(),
+                merged_into: kept.get_name(context).to_string(),
+                num_instructions: merged.num_instructions(context),
+            });
+            module.remove_function(context, &merged);
+        }
+    }
+}
+
+/// Map each function which is identical to an earlier function in the module to that function.
+fn find_identical_functions(context: &Context, module: Module) -> Vec<(Function, Function)> {
+    // Only functions with the same signature and size need to be compared.
+    let mut candidates: FxHashMap<_, Vec<Function>> = FxHashMap::default();
+    for function in module.function_iter(context) {
+        if function.is_entry(context) {
+            continue;
+        }
+        let signature = (
+            function
+                .args_iter(context)
+                .map(|(_, arg)| arg.get_argument_type_and_byref(context))
+                .collect::<Vec<_>>(),
+            function.get_return_type(context),
+            function.num_blocks(context),
+            function.num_instructions(context),
+        );
+        candidates.entry(signature).or_default().push(function);
+    }
+
+    let mut merges = Vec::new();
+    for functions in candidates.into_values() {
+        let mut kept: Vec<Function> = Vec::new();
+        for function in functions {
+            match kept
+                .iter()
+                .find(|other| are_identical(context, **other, function))
+            {
+                Some(other) => merges.push((function, *other)),
+                None => kept.push(function),
+            }
+        }
+    }
+
+    // Sort the merges by the module order so that the result is deterministic.
+    let order = module
+        .function_iter(context)
+        .enumerate()
+        .map(|(idx, function)| (function, idx))
+        .collect::<FxHashMap<_, _>>();
+    merges.sort_by_key(|(function, _)| order[function]);
+    merges
+}
+
+fn are_identical(context: &Context, a: Function, b: Function) -> bool {
+    let a_blocks = a.block_iter(context).collect::<Vec<_>>();
+    let b_blocks = b.block_iter(context).collect::<Vec<_>>();
+    if a_blocks.len() != b_blocks.len() {
+        return false;
+    }
+
+    // Map everything defined in `b` to its counterpart in `a` up front, as a value may be used in
+    // an earlier block than the one defining it.
+    let mut comparison = Comparison {
+        context,
+        functions: (a, b),
+        blocks: FxHashMap::default(),
+        values: FxHashMap::default(),
+        locals: FxHashMap::default(),
+        mapped_locals: FxHashSet::default(),
+    };
+    let mut instructions = Vec::new();
+    for (a_block, b_block) in a_blocks.iter().zip(b_blocks.iter()) {
+        let a_args = a_block.arg_iter(context).collect::<Vec<_>>();
+        let b_args = b_block.arg_iter(context).collect::<Vec<_>>();
+        let a_insts = a_block.instruction_iter(context).collect::<Vec<_>>();
+        let b_insts = b_block.instruction_iter(context).collect::<Vec<_>>();
+        if a_args.len() != b_args.len() || a_insts.len() != b_insts.len() {
+            return false;
+        }
+        for (a_arg, b_arg) in a_args.into_iter().zip(b_args) {
+            if a_arg.get_argument_type_and_byref(context)
+                != b_arg.get_argument_type_and_byref(context)
+            {
+                return false;
+            }
+            comparison.values.insert(*b_arg, *a_arg);
+        }
+        comparison.blocks.insert(*b_block, *a_block);
+        for (a_inst, b_inst) in a_insts.into_iter().zip(b_insts) {
+            comparison.values.insert(b_inst, a_inst);
+            instructions.push((a_inst, b_inst));
+        }
+    }
+
+    instructions.into_iter().all(|(a_inst, b_inst)| {
+        match (
+            a_inst.get_instruction(context),
+            b_inst.get_instruction(context),
+        ) {
+            (Some(a_inst), Some(b_inst)) => comparison.same_instruction(a_inst, b_inst),
+            _ => false,
+        }
+    })
+}
+
+/// The correspondence between the blocks, values and locals of two functions being compared,
+/// mapping those of the second to those of the first.
+struct Comparison<'a> {
+    context: &'a Context,
+    functions: (Function, Function),
+    blocks: FxHashMap<Block, Block>,
+    values: FxHashMap<Value, Value>,
+    locals: FxHashMap<LocalVar, LocalVar>,
+    mapped_locals: FxHashSet<LocalVar>,
+}
+
+impl Comparison<'_> {
+    fn same_instruction(&mut self, a: &Instruction, b: &Instruction) -> bool {
+        let a_operands = a.get_operands();
+        let b_operands = b.get_operands();
+        a_operands.len() == b_operands.len()
+            && a_operands
+                .iter()
+                .zip(b_operands.iter())
+                .all(|(a, b)| self.same_value(*a, *b))
+            && self.same_opcode(a, b)
+    }
+
+    /// Whether the instructions are the same other than their operands.
+    fn same_opcode(&mut self, a: &Instruction, b: &Instruction) -> bool {
+        use Instruction::*;
+
+        match (a, b) {
+            (AddrOf(_), AddrOf(_))
+            | (Load(_), Load(_))
+            | (Nop, Nop)
+            | (Store { .. }, Store { .. }) => true,
+            (AsmBlock(a_block, a_args), AsmBlock(b_block, b_args)) => {
+                self.same_asm_block(*a_block, a_args, *b_block, b_args)
+            }
+            (BinaryOp { op: a_op, .. }, BinaryOp { op: b_op, .. }) => a_op == b_op,
+            (BitCast(_, a_ty), BitCast(_, b_ty))
+            | (IntToPtr(_, a_ty), IntToPtr(_, b_ty))
+            | (Ret(_, a_ty), Ret(_, b_ty))
+            | (ExtractElement { ty: a_ty, .. }, ExtractElement { ty: b_ty, .. })
+            | (InsertElement { ty: a_ty, .. }, InsertElement { ty: b_ty, .. }) => a_ty == b_ty,
+            (Branch(a_to), Branch(b_to)) => self.same_block(a_to.block, b_to.block),
+            (Call(a_callee, _), Call(b_callee, _)) => {
+                a_callee == b_callee || (*a_callee, *b_callee) == self.functions
+            }
+            (CastPtr(_, a_ty, a_offs), CastPtr(_, b_ty, b_offs)) => {
+                a_ty == b_ty && a_offs == b_offs
+            }
+            (Cmp(a_pred, ..), Cmp(b_pred, ..)) => a_pred == b_pred,
+            (
+                ConditionalBranch {
+                    true_block: a_true,
+                    false_block: a_false,
+                    ..
+                },
+                ConditionalBranch {
+                    true_block: b_true,
+                    false_block: b_false,
+                    ..
+                },
+            ) => {
+                self.same_block(a_true.block, b_true.block)
+                    && self.same_block(a_false.block, b_false.block)
+            }
+            (
+                ContractCall {
+                    return_type: a_ty,
+                    name: a_name,
+                    ..
+                },
+                ContractCall {
+                    return_type: b_ty,
+                    name: b_name,
+                    ..
+                },
+            ) => a_ty == b_ty && a_name == b_name,
+            (
+                ExtractValue {
+                    ty: a_ty,
+                    indices: a_indices,
+                    ..
+                },
+                ExtractValue {
+                    ty: b_ty,
+                    indices: b_indices,
+                    ..
+                },
+            )
+            | (
+                InsertValue {
+                    ty: a_ty,
+                    indices: a_indices,
+                    ..
+                },
+                InsertValue {
+                    ty: b_ty,
+                    indices: b_indices,
+                    ..
+                },
+            ) => a_ty == b_ty && a_indices == b_indices,
+            (FuelVm(a_inst), FuelVm(b_inst)) => Self::same_fuel_vm_opcode(a_inst, b_inst),
+            (GetLocal(a_local), GetLocal(b_local)) => self.same_local(*a_local, *b_local),
+            (
+                MemCopy {
+                    byte_len: a_len, ..
+                },
+                MemCopy {
+                    byte_len: b_len, ..
+                },
+            ) => a_len == b_len,
+            _ => false,
+        }
+    }
+
+    fn same_fuel_vm_opcode(a: &FuelVmInstruction, b: &FuelVmInstruction) -> bool {
+        use FuelVmInstruction::*;
+
+        match (a, b) {
+            // The storage key is taken from the instruction's metadata, which isn't compared.
+            (GetStorageKey, GetStorageKey) => false,
+            (
+                Gtf {
+                    tx_field_id: a_id, ..
+                },
+                Gtf {
+                    tx_field_id: b_id, ..
+                },
+            ) => a_id == b_id,
+            (Log { log_ty: a_ty, .. }, Log { log_ty: b_ty, .. }) => a_ty == b_ty,
+            (ReadRegister(a_reg), ReadRegister(b_reg)) => {
+                std::mem::discriminant(a_reg) == std::mem::discriminant(b_reg)
+            }
+            (Revert(_), Revert(_))
+            | (Smo { .. }, Smo { .. })
+            | (StateClear { .. }, StateClear { .. })
+            | (StateLoadQuadWord { .. }, StateLoadQuadWord { .. })
+            | (StateLoadWord(_), StateLoadWord(_))
+            | (StateStoreQuadWord { .. }, StateStoreQuadWord { .. })
+            | (StateStoreWord { .. }, StateStoreWord { .. }) => true,
+            _ => false,
+        }
+    }
+
+    fn same_asm_block(
+        &self,
+        a_block: AsmBlock,
+        a_args: &[AsmArg],
+        b_block: AsmBlock,
+        b_args: &[AsmArg],
+    ) -> bool {
+        let a_block = a_block.get_content(self.context);
+        let b_block = b_block.get_content(self.context);
+        a_args.len() == b_args.len()
+            && a_args.iter().zip(b_args.iter()).all(|(a_arg, b_arg)| {
+                a_arg.name == b_arg.name
+                    && a_arg.initializer.is_some() == b_arg.initializer.is_some()
+            })
+            && a_block.args_names == b_block.args_names
+            && a_block.return_type == b_block.return_type
+            && a_block.return_name == b_block.return_name
+            && a_block.body.len() == b_block.body.len()
+            && a_block
+                .body
+                .iter()
+                .zip(b_block.body.iter())
+                .all(|(a_inst, b_inst)| {
+                    a_inst.name == b_inst.name
+                        && a_inst.args == b_inst.args
+                        && a_inst.immediate == b_inst.immediate
+                })
+    }
+
+    fn same_value(&self, a: Value, b: Value) -> bool {
+        if let Some(mapped) = self.values.get(&b) {
+            return *mapped == a;
+        }
+        match (
+            &self.context.values[a.0].value,
+            &self.context.values[b.0].value,
+        ) {
+            (ValueDatum::Constant(a_const), ValueDatum::Constant(b_const)) => {
+                same_constant(a_const, b_const)
+            }
+            // Configurables are global to the module.
+            (ValueDatum::Configurable(_), ValueDatum::Configurable(_)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn same_block(&self, a: Block, b: Block) -> bool {
+        self.blocks.get(&b) == Some(&a)
+    }
+
+    /// Whether the locals are the same, mapping `b` to `a` if neither has been mapped yet.
+    fn same_local(&mut self, a: LocalVar, b: LocalVar) -> bool {
+        if let Some(mapped) = self.locals.get(&b) {
+            return *mapped == a;
+        }
+        let same = !self.mapped_locals.contains(&a)
+            && a.get_type(self.context) == b.get_type(self.context)
+            && match (
+                a.get_initializer(self.context),
+                b.get_initializer(self.context),
+            ) {
+                (None, None) => true,
+                (Some(a_init), Some(b_init)) => same_constant(a_init, b_init),
+                _ => false,
+            };
+        if same {
+            self.locals.insert(b, a);
+            self.mapped_locals.insert(a);
+        }
+        same
+    }
+}
+
+/// Whether the constants are identical.  Unlike [`Constant::eq()`], the types must match exactly
+/// and undefined constants are identical to each other, as the same code is generated for them.
+fn same_constant(a: &Constant, b: &Constant) -> bool {
+    a.ty == b.ty
+        && match (&a.value, &b.value) {
+            (ConstantValue::Undef, ConstantValue::Undef)
+            | (ConstantValue::Unit, ConstantValue::Unit) => true,
+            (ConstantValue::Bool(a), ConstantValue::Bool(b)) => a == b,
+            (ConstantValue::Uint(a), ConstantValue::Uint(b)) => a == b,
+            (ConstantValue::B256(a), ConstantValue::B256(b)) => a == b,
+            (ConstantValue::String(a), ConstantValue::String(b)) => a == b,
+            (ConstantValue::Array(a), ConstantValue::Array(b))
+            | (ConstantValue::Struct(a), ConstantValue::Struct(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_constant(a, b))
+            }
+            _ => false,
+        }
+}
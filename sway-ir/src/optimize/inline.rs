@@ -45,6 +45,36 @@ pub fn create_inline_in_non_predicate_pass() -> Pass {
     }
 }
 
+pub fn create_inline_required_in_non_predicate_pass() -> Pass {
+    Pass {
+        name: "inline",
+        descr: "inline only the function calls which cannot be compiled as calls.",
+        runner: ScopedPass::ModulePass(PassMutability::Transform(
+            inline_required_in_non_predicate_module,
+        )),
+    }
+}
+
+pub fn create_inline_single_calls_in_non_predicate_pass() -> Pass {
+    Pass {
+        name: "inline",
+        descr: "inline function calls to functions which are called only once.",
+        runner: ScopedPass::ModulePass(PassMutability::Transform(
+            inline_single_calls_in_non_predicate_module,
+        )),
+    }
+}
+
+pub fn create_inline_aggressively_in_non_predicate_pass() -> Pass {
+    Pass {
+        name: "inline",
+        descr: "inline function calls, including calls to larger functions.",
+        runner: ScopedPass::ModulePass(PassMutability::Transform(
+            inline_aggressively_in_non_predicate_module,
+        )),
+    }
+}
+
 /// This is a copy of sway_core::inline::Inline.
 /// TODO: Reuse: Depend on sway_core? Move it to sway_types?
 #[derive(Debug)]
@@ -114,10 +144,94 @@ pub fn inline_in_predicate_module(
     Ok(modified)
 }
 
+/// The maximum number of instructions of a function which is inlined regardless of how often it
+/// is called.
+const MAX_INLINE_INSTRS_COUNT: usize = 4;
+
+/// The maximum number of instructions of a function which is inlined by the aggressive inliner,
+/// regardless of how often it is called.
+const MAX_AGGRESSIVE_INLINE_INSTRS_COUNT: usize = 16;
+
+/// Which function calls are inlined in a non-predicate program, in addition to those which must
+/// always be inlined.
+#[derive(Clone, Copy)]
+struct InlineHeuristic {
+    /// Whether functions which are called only once are inlined.
+    single_calls: bool,
+    /// The maximum number of instructions of a function for it to be inlined, if small functions
+    /// are to be inlined.
+    max_instrs: Option<usize>,
+}
+
 pub fn inline_in_non_predicate_module(
     context: &mut Context,
     _: &AnalysisResults,
     module: Module,
+) -> Result<bool, IrError> {
+    inline_in_non_predicate_module_with(
+        context,
+        module,
+        InlineHeuristic {
+            single_calls: true,
+            max_instrs: Some(MAX_INLINE_INSTRS_COUNT),
+        },
+    )
+}
+
+/// Inline only the function calls which cannot be compiled as calls, i.e. those with too many
+/// arguments or which get the storage key.
+pub fn inline_required_in_non_predicate_module(
+    context: &mut Context,
+    _: &AnalysisResults,
+    module: Module,
+) -> Result<bool, IrError> {
+    inline_in_non_predicate_module_with(
+        context,
+        module,
+        InlineHeuristic {
+            single_calls: false,
+            max_instrs: None,
+        },
+    )
+}
+
+/// Inline the required function calls and the calls to functions which are called only once,
+/// neither of which increases the size of the program.
+pub fn inline_single_calls_in_non_predicate_module(
+    context: &mut Context,
+    _: &AnalysisResults,
+    module: Module,
+) -> Result<bool, IrError> {
+    inline_in_non_predicate_module_with(
+        context,
+        module,
+        InlineHeuristic {
+            single_calls: true,
+            max_instrs: None,
+        },
+    )
+}
+
+/// Inline function calls like `inline_in_non_predicate_module`, but also inline larger functions.
+pub fn inline_aggressively_in_non_predicate_module(
+    context: &mut Context,
+    _: &AnalysisResults,
+    module: Module,
+) -> Result<bool, IrError> {
+    inline_in_non_predicate_module_with(
+        context,
+        module,
+        InlineHeuristic {
+            single_calls: true,
+            max_instrs: Some(MAX_AGGRESSIVE_INLINE_INSTRS_COUNT),
+        },
+    )
+}
+
+fn inline_in_non_predicate_module_with(
+    context: &mut Context,
+    module: Module,
+    heuristic: InlineHeuristic,
 ) -> Result<bool, IrError> {
     // Inspect ALL calls and count how often each function is called.
     let call_counts: HashMap<Function, u64> =
@@ -154,31 +268,32 @@ pub fn inline_in_non_predicate_module(
             return true;
         }
 
-        // If the function is called only once then definitely inline it.
-        if call_counts.get(func).copied().unwrap_or(0) == 1 {
+        // The storage key for `__get_storage_key` is the one for the storage field the function
+        // was called on, which is only known once it's inlined into the caller.
+        if func.instruction_iter(ctx).any(|(_block, ins)| {
+            matches!(
+                ins.get_instruction(ctx),
+                Some(Instruction::FuelVm(FuelVmInstruction::GetStorageKey))
+            )
+        }) {
             return true;
         }
 
-        // If the function is (still) small then also inline it.
-        const MAX_INLINE_INSTRS_COUNT: usize = 4;
-        if func.num_instructions(ctx) <= MAX_INLINE_INSTRS_COUNT {
+        // If the function is called only once then definitely inline it.
+        if heuristic.single_calls && call_counts.get(func).copied().unwrap_or(0) == 1 {
             return true;
         }
 
-        // As per https://github.com/FuelLabs/sway/issues/2819 we can hit problems if a function
-        // argument is used as a pointer (probably because it has a ref type) although it actually
-        // isn't one.  Ref type args which aren't pointers need to be inlined.
-        if func.args_iter(ctx).any(|(_name, arg_val)| {
-            arg_val
-                .get_argument_type_and_byref(ctx)
-                .map(|(ty, by_ref)| {
-                    by_ref || !(ty.is_unit(ctx) | ty.is_bool(ctx) | ty.is_uint(ctx))
-                })
-                .unwrap_or(false)
-        }) {
+        // If the function is (still) small then also inline it.
+        if heuristic
+            .max_instrs
+            .map_or(false, |max_instrs| func.num_instructions(ctx) <= max_instrs)
+        {
             return true;
         }
 
+        // Ref type args and return values are passed by reference, so they don't make the call
+        // any more expensive and don't need to be inlined.
         false
     };
 
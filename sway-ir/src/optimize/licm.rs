@@ -0,0 +1,168 @@
+//! ## Loop Invariant Code Motion
+//!
+//! This optimization hoists instructions whose operands are all defined outside of a loop into
+//! the loop preheader, so they are computed once rather than on every iteration.
+//!
+//! Only instructions without side effects are hoisted.  Those which may trap, like arithmetic
+//! which may overflow, are only hoisted from blocks which are executed on every iteration of the
+//! loop, and those which read memory or storage only from loops which don't write to either.
+
+use rustc_hash::FxHashSet;
+
+use crate::{
+    analysis::{compute_loops, dominates, Loop},
+    dominator::compute_dom_tree,
+    AnalysisResults, Block, Context, DomTree, FuelVmInstruction, Function, Instruction, IrError,
+    Pass, PassMutability, ScopedPass, Value,
+};
+
+pub fn create_licm_pass() -> Pass {
+    Pass {
+        name: "licm",
+        descr: "Loop invariant code motion.",
+        runner: ScopedPass::FunctionPass(PassMutability::Transform(licm)),
+    }
+}
+
+/// Hoist the loop invariant instructions of every loop in the function with a preheader, and
+/// return true if the function was modified.
+pub fn licm(
+    context: &mut Context,
+    _: &AnalysisResults,
+    function: Function,
+) -> Result<bool, IrError> {
+    let (dom_tree, po) = compute_dom_tree(context, &function);
+
+    // Inner loops come first, so instructions hoisted into an inner loop preheader may then be
+    // hoisted out of the outer loop too.
+    let mut modified = false;
+    for lp in compute_loops(context, &dom_tree, &po) {
+        let rpo = po
+            .po_to_block
+            .iter()
+            .rev()
+            .filter(|block| lp.contains(block))
+            .cloned()
+            .collect::<Vec<_>>();
+        modified |= hoist_invariants(context, &dom_tree, &lp, &rpo);
+    }
+
+    Ok(modified)
+}
+
+/// How an instruction may be hoisted.
+enum Hoistable {
+    /// The instruction may be executed speculatively.
+    Always,
+    /// The instruction may trap, so must be executed whenever the loop is.
+    IfGuaranteed,
+    /// The instruction may trap and reads memory or storage.
+    IfGuaranteedAndNoWrites,
+}
+
+fn get_hoistable(instruction: &Instruction) -> Option<Hoistable> {
+    use crate::BinaryOpKind::*;
+    match instruction {
+        Instruction::AddrOf(_)
+        | Instruction::BitCast(..)
+        | Instruction::CastPtr(..)
+        | Instruction::Cmp(..)
+        | Instruction::IntToPtr(..)
+        | Instruction::FuelVm(FuelVmInstruction::GetStorageKey)
+        | Instruction::BinaryOp {
+            op: And | Or | Xor | Lsh | Rsh,
+            ..
+        } => Some(Hoistable::Always),
+        Instruction::BinaryOp { .. } | Instruction::FuelVm(FuelVmInstruction::Gtf { .. }) => {
+            Some(Hoistable::IfGuaranteed)
+        }
+        Instruction::ExtractElement { .. }
+        | Instruction::ExtractValue { .. }
+        | Instruction::Load(_)
+        | Instruction::FuelVm(FuelVmInstruction::StateLoadWord(_)) => {
+            Some(Hoistable::IfGuaranteedAndNoWrites)
+        }
+        // `get_local` isn't hoisted, as for copy types it also loads the local's value when
+        // compiled to asm.
+        _ => None,
+    }
+}
+
+fn hoist_invariants(context: &mut Context, dom_tree: &DomTree, lp: &Loop, rpo: &[Block]) -> bool {
+    let preheader = match lp.get_preheader(context) {
+        Some(preheader) => preheader,
+        None => return false,
+    };
+
+    let exiting_blocks = lp.exiting_blocks(context);
+    let has_writes = rpo.iter().any(|block| {
+        block.instruction_iter(context).any(|inst| {
+            inst.get_instruction(context)
+                .unwrap()
+                .may_have_side_effect()
+        })
+    });
+
+    // The values defined within the loop which haven't been hoisted.
+    let mut loop_values: FxHashSet<Value> = rpo
+        .iter()
+        .flat_map(|block| {
+            block
+                .arg_iter(context)
+                .cloned()
+                .chain(block.instruction_iter(context))
+        })
+        .collect();
+
+    let mut hoisted = Vec::new();
+    for block in rpo {
+        // A block dominating every exit and latch of the loop is executed on each iteration.
+        let mut is_guaranteed = None;
+        let mut get_is_guaranteed = || {
+            *is_guaranteed.get_or_insert_with(|| {
+                exiting_blocks
+                    .iter()
+                    .chain(lp.latches.iter())
+                    .all(|exiting| dominates(dom_tree, *block, *exiting))
+            })
+        };
+        let mut block_hoisted = Vec::new();
+        for inst in block.instruction_iter(context) {
+            let instruction = inst.get_instruction(context).unwrap();
+            let may_hoist = match get_hoistable(instruction) {
+                Some(Hoistable::Always) => true,
+                Some(Hoistable::IfGuaranteed) => get_is_guaranteed(),
+                Some(Hoistable::IfGuaranteedAndNoWrites) => !has_writes && get_is_guaranteed(),
+                None => false,
+            };
+            if may_hoist
+                && instruction
+                    .get_operands()
+                    .iter()
+                    .all(|operand| !loop_values.contains(operand))
+            {
+                loop_values.remove(&inst);
+                block_hoisted.push(inst);
+            }
+        }
+        if !block_hoisted.is_empty() {
+            let block_hoisted_set = block_hoisted.iter().collect::<FxHashSet<_>>();
+            context.blocks[block.0]
+                .instructions
+                .retain(|inst| !block_hoisted_set.contains(inst));
+            hoisted.append(&mut block_hoisted);
+        }
+    }
+
+    if hoisted.is_empty() {
+        return false;
+    }
+
+    // Insert the hoisted instructions, in order, before the preheader terminator.
+    let preheader_insts = &mut context.blocks[preheader.0].instructions;
+    let terminator = preheader_insts.pop().unwrap();
+    preheader_insts.append(&mut hoisted);
+    preheader_insts.push(terminator);
+
+    true
+}
@@ -0,0 +1,288 @@
+//! ## Loop Unrolling
+//!
+//! This optimization fully unrolls small loops with a constant trip count, replacing them with a
+//! straight-line copy of each iteration appended to the loop preheader.
+//!
+//! Only loops made up of a header, which conditionally branches either to the latch without
+//! arguments or out of the loop, and a latch, which branches back to the header, are unrolled.
+//! The condition must compare a header argument against a constant, where the argument starts at
+//! a constant and is incremented or decremented by a constant on each iteration.
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    analysis::compute_loops, dominator::compute_dom_tree, AnalysisResults, BinaryOpKind, Block,
+    BranchToWithArgs, ConstantValue, Context, Function, Instruction, IrError, Pass, PassMutability,
+    Predicate, ScopedPass, Value,
+};
+
+pub fn create_loop_unroll_pass() -> Pass {
+    Pass {
+        name: "loop_unroll",
+        descr: "Full unrolling of loops with a small constant trip count.",
+        runner: ScopedPass::FunctionPass(PassMutability::Transform(loop_unroll)),
+    }
+}
+
+/// The most iterations of a loop to unroll.
+const MAX_TRIP_COUNT: u64 = 8;
+
+/// The most instructions a loop may have once unrolled.
+const MAX_UNROLLED_INSTRUCTIONS: usize = 64;
+
+/// Unroll every loop in the function which may be, and return true if the function was modified.
+pub fn loop_unroll(
+    context: &mut Context,
+    _: &AnalysisResults,
+    function: Function,
+) -> Result<bool, IrError> {
+    let mut modified = false;
+    while unroll_one_loop(context, function)? {
+        modified = true;
+    }
+    Ok(modified)
+}
+
+/// Unroll the first loop in the function which may be.  The loop analysis is invalidated by
+/// unrolling, and unrolling an inner loop may make its outer loop a candidate, so it is redone
+/// for each loop.
+fn unroll_one_loop(context: &mut Context, function: Function) -> Result<bool, IrError> {
+    let (dom_tree, po) = compute_dom_tree(context, &function);
+    for lp in compute_loops(context, &dom_tree, &po) {
+        if lp.blocks.len() != 2 || lp.latches.len() != 1 || lp.latches[0] == lp.header {
+            continue;
+        }
+        let preheader = match lp.get_preheader(context) {
+            Some(preheader) => preheader,
+            None => continue,
+        };
+        if let Some(unrollable) = get_unrollable(context, preheader, lp.header, lp.latches[0]) {
+            unroll(context, function, unrollable)?;
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+struct Unrollable {
+    preheader: Block,
+    header: Block,
+    latch: Block,
+    exit: BranchToWithArgs,
+    trip_count: u64,
+}
+
+fn get_unrollable(
+    context: &Context,
+    preheader: Block,
+    header: Block,
+    latch: Block,
+) -> Option<Unrollable> {
+    // The header must either continue to the latch or exit the loop.  The latch instructions are
+    // copied as is for each iteration, so it may not have any arguments.
+    let (cond_value, continue_if, exit) = match header.get_terminator(context)? {
+        Instruction::ConditionalBranch {
+            cond_value,
+            true_block,
+            false_block,
+        } => {
+            if true_block.block == latch && false_block.block != header {
+                (cond_value, true, false_block.clone())
+            } else if false_block.block == latch && true_block.block != header {
+                (cond_value, false, true_block.clone())
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    };
+    if latch.num_args(context) != 0 {
+        return None;
+    }
+    if !matches!(latch.get_terminator(context)?, Instruction::Branch(_)) {
+        return None;
+    }
+
+    // The condition compares an induction variable, a header argument, against a constant.
+    let (pred, lhs, rhs) = match cond_value.get_instruction(context)? {
+        Instruction::Cmp(pred, lhs, rhs) => (*pred, *lhs, *rhs),
+        _ => return None,
+    };
+    let (arg_idx, bound, iv_on_lhs) = match (
+        get_arg_index(context, header, lhs),
+        get_arg_index(context, header, rhs),
+    ) {
+        (Some(idx), None) => (idx, get_uint64(context, rhs)?, true),
+        (None, Some(idx)) => (idx, get_uint64(context, lhs)?, false),
+        _ => return None,
+    };
+    let iv = header.get_arg(context, arg_idx)?;
+
+    // It starts at a constant and is incremented or decremented by a constant in the latch.
+    let init = get_uint64(
+        context,
+        *preheader.get_succ_params(context, &header).get(arg_idx)?,
+    )?;
+    let next = *latch.get_succ_params(context, &header).get(arg_idx)?;
+    if !latch.instruction_iter(context).any(|inst| inst == next) {
+        return None;
+    }
+    let (op, step) = match next.get_instruction(context)? {
+        Instruction::BinaryOp {
+            op: op @ BinaryOpKind::Add,
+            arg1,
+            arg2,
+        } if *arg1 == iv => (*op, get_uint64(context, *arg2)?),
+        Instruction::BinaryOp {
+            op: op @ BinaryOpKind::Add,
+            arg1,
+            arg2,
+        } if *arg2 == iv => (*op, get_uint64(context, *arg1)?),
+        Instruction::BinaryOp {
+            op: op @ BinaryOpKind::Sub,
+            arg1,
+            arg2,
+        } if *arg1 == iv => (*op, get_uint64(context, *arg2)?),
+        _ => return None,
+    };
+
+    // Simulate the loop to find how many times the latch is executed, giving up if the induction
+    // variable would overflow, as the loop would then panic.
+    let mut value = init;
+    let mut trip_count = 0;
+    loop {
+        let (lhs, rhs) = if iv_on_lhs {
+            (value, bound)
+        } else {
+            (bound, value)
+        };
+        let cond = match pred {
+            Predicate::Equal => lhs == rhs,
+            Predicate::LessThan => lhs < rhs,
+            Predicate::GreaterThan => lhs > rhs,
+        };
+        if cond != continue_if {
+            break;
+        }
+        trip_count += 1;
+        if trip_count > MAX_TRIP_COUNT {
+            return None;
+        }
+        value = match op {
+            BinaryOpKind::Add => value.checked_add(step)?,
+            _ => value.checked_sub(step)?,
+        };
+    }
+
+    let unrolled_instructions =
+        trip_count as usize * (header.num_instructions(context) + latch.num_instructions(context));
+    (unrolled_instructions <= MAX_UNROLLED_INSTRUCTIONS).then_some(Unrollable {
+        preheader,
+        header,
+        latch,
+        exit,
+        trip_count,
+    })
+}
+
+fn get_arg_index(context: &Context, block: Block, value: Value) -> Option<usize> {
+    block.arg_iter(context).position(|arg| *arg == value)
+}
+
+fn get_uint64(context: &Context, value: Value) -> Option<u64> {
+    match value.get_constant(context) {
+        Some(constant) if constant.ty.is_uint64(context) => match constant.value {
+            ConstantValue::Uint(n) => Some(n),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn unroll(
+    context: &mut Context,
+    function: Function,
+    unrollable: Unrollable,
+) -> Result<(), IrError> {
+    let Unrollable {
+        preheader,
+        header,
+        latch,
+        exit,
+        trip_count,
+    } = unrollable;
+
+    // Copy the header and latch instructions, for each iteration, to the end of the preheader,
+    // followed by the header instructions of the final iteration which leaves the loop.
+    let mut value_map = FxHashMap::default();
+    let mut header_args = preheader.get_succ_params(context, &header);
+    let mut new_insts = Vec::new();
+    for iteration in 0..=trip_count {
+        for (arg, value) in header.arg_iter(context).zip(header_args.iter()) {
+            value_map.insert(*arg, *value);
+        }
+        copy_instructions(context, header, &mut value_map, &mut new_insts);
+        if iteration < trip_count {
+            copy_instructions(context, latch, &mut value_map, &mut new_insts);
+            header_args = latch
+                .get_succ_params(context, &header)
+                .iter()
+                .map(|value| value_map.get(value).copied().unwrap_or(*value))
+                .collect();
+        }
+    }
+
+    // Branch straight out of the loop.
+    let md_idx = context.blocks[header.0]
+        .instructions
+        .last()
+        .and_then(|terminator| terminator.get_metadata(context));
+    let exit_args = exit
+        .args
+        .iter()
+        .map(|value| value_map.get(value).copied().unwrap_or(*value))
+        .collect();
+    let branch = Value::new_instruction(
+        context,
+        Instruction::Branch(BranchToWithArgs {
+            block: exit.block,
+            args: exit_args,
+        }),
+    )
+    .add_metadatum(context, md_idx);
+    new_insts.push(branch);
+
+    let preheader_insts = &mut context.blocks[preheader.0].instructions;
+    preheader_insts.pop();
+    preheader_insts.append(&mut new_insts);
+    exit.block.replace_pred(context, &header, &preheader);
+
+    function.remove_block(context, &header)?;
+    function.remove_block(context, &latch)?;
+
+    // The header values may be used after the loop, so are replaced with those of its final
+    // iteration.
+    function.replace_values(context, &value_map, None);
+
+    Ok(())
+}
+
+/// Copy the non-terminator instructions of `block`, mapping their operands with `value_map`, to
+/// which the copies are added.
+fn copy_instructions(
+    context: &mut Context,
+    block: Block,
+    value_map: &mut FxHashMap<Value, Value>,
+    new_insts: &mut Vec<Value>,
+) {
+    let insts = block.instruction_iter(context).collect::<Vec<_>>();
+    for inst in &insts[..insts.len() - 1] {
+        let instruction = inst.get_instruction(context).unwrap().clone();
+        let md_idx = inst.get_metadata(context);
+        let new_inst = Value::new_instruction(context, instruction).add_metadatum(context, md_idx);
+        new_inst.replace_instruction_values(context, value_map);
+        value_map.insert(*inst, new_inst);
+        new_insts.push(new_inst);
+    }
+}
@@ -0,0 +1,418 @@
+//! ## Return Value Demotion
+//!
+//! Non-entry functions which return a ref type (a struct, tuple, array, etc.) take an extra
+//! by-ref 'out' argument instead, which the caller points at a temporary local.  The callee
+//! builds the returned value in a local of its own and copies it to the out argument, then the
+//! caller copies it again from the temporary to wherever it is wanted.  Ref type arguments are
+//! already passed by reference, as a pointer to the caller's memory.
+//!
+//! This pass removes both of the return value copies:
+//! - In the callee, a local which is only copied to the out argument when returning is replaced
+//!   by the out argument itself, so the returned value is built in place.
+//! - At a call site, a temporary which is only copied to another local, or to the caller's own
+//!   out argument, is replaced by that destination, so the callee writes the value straight to
+//!   where it is wanted.  This is only done if the destination can't be read or written by the
+//!   callee via its other arguments.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    AnalysisResults, Block, Context, Function, Instruction, IrError, LocalVar, Pass,
+    PassMutability, ScopedPass, Type, Value, ValueDatum,
+};
+
+pub fn create_ret_demotion_pass() -> Pass {
+    Pass {
+        name: "ret_demotion",
+        descr: "Demotion of copied return values to writes through the 'out' argument.",
+        runner: ScopedPass::FunctionPass(PassMutability::Transform(ret_demotion)),
+    }
+}
+
+/// Replace the temporaries of calls to functions returning by reference with the destinations
+/// their values are copied to, and build the value returned by the function in its 'out'
+/// argument.  Return true if the function was modified.
+pub fn ret_demotion(
+    context: &mut Context,
+    _: &AnalysisResults,
+    function: Function,
+) -> Result<bool, IrError> {
+    let mut modified = false;
+
+    // Forwarding one call site changes the uses of the other values, so find them one at a time.
+    while let Some(call_site) = find_forwardable_call_site(context, &function) {
+        forward_call_site(context, &function, call_site);
+        modified = true;
+    }
+
+    if let Some(local) = find_returned_local(context, &function) {
+        build_returned_local_in_place(context, &function, local);
+        modified = true;
+    }
+
+    Ok(modified)
+}
+
+/// A call whose returned value is copied from its temporary to `dst` straight after the call.
+struct CallSite {
+    block: Block,
+    call: Value,
+    temp_ptr: Value,
+    temp: LocalVar,
+    copy: Value,
+    dst: Value,
+}
+
+fn is_copy_type(context: &Context, ty: Type) -> bool {
+    ty.is_unit(context) || ty.is_bool(context) || ty.is_uint(context)
+}
+
+/// Map each value to the instructions using it.
+fn get_uses(context: &Context, function: &Function) -> FxHashMap<Value, Vec<Value>> {
+    let mut uses: FxHashMap<Value, Vec<Value>> = FxHashMap::default();
+    for (_, inst) in function.instruction_iter(context) {
+        for op in inst.get_instruction(context).unwrap().get_operands() {
+            uses.entry(op).or_default().push(inst);
+        }
+    }
+    uses
+}
+
+fn find_forwardable_call_site(context: &Context, function: &Function) -> Option<CallSite> {
+    let uses = get_uses(context, function);
+    let is_used_only_by = |val: &Value, user: &Value| {
+        uses.get(val)
+            .map_or(false, |users| users.iter().all(|u| u == user))
+    };
+    let own_ret_value_arg = function.get_ret_value_arg(context);
+
+    for block in function.block_iter(context) {
+        let insts = &context.blocks[block.0].instructions;
+        for (call_idx, call) in insts.iter().enumerate() {
+            let (callee, args) = match call.get_instruction(context) {
+                Some(Instruction::Call(callee, args)) => (callee, args),
+                _ => continue,
+            };
+            if callee.get_ret_value_arg(context).is_none() {
+                continue;
+            }
+            let ret_ty = callee.get_return_type(context);
+
+            // The temporary must be used for nothing but the call.
+            let temp_ptr = *args.last().unwrap();
+            let temp = match temp_ptr.get_instruction(context) {
+                Some(Instruction::GetLocal(temp)) if temp.get_initializer(context).is_none() => {
+                    *temp
+                }
+                _ => continue,
+            };
+            if !is_used_only_by(&temp_ptr, call)
+                || args.iter().filter(|arg| **arg == temp_ptr).count() != 1
+                || get_local_ptrs(context, function, &temp).len() != 1
+            {
+                continue;
+            }
+
+            // The returned value must be copied as a whole by the next instruction, ignoring
+            // `get_local`s, and used for nothing else.
+            let copy = match insts[call_idx + 1..].iter().find(|inst| {
+                !matches!(
+                    inst.get_instruction(context),
+                    Some(Instruction::GetLocal(_))
+                )
+            }) {
+                Some(copy) => *copy,
+                None => continue,
+            };
+            let dst = match copy.get_instruction(context) {
+                Some(Instruction::Store {
+                    dst_val,
+                    stored_val,
+                }) if stored_val == call => *dst_val,
+                Some(Instruction::MemCopy {
+                    dst_val,
+                    src_val,
+                    byte_len,
+                }) if src_val == call && *byte_len == ret_ty.size_in_bytes(context) => *dst_val,
+                _ => continue,
+            };
+            if !is_used_only_by(call, &copy) {
+                continue;
+            }
+
+            // The destination is either a whole local of the returned type or our own 'out'
+            // argument, neither of which may be reachable from the other arguments.
+            let dst_ptrs = match dst.get_instruction(context) {
+                Some(Instruction::GetLocal(local))
+                    if *local != temp
+                        && local.get_type(context) == ret_ty
+                        && local.get_initializer(context).is_none() =>
+                {
+                    get_local_ptrs(context, function, local)
+                }
+                None if Some(dst) == own_ret_value_arg && dst.get_type(context) == Some(ret_ty) => {
+                    FxHashSet::from_iter([dst])
+                }
+                _ => continue,
+            };
+            match get_derived_ptrs(context, function, dst_ptrs) {
+                Some(ptrs) if !args.iter().any(|arg| ptrs.contains(arg)) => (),
+                _ => continue,
+            }
+
+            return Some(CallSite {
+                block,
+                call: *call,
+                temp_ptr,
+                temp,
+                copy,
+                dst,
+            });
+        }
+    }
+    None
+}
+
+fn forward_call_site(context: &mut Context, function: &Function, call_site: CallSite) {
+    let CallSite {
+        block,
+        call,
+        temp_ptr,
+        temp,
+        copy,
+        dst,
+    } = call_site;
+
+    // If the destination pointer is only got after the call then move it before.
+    let insts = &mut context.blocks[block.0].instructions;
+    let call_idx = insts.iter().position(|inst| *inst == call).unwrap();
+    if let Some(dst_idx) = insts[call_idx..].iter().position(|inst| *inst == dst) {
+        insts.remove(call_idx + dst_idx);
+        insts.insert(call_idx, dst);
+    }
+
+    call.replace_instruction_values(context, &FxHashMap::from_iter([(temp_ptr, dst)]));
+    block.remove_instruction(context, copy);
+    remove_local(context, function, &temp);
+}
+
+/// Return the `get_local` instructions for the local.
+fn get_local_ptrs(context: &Context, function: &Function, local: &LocalVar) -> FxHashSet<Value> {
+    function
+        .instruction_iter(context)
+        .filter_map(|(_, inst)| match inst.get_instruction(context) {
+            Some(Instruction::GetLocal(inst_local)) if inst_local == local => Some(inst),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Remove the local and all the `get_local` instructions for it, which must be unused.
+fn remove_local(context: &mut Context, function: &Function, local: &LocalVar) {
+    let blocks = function.block_iter(context).collect::<Vec<_>>();
+    for block in blocks {
+        context.blocks[block.0].instructions.retain(|inst| {
+            !matches!(
+                &context.values[inst.0].value,
+                ValueDatum::Instruction(Instruction::GetLocal(inst_local))
+                    if inst_local == local
+            )
+        });
+    }
+    if let Some(name) = function.lookup_local_name(context, local).cloned() {
+        context.functions[function.0].local_storage.remove(&name);
+    }
+}
+
+/// Return the pointers which refer to the same memory as `ptrs`, i.e. `ptrs` and any ref type
+/// values derived from them, or `None` if the memory escapes via its address.
+fn get_derived_ptrs(
+    context: &Context,
+    function: &Function,
+    mut ptrs: FxHashSet<Value>,
+) -> Option<FxHashSet<Value>> {
+    // Instructions aren't necessarily in dominance order, so iterate until no more pointers are
+    // found.
+    loop {
+        let mut new_ptrs = Vec::new();
+        for (_, inst) in function.instruction_iter(context) {
+            let instruction = inst.get_instruction(context).unwrap();
+            if !instruction
+                .get_operands()
+                .iter()
+                .any(|op| ptrs.contains(op))
+            {
+                continue;
+            }
+            match instruction {
+                Instruction::AddrOf(_) | Instruction::AsmBlock(..) => return None,
+                Instruction::Branch(to_block) => new_ptrs.extend(
+                    to_block
+                        .args
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, arg)| ptrs.contains(arg))
+                        .filter_map(|(idx, _)| to_block.block.get_arg(context, idx)),
+                ),
+                Instruction::ConditionalBranch {
+                    true_block,
+                    false_block,
+                    ..
+                } => {
+                    for to_block in [true_block, false_block] {
+                        new_ptrs.extend(
+                            to_block
+                                .args
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, arg)| ptrs.contains(arg))
+                                .filter_map(|(idx, _)| to_block.block.get_arg(context, idx)),
+                        );
+                    }
+                }
+                _ if instruction
+                    .get_type(context)
+                    .map_or(false, |ty| !is_copy_type(context, ty)) =>
+                {
+                    new_ptrs.push(inst)
+                }
+                _ => (),
+            }
+        }
+        let num_ptrs = ptrs.len();
+        ptrs.extend(new_ptrs);
+        if ptrs.len() == num_ptrs {
+            return Some(ptrs);
+        }
+    }
+}
+
+/// Find the local which is copied to the 'out' argument by every `ret` of the function and can
+/// be replaced by it.
+fn find_returned_local(context: &Context, function: &Function) -> Option<LocalVar> {
+    let ret_value_arg = function.get_ret_value_arg(context)?;
+    let ret_ty = function.get_return_type(context);
+
+    // Every `ret` must return the 'out' argument, straight after copying the local to it.
+    let mut copies = FxHashSet::default();
+    let mut returned_local = None;
+    for block in function.block_iter(context) {
+        let insts = &context.blocks[block.0].instructions;
+        for (idx, inst) in insts.iter().enumerate() {
+            match inst.get_instruction(context) {
+                Some(Instruction::Ret(ret_val, _)) if *ret_val == ret_value_arg && idx > 0 => (),
+                Some(Instruction::Ret(..)) => return None,
+                _ => continue,
+            }
+            let copy = insts[idx - 1];
+            let local = match copy.get_instruction(context) {
+                Some(Instruction::MemCopy {
+                    dst_val,
+                    src_val,
+                    byte_len,
+                }) if *dst_val == ret_value_arg && *byte_len == ret_ty.size_in_bytes(context) => {
+                    get_whole_local(context, src_val)?
+                }
+                _ => return None,
+            };
+            if returned_local
+                .replace(local)
+                .map_or(false, |other| other != local)
+            {
+                return None;
+            }
+            copies.insert(copy);
+        }
+    }
+    let local = returned_local?;
+    if local.get_type(context) != ret_ty || local.get_initializer(context).is_some() {
+        return None;
+    }
+
+    // The 'out' argument must not be used otherwise, and the local, or any pointer derived from
+    // it, may only be used by instructions which can also access it via the argument.
+    let uses = get_uses(context, function);
+    if !uses[&ret_value_arg].iter().all(|user| {
+        copies.contains(user)
+            || matches!(
+                user.get_instruction(context),
+                Some(Instruction::Ret(ret_val, _)) if *ret_val == ret_value_arg
+            )
+    }) {
+        return None;
+    }
+    let is_supported_use = |user: &Value| {
+        matches!(
+            user.get_instruction(context),
+            Some(
+                Instruction::Call(..)
+                    | Instruction::ExtractElement { .. }
+                    | Instruction::ExtractValue { .. }
+                    | Instruction::InsertElement { .. }
+                    | Instruction::InsertValue { .. }
+                    | Instruction::Load(_)
+                    | Instruction::MemCopy { .. }
+                    | Instruction::Store { .. }
+            )
+        )
+    };
+    get_derived_ptrs(context, function, get_local_ptrs(context, function, &local))?
+        .iter()
+        .flat_map(|ptr| uses.get(ptr).into_iter().flatten())
+        .all(is_supported_use)
+        .then_some(local)
+}
+
+/// Return the local which the value refers to as a whole, by way of `insert_value`s.
+fn get_whole_local(context: &Context, val: &Value) -> Option<LocalVar> {
+    match val.get_instruction(context)? {
+        Instruction::GetLocal(local) => Some(*local),
+        Instruction::InsertValue { aggregate, .. } => get_whole_local(context, aggregate),
+        _ => None,
+    }
+}
+
+fn build_returned_local_in_place(context: &mut Context, function: &Function, local: LocalVar) {
+    let ret_value_arg = function.get_ret_value_arg(context).unwrap();
+
+    let replacements = get_local_ptrs(context, function, &local)
+        .into_iter()
+        .map(|ptr| (ptr, ret_value_arg))
+        .collect::<FxHashMap<_, _>>();
+    function.replace_values(context, &replacements, None);
+
+    // Replacing values leaves the pointers of loads and stores alone, so they're done here.
+    for (_, inst) in function.instruction_iter(context).collect::<Vec<_>>() {
+        match &mut context.values[inst.0].value {
+            ValueDatum::Instruction(Instruction::Load(ptr))
+            | ValueDatum::Instruction(Instruction::Store { dst_val: ptr, .. })
+                if replacements.contains_key(ptr) =>
+            {
+                *ptr = ret_value_arg;
+            }
+            _ => (),
+        }
+    }
+    remove_local(context, function, &local);
+
+    // The copies to the 'out' argument are now from the argument to itself.
+    let blocks = function.block_iter(context).collect::<Vec<_>>();
+    for block in blocks {
+        let insts = &context.blocks[block.0].instructions;
+        let copies = insts
+            .windows(2)
+            .filter_map(|pair| match pair[1].get_instruction(context) {
+                Some(Instruction::Ret(..)) => match pair[0].get_instruction(context) {
+                    Some(Instruction::MemCopy { dst_val, .. }) if *dst_val == ret_value_arg => {
+                        Some(pair[0])
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for copy in copies {
+            block.remove_instruction(context, copy);
+        }
+    }
+}
@@ -0,0 +1,422 @@
+//! ## Scalar Replacement of Aggregates
+//!
+//! This optimization splits struct locals into a separate local for each of their fields, so that
+//! the scalar fields can then be promoted to SSA registers by `mem2reg` and folded by the other
+//! passes.
+//!
+//! A local is split only if it doesn't escape, i.e., it is only accessed a field at a time via
+//! `extract_value` and `insert_value`, or is stored to as a whole.  Whole stores are split into a
+//! store to each field.  Fields which aren't themselves structs (including arrays and unions) are
+//! not split any further.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    AnalysisResults, Constant, ConstantValue, Context, Function, Instruction, IrError, LocalVar,
+    MetadataIndex, Pass, PassMutability, ScopedPass, Type, Value,
+};
+
+pub fn create_sroa_pass() -> Pass {
+    Pass {
+        name: "sroa",
+        descr: "Scalar replacement of aggregates.",
+        runner: ScopedPass::FunctionPass(PassMutability::Transform(sroa)),
+    }
+}
+
+/// Split the non-escaping struct locals of the function into their fields and return true if
+/// the function was modified.
+pub fn sroa(
+    context: &mut Context,
+    _: &AnalysisResults,
+    function: Function,
+) -> Result<bool, IrError> {
+    let mut modified = false;
+
+    // Splitting a local rewrites whole stores of other aggregates into it to read them a field at
+    // a time, which may in turn allow those aggregates to be split.  So keep going until there is
+    // nothing left to split.
+    loop {
+        let refs = get_splittable_local_refs(context, &function);
+        if refs.is_empty() {
+            break;
+        }
+        split_locals(context, &function, &refs);
+        modified = true;
+    }
+
+    Ok(modified)
+}
+
+/// The values referring to a local, or to one of its nested structs, mapped to the local and the
+/// path of indices from it to the struct they refer to.
+type LocalRefs = FxHashMap<Value, (LocalVar, Vec<u64>)>;
+
+/// Return the values referring to the struct locals which can be split.
+fn get_splittable_local_refs(context: &Context, function: &Function) -> LocalRefs {
+    // Locals with initializers are immutable and are placed in the data section, there's no
+    // point in splitting them.
+    let mut refs: LocalRefs = function
+        .instruction_iter(context)
+        .filter_map(|(_, inst)| match inst.get_instruction(context) {
+            Some(Instruction::GetLocal(local))
+                if local.get_type(context).is_struct(context)
+                    && local.get_initializer(context).is_none() =>
+            {
+                Some((inst, (*local, Vec::new())))
+            }
+            _ => None,
+        })
+        .collect();
+
+    // Instructions aren't necessarily in dominance order, so iterate until no more references
+    // are found.
+    loop {
+        let mut new_refs = Vec::new();
+        for (_, inst) in function.instruction_iter(context) {
+            if refs.contains_key(&inst) {
+                continue;
+            }
+            match inst.get_instruction(context) {
+                Some(Instruction::ExtractValue {
+                    aggregate, indices, ..
+                }) => {
+                    if let Some((local, path)) = refs.get(aggregate) {
+                        let path = [path.as_slice(), indices].concat();
+                        if is_struct_path(context, local.get_type(context), &path) {
+                            new_refs.push((inst, (*local, path)));
+                        }
+                    }
+                }
+                Some(Instruction::InsertValue { aggregate, .. }) => {
+                    if let Some(local_ref) = refs.get(aggregate) {
+                        new_refs.push((inst, local_ref.clone()));
+                    }
+                }
+                _ => (),
+            }
+        }
+        if new_refs.is_empty() {
+            break;
+        }
+        refs.extend(new_refs);
+    }
+
+    // A local escapes if a reference to it is used by anything other than a field access or a
+    // store to it.  Configurables can't be read a field at a time, and values of a variant type
+    // may be inserted directly into a union field, so stores of those are treated as escapes too.
+    // (Types are uniqued, so `==` is strict, unlike `Type::eq()` which matches unions to their
+    // variant types.)
+    let is_unsplittable_store = |local: &LocalVar, path: &[u64], value: &Value| {
+        let local_ty = local.get_type(context);
+        let field_ty = if path.is_empty() {
+            Some(local_ty)
+        } else {
+            local_ty.get_indexed_type(context, path)
+        };
+        value.is_configurable(context)
+            || !field_ty
+                .zip(value.get_type(context))
+                .map_or(false, |(field_ty, value_ty)| field_ty == value_ty)
+    };
+    let mut escaping = FxHashSet::default();
+    for (_, inst) in function.instruction_iter(context) {
+        match inst.get_instruction(context).unwrap() {
+            Instruction::GetLocal(_) | Instruction::ExtractValue { .. } => (),
+            Instruction::InsertValue {
+                aggregate,
+                value,
+                indices,
+                ..
+            } => {
+                if let Some((local, _)) = refs.get(value) {
+                    escaping.insert(*local);
+                }
+                if let Some((local, path)) = refs.get(aggregate) {
+                    if is_unsplittable_store(local, &[path.as_slice(), indices].concat(), value) {
+                        escaping.insert(*local);
+                    }
+                }
+            }
+            Instruction::Store {
+                dst_val,
+                stored_val,
+            } => {
+                if let Some((local, _)) = refs.get(stored_val) {
+                    escaping.insert(*local);
+                }
+                if let Some((local, path)) = refs.get(dst_val) {
+                    if is_unsplittable_store(local, path, stored_val) {
+                        escaping.insert(*local);
+                    }
+                }
+            }
+            inst => {
+                for opd in inst.get_operands() {
+                    if let Some((local, _)) = refs.get(&opd) {
+                        escaping.insert(*local);
+                    }
+                }
+            }
+        }
+    }
+
+    refs.retain(|_, (local, _)| !escaping.contains(local));
+    refs
+}
+
+/// Return whether the path of indices leads through structs only, to a struct.
+fn is_struct_path(context: &Context, ty: Type, path: &[u64]) -> bool {
+    path.iter()
+        .try_fold(ty, |ty, idx| {
+            ty.is_struct(context)
+                .then(|| ty.get_field_type(context, *idx))
+                .flatten()
+        })
+        .map_or(false, |ty| ty.is_struct(context))
+}
+
+/// Return the paths of indices to the fields of a struct which aren't themselves structs, along
+/// with their types.
+fn get_leaf_fields(context: &Context, ty: Type) -> Vec<(Vec<u64>, Type)> {
+    if !ty.is_struct(context) {
+        return vec![(Vec::new(), ty)];
+    }
+    ty.get_field_types(context)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(idx, field_ty)| {
+            get_leaf_fields(context, field_ty)
+                .into_iter()
+                .map(move |(mut path, leaf_ty)| {
+                    path.insert(0, idx as u64);
+                    (path, leaf_ty)
+                })
+        })
+        .collect()
+}
+
+/// Return the field of the constant struct at the path of indices, or `None` if the field isn't
+/// itself a constant of the field type.  Constant unions hold just the value of their variant,
+/// which may not be stored directly as the variant is right aligned within the union.
+fn get_constant_field(constant: &Constant, path: &[u64], ty: Type) -> Option<Constant> {
+    match path
+        .iter()
+        .try_fold(constant, |constant, idx| match &constant.value {
+            ConstantValue::Struct(fields) => fields.get(*idx as usize),
+            _ => None,
+        }) {
+        Some(field) => (field.ty == ty).then(|| field.clone()),
+        None => Some(Constant {
+            ty,
+            value: ConstantValue::Undef,
+        }),
+    }
+}
+
+/// The new locals holding the fields of a split local, keyed by the path of indices to them.
+struct LeafLocals {
+    locals: FxHashMap<Vec<u64>, LocalVar>,
+}
+
+impl LeafLocals {
+    /// Return the local holding the field at the path of indices, and the remainder of the path
+    /// into that field.  Returns `None` if the path leads to a (nested) struct.
+    fn lookup<'a>(&self, path: &'a [u64]) -> Option<(LocalVar, &'a [u64])> {
+        (0..=path.len()).find_map(|len| {
+            self.locals
+                .get(&path[..len])
+                .map(|local| (*local, &path[len..]))
+        })
+    }
+}
+
+/// A split local, with the new locals holding its fields.
+struct SplitLocal {
+    ty: Type,
+    leaf_locals: LeafLocals,
+}
+
+fn split_locals(context: &mut Context, function: &Function, refs: &LocalRefs) {
+    let ref_locals = refs
+        .values()
+        .map(|(local, _)| *local)
+        .collect::<FxHashSet<_>>();
+    let locals = function
+        .locals_iter(context)
+        .filter(|(_, local)| ref_locals.contains(local))
+        .map(|(name, local)| (name.clone(), *local))
+        .collect::<Vec<_>>();
+    let mut split_locals = FxHashMap::default();
+    for (name, local) in locals {
+        let local_ty = local.get_type(context);
+        let leaf_locals = LeafLocals {
+            locals: get_leaf_fields(context, local_ty)
+                .into_iter()
+                .map(|(path, leaf_ty)| {
+                    let leaf_name = path
+                        .iter()
+                        .fold(name.clone(), |name, idx| format!("{name}_{idx}"));
+                    let leaf_local =
+                        function.new_unique_local_var(context, leaf_name, leaf_ty, None);
+                    (path, leaf_local)
+                })
+                .collect(),
+        };
+        context.functions[function.0].local_storage.remove(&name);
+        split_locals.insert(
+            local,
+            SplitLocal {
+                ty: local_ty,
+                leaf_locals,
+            },
+        );
+    }
+
+    // Rebuild each block, replacing the accesses to the locals with accesses to their fields.
+    // The references to the locals are all dropped, as nothing else uses them.
+    let mut replacements = FxHashMap::default();
+    let blocks = function.block_iter(context).collect::<Vec<_>>();
+    for block in blocks {
+        let mut new_insts = Vec::new();
+        let insts = context.blocks[block.0].instructions.clone();
+        for inst in insts {
+            let mut new_inst = NewInsts {
+                insts: &mut new_insts,
+                md_idx: inst.get_metadata(context),
+            };
+            match inst.get_instruction(context).unwrap().clone() {
+                Instruction::GetLocal(_) if refs.contains_key(&inst) => (),
+                Instruction::ExtractValue { .. } if refs.contains_key(&inst) => (),
+                Instruction::ExtractValue {
+                    aggregate, indices, ..
+                } if refs.contains_key(&aggregate) => {
+                    let (local, path) = &refs[&aggregate];
+                    let path = [path.as_slice(), &indices].concat();
+                    let (leaf_local, rest) = split_locals[local].leaf_locals.lookup(&path).unwrap();
+                    let leaf_ty = leaf_local.get_type(context);
+                    let leaf_ptr = new_inst.push(context, Instruction::GetLocal(leaf_local));
+                    let field_val = if !rest.is_empty() {
+                        new_inst.push(
+                            context,
+                            Instruction::ExtractValue {
+                                aggregate: leaf_ptr,
+                                ty: leaf_ty,
+                                indices: rest.to_vec(),
+                            },
+                        )
+                    } else if is_copy_type(context, leaf_ty) {
+                        new_inst.push(context, Instruction::Load(leaf_ptr))
+                    } else {
+                        leaf_ptr
+                    };
+                    replacements.insert(inst, field_val);
+                }
+                Instruction::InsertValue {
+                    aggregate,
+                    value,
+                    indices,
+                    ..
+                } if refs.contains_key(&aggregate) => {
+                    let (local, path) = &refs[&aggregate];
+                    let path = [path.as_slice(), &indices].concat();
+                    store_fields(context, &split_locals[local], &path, value, &mut new_inst);
+                }
+                Instruction::Store {
+                    dst_val,
+                    stored_val,
+                } if refs.contains_key(&dst_val) => {
+                    let (local, path) = &refs[&dst_val];
+                    store_fields(
+                        context,
+                        &split_locals[local],
+                        path,
+                        stored_val,
+                        &mut new_inst,
+                    );
+                }
+                _ => new_inst.insts.push(inst),
+            }
+        }
+        context.blocks[block.0].instructions = new_insts;
+    }
+    function.replace_values(context, &replacements, None);
+}
+
+/// The instructions of a block being rebuilt, with the metadata of the instruction they replace.
+struct NewInsts<'a> {
+    insts: &'a mut Vec<Value>,
+    md_idx: Option<MetadataIndex>,
+}
+
+impl NewInsts<'_> {
+    fn push(&mut self, context: &mut Context, inst: Instruction) -> Value {
+        let val = Value::new_instruction(context, inst).add_metadatum(context, self.md_idx);
+        self.insts.push(val);
+        val
+    }
+}
+
+/// Store the value to the field of the split local at the path of indices.  If the field is a
+/// (nested) struct then each of its fields is stored separately.
+fn store_fields(
+    context: &mut Context,
+    split_local: &SplitLocal,
+    path: &[u64],
+    value: Value,
+    new_inst: &mut NewInsts,
+) {
+    if let Some((leaf_local, rest)) = split_local.leaf_locals.lookup(path) {
+        let leaf_ptr = new_inst.push(context, Instruction::GetLocal(leaf_local));
+        if rest.is_empty() {
+            new_inst.push(
+                context,
+                Instruction::Store {
+                    dst_val: leaf_ptr,
+                    stored_val: value,
+                },
+            );
+        } else {
+            new_inst.push(
+                context,
+                Instruction::InsertValue {
+                    aggregate: leaf_ptr,
+                    ty: leaf_local.get_type(context),
+                    value,
+                    indices: rest.to_vec(),
+                },
+            );
+        }
+        return;
+    }
+
+    let struct_ty = if path.is_empty() {
+        split_local.ty
+    } else {
+        split_local.ty.get_indexed_type(context, path).unwrap()
+    };
+    let constant = value.get_constant(context).cloned();
+    for (field_path, field_ty) in get_leaf_fields(context, struct_ty) {
+        let field_val = match constant
+            .as_ref()
+            .and_then(|constant| get_constant_field(constant, &field_path, field_ty))
+        {
+            Some(field_constant) => Value::new_constant(context, field_constant),
+            None => new_inst.push(
+                context,
+                Instruction::ExtractValue {
+                    aggregate: value,
+                    ty: struct_ty,
+                    indices: field_path.clone(),
+                },
+            ),
+        };
+        let path = [path, &field_path].concat();
+        store_fields(context, split_local, &path, field_val, new_inst);
+    }
+}
+
+// Copy types are read from memory by value, all other types are referred to by their address.
+fn is_copy_type(context: &Context, ty: Type) -> bool {
+    ty.is_unit(context) || ty.is_bool(context) || ty.is_uint(context)
+}
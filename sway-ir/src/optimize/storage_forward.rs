@@ -0,0 +1,665 @@
+//! ## Storage Access Forwarding
+//!
+//! Each access to a storage field is compiled to a `state_load_word` or `state_store_word` (or
+//! their quad word equivalents) with a key held in a local, which is set to the constant key for
+//! the field just before the access.  Reading a field more than once, or reading it after writing
+//! it, reads the same storage slot again, which is much more expensive than reusing the value.
+//!
+//! This pass:
+//! - Replaces word loads from a key with the value previously loaded from or stored to the same
+//!   key, as long as nothing in between may have written to the slot.  Writes to unknown keys,
+//!   calls to functions which may write to storage, contract calls (which may reenter the
+//!   contract) and ASM blocks which may write to storage are all assumed to overwrite every slot.
+//! - Merges word loads from, or word stores to, consecutive keys into a single quad word access
+//!   of the whole range of slots, when there is no other access to storage in between.  A word
+//!   is stored to a slot as its first word with the rest zeroed, and read back from the first
+//!   word, so the slots are accessed via a buffer of 4 words per slot.
+//!
+//! The key locals which are no longer used by any access are then removed.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    dominator::compute_post_order, AnalysisResults, Block, Constant, ConstantValue, Context,
+    FuelVmInstruction, Function, Instruction, IrError, LocalVar, MetadataIndex, Pass,
+    PassMutability, ScopedPass, Type, Value, ValueDatum,
+};
+
+pub fn create_storage_forward_pass() -> Pass {
+    Pass {
+        name: "storage_forward",
+        descr: "Forwarding of storage loads and merging of adjacent storage word accesses.",
+        runner: ScopedPass::FunctionPass(PassMutability::Transform(storage_forward)),
+    }
+}
+
+/// Forward the storage word loads of the function and merge its adjacent word accesses, and
+/// return true if the function was modified.
+pub fn storage_forward(
+    context: &mut Context,
+    _: &AnalysisResults,
+    function: Function,
+) -> Result<bool, IrError> {
+    let key_locals = get_key_locals(context, &function);
+    if key_locals.is_empty() {
+        return Ok(false);
+    }
+
+    let mut callee_effects = FxHashMap::default();
+    let mut modified = forward_loads(context, &function, &key_locals, &mut callee_effects);
+    modified |= merge_adjacent_accesses(context, &function, &key_locals, &mut callee_effects);
+    if modified {
+        remove_unused_key_locals(context, &function, &key_locals);
+    }
+    Ok(modified)
+}
+
+/// A storage key, in big-endian order.
+type Key = [u8; 32];
+
+/// The effect an instruction has on storage, with the keys resolved where known.
+enum StorageAccess {
+    /// Loads a word from a known key.
+    LoadWord(Key),
+    /// Stores a word value to a known key.
+    StoreWord(Key, Value),
+    /// Writes a number of slots starting at a known key.
+    WriteSlots(Key, u64),
+    /// Reads from storage, without writing to it.
+    Read,
+    /// May write to any slot.
+    WriteAny,
+    /// Doesn't access storage.
+    None,
+}
+
+/// Whether a function (transitively) reads or writes storage.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Effect {
+    None,
+    Read,
+    Write,
+}
+
+/// Return the locals which are only used to hold constant storage keys, i.e. which are only ever
+/// stored constants to and are otherwise only used as the keys of storage accesses.
+fn get_key_locals(context: &Context, function: &Function) -> FxHashSet<LocalVar> {
+    let mut key_locals: FxHashSet<LocalVar> = function
+        .locals_iter(context)
+        .filter(|(_, local)| {
+            local.get_type(context).is_b256(context) && local.get_initializer(context).is_none()
+        })
+        .map(|(_, local)| *local)
+        .collect();
+
+    for (_, inst) in function.instruction_iter(context) {
+        let instruction = inst.get_instruction(context).unwrap();
+        let keys = match instruction {
+            Instruction::Store {
+                dst_val,
+                stored_val,
+            } if matches!(
+                stored_val.get_constant(context),
+                Some(Constant {
+                    value: ConstantValue::B256(_),
+                    ..
+                })
+            ) =>
+            {
+                vec![*dst_val]
+            }
+            Instruction::FuelVm(FuelVmInstruction::StateClear { key, .. })
+            | Instruction::FuelVm(FuelVmInstruction::StateLoadWord(key))
+            | Instruction::FuelVm(FuelVmInstruction::StateStoreWord { key, .. })
+            | Instruction::FuelVm(FuelVmInstruction::StateLoadQuadWord { key, .. })
+            | Instruction::FuelVm(FuelVmInstruction::StateStoreQuadWord { key, .. }) => {
+                vec![*key]
+            }
+            _ => vec![],
+        };
+        for op in instruction.get_operands() {
+            if keys.contains(&op) {
+                continue;
+            }
+            if let Some(Instruction::GetLocal(local)) = op.get_instruction(context) {
+                key_locals.remove(local);
+            }
+        }
+    }
+    key_locals
+}
+
+fn get_key(
+    context: &Context,
+    key_consts: &FxHashMap<LocalVar, Key>,
+    key_ptr: &Value,
+) -> Option<Key> {
+    match key_ptr.get_instruction(context) {
+        Some(Instruction::GetLocal(local)) => key_consts.get(local).copied(),
+        _ => None,
+    }
+}
+
+fn get_uint_const(context: &Context, val: &Value) -> Option<u64> {
+    match val.get_constant(context) {
+        Some(Constant {
+            value: ConstantValue::Uint(n),
+            ..
+        }) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Track the constants stored to the key locals.  Each access sets its key just before it, in the
+/// same block, so only the keys set in the current block are tracked.
+fn update_key_consts(
+    context: &Context,
+    key_locals: &FxHashSet<LocalVar>,
+    key_consts: &mut FxHashMap<LocalVar, Key>,
+    inst: &Value,
+) {
+    if let Some(Instruction::Store {
+        dst_val,
+        stored_val,
+    }) = inst.get_instruction(context)
+    {
+        if let (
+            Some(Instruction::GetLocal(local)),
+            Some(Constant {
+                value: ConstantValue::B256(key),
+                ..
+            }),
+        ) = (
+            dst_val.get_instruction(context),
+            stored_val.get_constant(context),
+        ) {
+            if key_locals.contains(local) {
+                key_consts.insert(*local, *key);
+            }
+        }
+    }
+}
+
+fn get_storage_access(
+    context: &Context,
+    key_consts: &FxHashMap<LocalVar, Key>,
+    callee_effects: &mut FxHashMap<Function, Effect>,
+    inst: &Value,
+) -> StorageAccess {
+    match inst.get_instruction(context).unwrap() {
+        Instruction::FuelVm(FuelVmInstruction::StateLoadWord(key)) => {
+            match get_key(context, key_consts, key) {
+                Some(key) => StorageAccess::LoadWord(key),
+                None => StorageAccess::Read,
+            }
+        }
+        Instruction::FuelVm(FuelVmInstruction::StateStoreWord { stored_val, key }) => {
+            match get_key(context, key_consts, key) {
+                Some(key) => StorageAccess::StoreWord(key, *stored_val),
+                None => StorageAccess::WriteAny,
+            }
+        }
+        Instruction::FuelVm(FuelVmInstruction::StateClear {
+            key,
+            number_of_slots,
+        })
+        | Instruction::FuelVm(FuelVmInstruction::StateStoreQuadWord {
+            key,
+            number_of_slots,
+            ..
+        }) => match (
+            get_key(context, key_consts, key),
+            get_uint_const(context, number_of_slots),
+        ) {
+            (Some(key), Some(number_of_slots)) => StorageAccess::WriteSlots(key, number_of_slots),
+            _ => StorageAccess::WriteAny,
+        },
+        Instruction::FuelVm(FuelVmInstruction::StateLoadQuadWord { .. }) => StorageAccess::Read,
+        Instruction::ContractCall { .. } => StorageAccess::WriteAny,
+        Instruction::AsmBlock(asm_block, _) => match get_asm_block_effect(context, asm_block) {
+            Effect::None => StorageAccess::None,
+            Effect::Read => StorageAccess::Read,
+            Effect::Write => StorageAccess::WriteAny,
+        },
+        Instruction::Call(callee, _) => match get_fn_effect(context, callee, callee_effects) {
+            Effect::None => StorageAccess::None,
+            Effect::Read => StorageAccess::Read,
+            Effect::Write => StorageAccess::WriteAny,
+        },
+        _ => StorageAccess::None,
+    }
+}
+
+fn get_asm_block_effect(context: &Context, asm_block: &crate::AsmBlock) -> Effect {
+    context.asm_blocks[asm_block.0]
+        .body
+        .iter()
+        .map(|op| match op.name.as_str() {
+            "call" | "scwq" | "sww" | "swwq" => Effect::Write,
+            "srw" | "srwq" => Effect::Read,
+            _ => Effect::None,
+        })
+        .max()
+        .unwrap_or(Effect::None)
+}
+
+/// Return how the function, including the functions it calls, accesses storage.
+fn get_fn_effect(
+    context: &Context,
+    function: &Function,
+    callee_effects: &mut FxHashMap<Function, Effect>,
+) -> Effect {
+    if let Some(effect) = callee_effects.get(function) {
+        return *effect;
+    }
+
+    // Assume the worst for recursive calls.
+    callee_effects.insert(*function, Effect::Write);
+    let no_keys = FxHashMap::default();
+    let effect = function
+        .instruction_iter(context)
+        .map(
+            |(_, inst)| match get_storage_access(context, &no_keys, callee_effects, &inst) {
+                StorageAccess::None => Effect::None,
+                StorageAccess::LoadWord(_) | StorageAccess::Read => Effect::Read,
+                StorageAccess::StoreWord(..)
+                | StorageAccess::WriteSlots(..)
+                | StorageAccess::WriteAny => Effect::Write,
+            },
+        )
+        .max()
+        .unwrap_or(Effect::None);
+    callee_effects.insert(*function, effect);
+    effect
+}
+
+/// Whether `key` is within the `number_of_slots` slots starting at `first_key`.
+fn is_key_in_range(key: &Key, first_key: &Key, number_of_slots: u64) -> bool {
+    key >= first_key && {
+        // Subtract the keys, which is fine to do a byte at a time as key >= first_key.
+        let mut diff = [0u8; 32];
+        let mut borrow = 0;
+        for idx in (0..32).rev() {
+            let (byte, borrow_0) = key[idx].overflowing_sub(first_key[idx]);
+            let (byte, borrow_1) = byte.overflowing_sub(borrow);
+            diff[idx] = byte;
+            borrow = (borrow_0 || borrow_1) as u8;
+        }
+        diff[..24].iter().all(|byte| *byte == 0)
+            && u64::from_be_bytes(diff[24..].try_into().unwrap()) < number_of_slots
+    }
+}
+
+/// Return the key following `key`, or `None` if it is the last key.
+fn next_key(key: &Key) -> Option<Key> {
+    let mut next = *key;
+    for byte in next.iter_mut().rev() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            return Some(next);
+        }
+    }
+    None
+}
+
+/// Replace the word loads from keys with known values.  The values known at the start of a block
+/// are those known at the end of all of its predecessors, which are visited first in reverse post
+/// order.  The values known at the end of predecessors via back edges aren't known yet, so loops
+/// start with nothing known.
+fn forward_loads(
+    context: &mut Context,
+    function: &Function,
+    key_locals: &FxHashSet<LocalVar>,
+    callee_effects: &mut FxHashMap<Function, Effect>,
+) -> bool {
+    let post_order = compute_post_order(context, function);
+    let entry_block = function.get_entry_block(context);
+
+    let mut known_at_end: FxHashMap<Block, FxHashMap<Key, Value>> = FxHashMap::default();
+    let mut replacements = FxHashMap::default();
+    let mut forwarded_loads = Vec::new();
+    for block in post_order.po_to_block.iter().rev() {
+        let mut preds = block.pred_iter(context);
+        let mut known = match preds.next().and_then(|pred| known_at_end.get(pred)) {
+            Some(pred_known) if *block != entry_block => pred_known.clone(),
+            _ => FxHashMap::default(),
+        };
+        for pred in preds {
+            match known_at_end.get(pred) {
+                Some(pred_known) => known.retain(|key, val| pred_known.get(key) == Some(val)),
+                None => known.clear(),
+            }
+        }
+
+        let mut key_consts = FxHashMap::default();
+        for inst in block.instruction_iter(context) {
+            update_key_consts(context, key_locals, &mut key_consts, &inst);
+            match get_storage_access(context, &key_consts, callee_effects, &inst) {
+                StorageAccess::LoadWord(key) => match known.get(&key) {
+                    Some(val) => {
+                        replacements.insert(inst, *val);
+                        forwarded_loads.push((*block, inst));
+                    }
+                    None => {
+                        known.insert(key, inst);
+                    }
+                },
+                StorageAccess::StoreWord(key, val) => {
+                    known.insert(key, replacements.get(&val).copied().unwrap_or(val));
+                }
+                StorageAccess::WriteSlots(first_key, number_of_slots) => {
+                    known.retain(|key, _| !is_key_in_range(key, &first_key, number_of_slots));
+                }
+                StorageAccess::WriteAny => known.clear(),
+                StorageAccess::Read | StorageAccess::None => (),
+            }
+        }
+        known_at_end.insert(*block, known);
+    }
+
+    function.replace_values(context, &replacements, None);
+    for (block, load) in &forwarded_loads {
+        block.remove_instruction(context, *load);
+    }
+    !forwarded_loads.is_empty()
+}
+
+/// A run of word loads or of word stores which can all be moved to the same place.
+#[derive(Default)]
+struct AccessRun {
+    is_store: bool,
+    accesses: Vec<(Value, Key)>,
+}
+
+/// Merge the word loads from consecutive keys, and the word stores to consecutive keys, into
+/// quad word accesses.
+fn merge_adjacent_accesses(
+    context: &mut Context,
+    function: &Function,
+    key_locals: &FxHashSet<LocalVar>,
+    callee_effects: &mut FxHashMap<Function, Effect>,
+) -> bool {
+    // Find the runs of accesses of the same kind in each block, with no other storage accesses
+    // in between.  Whether a store's result, the 'slot was set' flag, is used is not tracked per
+    // slot by the quad word store, so those are left alone.
+    let used_values: FxHashSet<Value> = function
+        .instruction_iter(context)
+        .flat_map(|(_, inst)| inst.get_instruction(context).unwrap().get_operands())
+        .collect();
+    let mut runs = Vec::new();
+    for block in function.block_iter(context) {
+        let mut key_consts = FxHashMap::default();
+        let mut run = AccessRun::default();
+        for inst in block.instruction_iter(context) {
+            update_key_consts(context, key_locals, &mut key_consts, &inst);
+            let (is_store, key) =
+                match get_storage_access(context, &key_consts, callee_effects, &inst) {
+                    StorageAccess::None => continue,
+                    StorageAccess::LoadWord(key) => (false, key),
+                    StorageAccess::StoreWord(key, _) if !used_values.contains(&inst) => (true, key),
+                    _ => {
+                        runs.push((block, std::mem::take(&mut run)));
+                        continue;
+                    }
+                };
+            if run.is_store != is_store {
+                runs.push((block, std::mem::take(&mut run)));
+                run.is_store = is_store;
+            }
+            run.accesses.push((inst, key));
+        }
+        runs.push((block, run));
+    }
+
+    let mut modified = false;
+    for (block, run) in runs {
+        for group in get_consecutive_key_groups(run.accesses) {
+            if run.is_store {
+                merge_stores(context, function, &block, &group);
+            } else {
+                merge_loads(context, function, &block, &group);
+            }
+            modified = true;
+        }
+    }
+    modified
+}
+
+/// Group the accesses in the run by consecutive keys, sorted by key.  Keys accessed more than once
+/// aren't grouped, as the order of the accesses to them matters.
+fn get_consecutive_key_groups(mut accesses: Vec<(Value, Key)>) -> Vec<Vec<(Value, Key)>> {
+    let mut key_counts: FxHashMap<Key, usize> = FxHashMap::default();
+    for (_, key) in &accesses {
+        *key_counts.entry(*key).or_default() += 1;
+    }
+    accesses.retain(|(_, key)| key_counts[key] == 1);
+    accesses.sort_by_key(|(_, key)| *key);
+
+    let mut groups: Vec<Vec<(Value, Key)>> = Vec::new();
+    for access in accesses {
+        match groups.last_mut() {
+            Some(group) if next_key(&group.last().unwrap().1) == Some(access.1) => {
+                group.push(access)
+            }
+            _ => groups.push(vec![access]),
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// The instructions accessing a range of slots with a quad word access, via a buffer of 4 words
+/// per slot.
+struct QuadAccess {
+    insts: Vec<Value>,
+    buf_local: LocalVar,
+    buf_ty: Type,
+}
+
+impl QuadAccess {
+    fn new(context: &mut Context, function: &Function, number_of_slots: usize) -> Self {
+        let u64_ty = Type::get_uint64(context);
+        let buf_ty = Type::new_struct(context, vec![u64_ty; number_of_slots * 4]);
+        let buf_local =
+            function.new_unique_local_var(context, "val_for_slots".to_owned(), buf_ty, None);
+        QuadAccess {
+            insts: Vec::new(),
+            buf_local,
+            buf_ty,
+        }
+    }
+
+    fn push(
+        &mut self,
+        context: &mut Context,
+        inst: Instruction,
+        md_idx: Option<MetadataIndex>,
+    ) -> Value {
+        let val = Value::new_instruction(context, inst).add_metadatum(context, md_idx);
+        self.insts.push(val);
+        val
+    }
+
+    /// Add the access itself, from the first key.
+    fn push_access(
+        &mut self,
+        context: &mut Context,
+        function: &Function,
+        first_key: Key,
+        number_of_slots: usize,
+        is_store: bool,
+        md_idx: Option<MetadataIndex>,
+    ) {
+        let b256_ty = Type::get_b256(context);
+        let key_local =
+            function.new_unique_local_var(context, "key_for_slots".to_owned(), b256_ty, None);
+        let key_ptr = self.push(context, Instruction::GetLocal(key_local), md_idx);
+        let key_val = Constant::get_b256(context, first_key);
+        self.push(
+            context,
+            Instruction::Store {
+                dst_val: key_ptr,
+                stored_val: key_val,
+            },
+            md_idx,
+        );
+
+        let buf_ptr = self.push(context, Instruction::GetLocal(self.buf_local), md_idx);
+        let slots_ptr = self.push(context, Instruction::CastPtr(buf_ptr, b256_ty, 0), md_idx);
+        let number_of_slots = Constant::get_uint(context, 64, number_of_slots as u64);
+        let access = match is_store {
+            true => FuelVmInstruction::StateStoreQuadWord {
+                stored_val: slots_ptr,
+                key: key_ptr,
+                number_of_slots,
+            },
+            false => FuelVmInstruction::StateLoadQuadWord {
+                load_val: slots_ptr,
+                key: key_ptr,
+                number_of_slots,
+            },
+        };
+        self.push(context, Instruction::FuelVm(access), md_idx);
+    }
+
+    /// Insert the instructions into the block before `before`.
+    fn insert(self, context: &mut Context, block: &Block, before: Value) {
+        let insts = &mut context.blocks[block.0].instructions;
+        let idx = insts.iter().position(|inst| *inst == before).unwrap();
+        insts.splice(idx..idx, self.insts);
+    }
+}
+
+/// Load the group of words at once, before the first of them, and read each of them from the
+/// buffer instead.
+fn merge_loads(context: &mut Context, function: &Function, block: &Block, group: &[(Value, Key)]) {
+    let first_load = *context.blocks[block.0]
+        .instructions
+        .iter()
+        .find(|inst| group.iter().any(|(load, _)| load == *inst))
+        .unwrap();
+    let md_idx = first_load.get_metadata(context);
+
+    let mut quad = QuadAccess::new(context, function, group.len());
+    quad.push_access(context, function, group[0].1, group.len(), false, md_idx);
+    let mut replacements = FxHashMap::default();
+    for (slot_idx, (load, _)) in group.iter().enumerate() {
+        let buf_ptr = quad.push(context, Instruction::GetLocal(quad.buf_local), md_idx);
+        let word = quad.push(
+            context,
+            Instruction::ExtractValue {
+                aggregate: buf_ptr,
+                ty: quad.buf_ty,
+                indices: vec![slot_idx as u64 * 4],
+            },
+            md_idx,
+        );
+        replacements.insert(*load, word);
+    }
+    quad.insert(context, block, first_load);
+
+    function.replace_values(context, &replacements, None);
+    for (load, _) in group {
+        block.remove_instruction(context, *load);
+    }
+}
+
+/// Write each of the group of words to the buffer, padded with zeros like a word store does, and
+/// store them at once in place of the last of them.
+fn merge_stores(context: &mut Context, function: &Function, block: &Block, group: &[(Value, Key)]) {
+    let last_store = *context.blocks[block.0]
+        .instructions
+        .iter()
+        .rev()
+        .find(|inst| group.iter().any(|(store, _)| store == *inst))
+        .unwrap();
+    let md_idx = last_store.get_metadata(context);
+
+    let mut quad = QuadAccess::new(context, function, group.len());
+    let zero = Constant::get_uint(context, 64, 0);
+    let mut buf_val = quad.push(context, Instruction::GetLocal(quad.buf_local), md_idx);
+    for (slot_idx, (store, _)) in group.iter().enumerate() {
+        let stored_val = match &context.values[store.0].value {
+            ValueDatum::Instruction(Instruction::FuelVm(FuelVmInstruction::StateStoreWord {
+                stored_val,
+                ..
+            })) => *stored_val,
+            _ => unreachable!("Merged stores are word stores."),
+        };
+        for word_idx in 0..4 {
+            buf_val = quad.push(
+                context,
+                Instruction::InsertValue {
+                    aggregate: buf_val,
+                    ty: quad.buf_ty,
+                    value: if word_idx == 0 { stored_val } else { zero },
+                    indices: vec![slot_idx as u64 * 4 + word_idx],
+                },
+                md_idx,
+            );
+        }
+    }
+    quad.push_access(context, function, group[0].1, group.len(), true, md_idx);
+    quad.insert(context, block, last_store);
+
+    for (store, _) in group {
+        block.remove_instruction(context, *store);
+    }
+}
+
+/// Remove the key locals which aren't used by any access any more, along with the stores to
+/// them.
+fn remove_unused_key_locals(
+    context: &mut Context,
+    function: &Function,
+    key_locals: &FxHashSet<LocalVar>,
+) {
+    let mut used_key_locals = FxHashSet::default();
+    for (_, inst) in function.instruction_iter(context) {
+        let instruction = inst.get_instruction(context).unwrap();
+        if matches!(instruction, Instruction::Store { .. }) {
+            continue;
+        }
+        for op in instruction.get_operands() {
+            if let Some(Instruction::GetLocal(local)) = op.get_instruction(context) {
+                used_key_locals.insert(*local);
+            }
+        }
+    }
+    let unused_key_locals = key_locals
+        .difference(&used_key_locals)
+        .copied()
+        .collect::<FxHashSet<_>>();
+    if unused_key_locals.is_empty() {
+        return;
+    }
+
+    let is_unused_key_ptr = |context: &Context, val: &Value| {
+        matches!(
+            val.get_instruction(context),
+            Some(Instruction::GetLocal(local)) if unused_key_locals.contains(local)
+        )
+    };
+    let blocks = function.block_iter(context).collect::<Vec<_>>();
+    for block in blocks {
+        let insts = context.blocks[block.0]
+            .instructions
+            .iter()
+            .copied()
+            .filter(|inst| {
+                !is_unused_key_ptr(context, inst)
+                    && !matches!(
+                        inst.get_instruction(context),
+                        Some(Instruction::Store { dst_val, .. }) if is_unused_key_ptr(context, dst_val)
+                    )
+            })
+            .collect();
+        context.blocks[block.0].instructions = insts;
+    }
+    for local in unused_key_locals {
+        if let Some(name) = function.lookup_local_name(context, &local).cloned() {
+            context.functions[function.0].local_storage.remove(&name);
+        }
+    }
+}
@@ -159,6 +159,12 @@ mod ir_builder {
                 / "sub" _ { BinaryOpKind::Sub }
                 / "mul" _ { BinaryOpKind::Mul }
                 / "div" _ { BinaryOpKind::Div }
+                / "and" _ { BinaryOpKind::And }
+                / "or" _ { BinaryOpKind::Or }
+                / "xor" _ { BinaryOpKind::Xor }
+                / "mod" _ { BinaryOpKind::Mod }
+                / "lsh" _ { BinaryOpKind::Lsh }
+                / "rsh" _ { BinaryOpKind::Rsh }
 
             rule operation() -> IrAstOperation
                 = op_addr_of()
@@ -188,6 +194,7 @@ mod ir_builder {
                 / op_ret()
                 / op_revert()
                 / op_smo()
+                / op_state_clear()
                 / op_state_load_quad_word()
                 / op_state_load_word()
                 / op_state_store_quad_word()
@@ -373,7 +380,7 @@ mod ir_builder {
                 }
 
             rule cmp_pred() -> String
-                = p:$("eq") _ {
+                = p:$("eq" / "lt" / "gt") _ {
                     p.to_string()
                 }
 
@@ -1121,6 +1128,8 @@ mod ir_builder {
                         .cmp(
                             match pred_str.as_str() {
                                 "eq" => Predicate::Equal,
+                                "lt" => Predicate::LessThan,
+                                "gt" => Predicate::GreaterThan,
                                 _ => unreachable!("Bug in `cmp` predicate rule."),
                             },
                             *val_map.get(&lhs).unwrap(),
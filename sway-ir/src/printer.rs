@@ -382,6 +382,12 @@ fn instruction_to_doc<'a>(
                     BinaryOpKind::Sub => "sub",
                     BinaryOpKind::Mul => "mul",
                     BinaryOpKind::Div => "div",
+                    BinaryOpKind::And => "and",
+                    BinaryOpKind::Or => "or",
+                    BinaryOpKind::Xor => "xor",
+                    BinaryOpKind::Mod => "mod",
+                    BinaryOpKind::Lsh => "lsh",
+                    BinaryOpKind::Rsh => "rsh",
                 };
                 maybe_constant_to_doc(context, md_namer, namer, arg1)
                     .append(maybe_constant_to_doc(context, md_namer, namer, arg2))
@@ -448,6 +454,8 @@ fn instruction_to_doc<'a>(
             Instruction::Cmp(pred, lhs_value, rhs_value) => {
                 let pred_str = match pred {
                     Predicate::Equal => "eq",
+                    Predicate::LessThan => "lt",
+                    Predicate::GreaterThan => "gt",
                 };
                 maybe_constant_to_doc(context, md_namer, namer, lhs_value)
                     .append(maybe_constant_to_doc(context, md_namer, namer, rhs_value))
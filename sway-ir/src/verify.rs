@@ -288,7 +288,7 @@ impl<'a> InstructionVerifier<'a> {
 
     fn verify_binary_op(
         &self,
-        _op: &BinaryOpKind,
+        op: &BinaryOpKind,
         arg1: &Value,
         arg2: &Value,
     ) -> Result<(), IrError> {
@@ -298,7 +298,12 @@ impl<'a> InstructionVerifier<'a> {
         let arg2_ty = arg2
             .get_type(self.context)
             .ok_or(IrError::VerifyBinaryOpIncorrectArgType)?;
-        if !arg1_ty.eq(self.context, &arg2_ty) || !arg1_ty.is_uint(self.context) {
+        if !arg1_ty.is_uint(self.context) || !arg2_ty.is_uint(self.context) {
+            return Err(IrError::VerifyBinaryOpIncorrectArgType);
+        }
+        // The shift amount of a shift may be of a different width to the shifted value.
+        let is_shift = matches!(op, BinaryOpKind::Lsh | BinaryOpKind::Rsh);
+        if !is_shift && !arg1_ty.eq(self.context, &arg2_ty) {
             return Err(IrError::VerifyBinaryOpIncorrectArgType);
         }
 
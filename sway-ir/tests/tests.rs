@@ -1,8 +1,10 @@
 use std::path::PathBuf;
 
 use sway_ir::{
-    create_const_combine_pass, create_dce_pass, create_mem2reg_pass, create_simplify_cfg_pass,
-    optimize as opt, Context, PassManager, PassManagerConfig,
+    create_const_combine_pass, create_cse_pass, create_dce_pass, create_fn_dedup_pass,
+    create_licm_pass, create_loop_unroll_pass, create_mem2reg_pass, create_ret_demotion_pass,
+    create_simplify_cfg_pass, create_sroa_pass, create_storage_forward_pass, optimize as opt,
+    Context, PassManager, PassManagerConfig,
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -130,6 +132,45 @@ fn constants() {
 
 // -------------------------------------------------------------------------------------------------
 
+#[test]
+fn cse() {
+    run_tests("cse", |_first_line, ir: &mut Context| {
+        let mut pass_mgr = PassManager::default();
+        let mut pmgr_config = PassManagerConfig { to_run: vec![] };
+        let pass = pass_mgr.register(create_cse_pass());
+        pmgr_config.to_run.push(pass.to_string());
+        pass_mgr.run(ir, &pmgr_config).unwrap()
+    })
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn licm() {
+    run_tests("licm", |_first_line, ir: &mut Context| {
+        let mut pass_mgr = PassManager::default();
+        let mut pmgr_config = PassManagerConfig { to_run: vec![] };
+        let pass = pass_mgr.register(create_licm_pass());
+        pmgr_config.to_run.push(pass.to_string());
+        pass_mgr.run(ir, &pmgr_config).unwrap()
+    })
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn loop_unroll() {
+    run_tests("loop_unroll", |_first_line, ir: &mut Context| {
+        let mut pass_mgr = PassManager::default();
+        let mut pmgr_config = PassManagerConfig { to_run: vec![] };
+        let pass = pass_mgr.register(create_loop_unroll_pass());
+        pmgr_config.to_run.push(pass.to_string());
+        pass_mgr.run(ir, &pmgr_config).unwrap()
+    })
+}
+
+// -------------------------------------------------------------------------------------------------
+
 #[allow(clippy::needless_collect)]
 #[test]
 fn simplify_cfg() {
@@ -172,6 +213,58 @@ fn mem2reg() {
 
 // -------------------------------------------------------------------------------------------------
 
+#[test]
+fn sroa() {
+    run_tests("sroa", |_first_line, ir: &mut Context| {
+        let mut pass_mgr = PassManager::default();
+        let mut pmgr_config = PassManagerConfig { to_run: vec![] };
+        let pass = pass_mgr.register(create_sroa_pass());
+        pmgr_config.to_run.push(pass.to_string());
+        pass_mgr.run(ir, &pmgr_config).unwrap()
+    })
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn fn_dedup() {
+    run_tests("fn_dedup", |_first_line, ir: &mut Context| {
+        let mut pass_mgr = PassManager::default();
+        let mut pmgr_config = PassManagerConfig { to_run: vec![] };
+        let pass = pass_mgr.register(create_fn_dedup_pass());
+        pmgr_config.to_run.push(pass.to_string());
+        pass_mgr.run(ir, &pmgr_config).unwrap()
+    })
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn ret_demotion() {
+    run_tests("ret_demotion", |_first_line, ir: &mut Context| {
+        let mut pass_mgr = PassManager::default();
+        let mut pmgr_config = PassManagerConfig { to_run: vec![] };
+        let pass = pass_mgr.register(create_ret_demotion_pass());
+        pmgr_config.to_run.push(pass.to_string());
+        pass_mgr.run(ir, &pmgr_config).unwrap()
+    })
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn storage_forward() {
+    run_tests("storage_forward", |_first_line, ir: &mut Context| {
+        let mut pass_mgr = PassManager::default();
+        let mut pmgr_config = PassManagerConfig { to_run: vec![] };
+        let pass = pass_mgr.register(create_storage_forward_pass());
+        pmgr_config.to_run.push(pass.to_string());
+        pass_mgr.run(ir, &pmgr_config).unwrap()
+    })
+}
+
+// -------------------------------------------------------------------------------------------------
+
 #[test]
 fn serialize() {
     // This isn't running a pass, it's just confirming that the IR can be loaded and printed, and
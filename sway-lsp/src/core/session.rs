@@ -405,7 +405,10 @@ impl Session {
     fn create_runnables(&self, typed_program: &ty::TyProgram) {
         // Insert runnable test functions.
         let decl_engine = &*self.decl_engine.read();
-        for (decl, _) in typed_program.test_fns(decl_engine) {
+        for (decl, _) in typed_program
+            .test_fns(decl_engine)
+            .filter(|(decl, _)| decl.is_test())
+        {
             // Get the span of the first attribute if it exists, otherwise use the span of the function name.
             let span = decl
                 .attributes
@@ -167,7 +167,7 @@ impl Parse for FnSignature {
 mod tests {
     use super::*;
     use std::sync::Arc;
-    use sway_ast::{AttributeDecl, Item};
+    use sway_ast::{AttributeDecl, Item, Literal};
 
     fn parse_item(input: &str) -> Item {
         let handler = <_>::default();
@@ -191,9 +191,9 @@ mod tests {
                     .map(|att| {
                         (
                             att.name.as_str(),
-                            att.args
-                                .as_ref()
-                                .map(|arg| arg.get().into_iter().map(|a| a.as_str()).collect()),
+                            att.args.as_ref().map(|arg| {
+                                arg.get().into_iter().map(|a| a.name.as_str()).collect()
+                            }),
                         )
                     })
                     .collect()
@@ -290,6 +290,36 @@ mod tests {
         assert_eq!(attributes(&item.attribute_list), vec![[("foo", None)]]);
     }
 
+    #[test]
+    fn parse_attributes_fn_arg_with_value() {
+        let item = parse_item(
+            r#"
+            #[foo(bar = "baz", qux = 42, quux)]
+            fn f() -> bool {
+                false
+            }
+            "#,
+        );
+
+        assert!(matches!(item.value, ItemKind::Fn(_)));
+        assert_eq!(
+            attributes(&item.attribute_list),
+            vec![[("foo", Some(vec!["bar", "qux", "quux"]))]]
+        );
+
+        let args = item.attribute_list[0]
+            .attribute
+            .get()
+            .into_iter()
+            .next()
+            .and_then(|attr| attr.args.as_ref())
+            .map(|args| args.get().into_iter().collect::<Vec<_>>())
+            .unwrap();
+        assert!(matches!(&args[0].value, Some(Literal::String(s)) if s.parsed == "baz"));
+        assert!(matches!(&args[1].value, Some(Literal::Int(i)) if i.parsed == 42u32.into()));
+        assert!(args[2].value.is_none());
+    }
+
     #[test]
     fn parse_attributes_fn_two_basic() {
         let item = parse_item(
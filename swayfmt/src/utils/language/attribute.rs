@@ -7,7 +7,7 @@ use crate::{
 };
 use std::fmt::Write;
 use sway_ast::{
-    attribute::{Annotated, Attribute, AttributeDecl},
+    attribute::{Annotated, Attribute, AttributeArg, AttributeDecl},
     token::{Delimiter, PunctKind},
 };
 use sway_types::{constants::DOC_COMMENT_ATTRIBUTE_NAME, Spanned};
@@ -53,7 +53,11 @@ impl Format for AttributeDecl {
                 .as_ref()
                 .map(|args| args.inner.final_value_opt.as_ref())
             {
-                writeln!(formatted_code, "///{}", doc_comment.as_str().trim_end())?;
+                writeln!(
+                    formatted_code,
+                    "///{}",
+                    doc_comment.name.as_str().trim_end()
+                )?;
             }
             return Ok(());
         }
@@ -92,6 +96,24 @@ impl Format for AttributeDecl {
     }
 }
 
+impl Format for AttributeArg {
+    fn format(
+        &self,
+        formatted_code: &mut FormattedCode,
+        formatter: &mut Formatter,
+    ) -> Result<(), FormatterError> {
+        // name e.g. `should_revert`
+        self.name.format(formatted_code, formatter)?;
+        // value e.g. ` = "18446744073709486084"`
+        if let Some(value) = &self.value {
+            write!(formatted_code, " {} ", PunctKind::Equals.as_char())?;
+            value.format(formatted_code, formatter)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl SquareBracket for AttributeDecl {
     fn open_square_bracket(
         line: &mut String,
@@ -141,3 +163,13 @@ impl LeafSpans for Attribute {
         collected_spans
     }
 }
+
+impl LeafSpans for AttributeArg {
+    fn leaf_spans(&self) -> Vec<ByteSpan> {
+        let mut collected_spans = vec![ByteSpan::from(self.name.span())];
+        if let Some(value) = &self.value {
+            collected_spans.append(&mut value.leaf_spans());
+        }
+        collected_spans
+    }
+}
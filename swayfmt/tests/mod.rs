@@ -1202,6 +1202,28 @@ fn foo() {}
     );
 }
 
+#[test]
+fn attribute_args_with_values() {
+    check(
+        r#"library my_lib;
+
+#[test(should_revert="18446744073709486084")]
+fn foo() {}
+
+#[test(should_revert   =   42)]
+fn bar() {}
+"#,
+        r#"library my_lib;
+
+#[test(should_revert = "18446744073709486084")]
+fn foo() {}
+
+#[test(should_revert = 42)]
+fn bar() {}
+"#,
+    );
+}
+
 #[test]
 fn comment_between_closing_brace_and_else() {
     check(
@@ -247,7 +247,13 @@ pub(crate) async fn compile_and_run_unit_tests(
             },
             ..Default::default()
         })?;
-        let tested = built_tests.run()?;
+        let tested = built_tests.run(
+            forc_test::TestRunnerCount::Auto,
+            None,
+            forc_test::IgnoredTests::Skip,
+            forc_test::fuzz::FuzzOpts::default(),
+            None,
+        )?;
 
         match tested {
             forc_test::Tested::Package(tested_pkg) => Ok(vec![*tested_pkg]),
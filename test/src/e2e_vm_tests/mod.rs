@@ -34,6 +34,7 @@ enum TestCategory {
     Runs,
     RunsWithContract,
     UnitTestsPass,
+    UnitTestsFail,
     Disabled,
 }
 
@@ -146,6 +147,9 @@ impl TestContext {
                                 TestResult::ReturnData(data)
                             }
                             ProgramState::Revert(v) => TestResult::Revert(v),
+                            ProgramState::RunProgram(_) | ProgramState::VerifyPredicate(_) => {
+                                unreachable!("programs are not executed in debug mode")
+                            }
                         }
                     }
                     harness::VMExecutionResult::Evm(state) => match state.exit_reason {
@@ -319,6 +323,22 @@ impl TestContext {
                 Ok(())
             }
 
+            TestCategory::UnitTestsFail => {
+                let (result, out) =
+                    harness::compile_and_run_unit_tests(&name, &context.run_config, true).await;
+                *output = out;
+
+                match result {
+                    Ok(_) => Err(anyhow::Error::msg(
+                        "Unit tests run but are expected to fail",
+                    )),
+                    Err(e) => {
+                        output.push_str(&format!("{e}\n"));
+                        check_file_checker(checker, &name, output)
+                    }
+                }
+            }
+
             category => Err(anyhow::Error::msg(format!(
                 "Unexpected test category: {category:?}",
             ))),
@@ -560,6 +580,7 @@ fn parse_test_toml(path: &Path) -> Result<TestDescription> {
             Some("compile") => Ok(TestCategory::Compiles),
             Some("disabled") => Ok(TestCategory::Disabled),
             Some("unit_tests_pass") => Ok(TestCategory::UnitTestsPass),
+            Some("unit_tests_fail") => Ok(TestCategory::UnitTestsFail),
             None => Err(anyhow!(
                 "Malformed category '{category_val}', should be a string."
             )),
@@ -570,6 +591,9 @@ fn parse_test_toml(path: &Path) -> Result<TestDescription> {
     if category == TestCategory::FailsToCompile && checker.is_empty() {
         bail!("'fail' tests must contain some FileCheck verification directives.");
     }
+    if category == TestCategory::UnitTestsFail && checker.is_empty() {
+        bail!("'unit_tests_fail' tests must contain some FileCheck verification directives.");
+    }
 
     let script_data = match &category {
         TestCategory::Runs | TestCategory::RunsWithContract => {
@@ -588,6 +612,7 @@ fn parse_test_toml(path: &Path) -> Result<TestDescription> {
         TestCategory::Compiles
         | TestCategory::FailsToCompile
         | TestCategory::UnitTestsPass
+        | TestCategory::UnitTestsFail
         | TestCategory::Disabled => None,
     };
 
@@ -598,6 +623,7 @@ fn parse_test_toml(path: &Path) -> Result<TestDescription> {
         TestCategory::Compiles
         | TestCategory::FailsToCompile
         | TestCategory::UnitTestsPass
+        | TestCategory::UnitTestsFail
         | TestCategory::Disabled => None,
     };
 
@@ -0,0 +1,6 @@
+script;
+
+fn main() {}
+
+#[test]
+fn test_with_invalid_env() {}
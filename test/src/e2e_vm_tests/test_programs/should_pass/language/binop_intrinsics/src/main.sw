@@ -30,6 +30,49 @@ fn main() -> u64 {
   assert(__mul(a, b) == 968);
   assert(__div(b, a) == 2);
 
+  let a: u8 = 22;
+  let b: u8 = 7;
+  assert(__mod(a, b) == 1);
+  assert(__and(a, b) == 6);
+  assert(__or(a, b) == 23);
+  assert(__xor(a, b) == 17);
+  assert(__lsh(b, 2) == 28);
+  assert(__rsh(a, 2) == 5);
+  assert(__gt(a, b));
+  assert(!__lt(a, b));
+
+  let a: u16 = 300;
+  let b: u16 = 7;
+  assert(__mod(a, b) == 6);
+  assert(__and(a, b) == 4);
+  assert(__or(a, b) == 303);
+  assert(__xor(a, b) == 299);
+  assert(__lsh(b, 3) == 56);
+  assert(__rsh(a, 3) == 37);
+  assert(__gt(a, b));
+  assert(__lt(b, a));
+
+  let a: u32 = 70000;
+  let b: u32 = 15;
+  assert(__mod(a, b) == 10);
+  assert(__and(a, b) == 0);
+  assert(__or(a, b) == 70015);
+  assert(__xor(a, b) == 70015);
+  assert(__lsh(b, 4) == 240);
+  assert(__rsh(a, 4) == 4375);
+  assert(!__gt(b, a));
+  assert(__lt(b, a));
+
+  let a: u64 = 0xff00;
+  let b: u64 = 0x0ff0;
+  assert(__mod(a, 0x1000) == 0x0f00);
+  assert(__and(a, b) == 0x0f00);
+  assert(__or(a, b) == 0xfff0);
+  assert(__xor(a, b) == 0xf0f0);
+  assert(__lsh(a, 8) == 0xff0000);
+  assert(__rsh(a, 8) == 0xff);
+  assert(__gt(a, b));
+  assert(!__lt(a, a));
 
   2
 }
@@ -0,0 +1,30 @@
+library logging_library;
+
+struct TestStruct {
+    field_1: bool,
+    field_2: b256,
+    field_3: u64,
+}
+
+enum TestEnum {
+    VariantOne: (),
+    VariantTwo: u32,
+}
+
+pub fn log_values() {
+    let k: b256 = 0xef86afa9696cf0dc6385e2c407a6e159a1103cefb7e2ae0636fb33d3cb2a9e4a;
+    __log(k);
+    __log(42);
+    __log("Fuel");
+    __log(TestStruct {
+        field_1: true,
+        field_2: k,
+        field_3: 11,
+    });
+    __log(TestEnum::VariantTwo(7));
+}
+
+#[test]
+fn test_log_values() {
+    log_values();
+}
@@ -0,0 +1,62 @@
+script;
+
+// None of these functions are inlined, so their ref type args and return values are all passed
+// by reference through the calling convention.
+
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+#[inline(never)]
+fn sum(p: Point) -> u64 {
+    p.x + p.y
+}
+
+#[inline(never)]
+fn scale(ref mut p: Point, factor: u64) {
+    p.x = p.x * factor;
+    p.y = p.y * factor;
+}
+
+#[inline(never)]
+fn swap(p: Point) -> Point {
+    Point {
+        x: p.y,
+        y: p.x,
+    }
+}
+
+#[inline(never)]
+fn pick(a: Point, b: Point, first: bool) -> Point {
+    if first { a } else { b }
+}
+
+#[inline(never)]
+fn first_word(words: [u64; 3]) -> u64 {
+    words[0]
+}
+
+#[inline(never)]
+fn make_pair(x: u64) -> (u64, Point) {
+    (x, Point {
+        x: x + 1,
+        y: x + 2,
+    })
+}
+
+fn main() -> u64 {
+    // p = { 3, 6 }, q = { 6, 3 } and r = q.
+    let mut p = Point { x: 1, y: 2 };
+    scale(p, 3);
+    let q = swap(p);
+    let r = pick(p, q, false);
+
+    // The returned value is written over the arg it is built from.
+    let mut t = Point { x: 1, y: 2 };
+    t = swap(t);
+
+    let pair = make_pair(10);
+
+    sum(p) + sum(q) * 10 + sum(r) * 100 + first_word([7, 8, 9]) * 1000 + pair.0 * 10000 + sum(pair.1) * 100000 + (t.x * 10 + t.y) * 10000000
+}
@@ -0,0 +1,379 @@
+script;
+
+// Each of these functions keeps more values live at once than there are registers, so some
+// must be spilled to the stack.
+
+fn sum_in_loop(x: u64) -> u64 {
+    let a1 = x + asm(r: 1) { r: u64 };
+    let a2 = x + asm(r: 2) { r: u64 };
+    let a3 = x + asm(r: 3) { r: u64 };
+    let a4 = x + asm(r: 4) { r: u64 };
+    let a5 = x + asm(r: 5) { r: u64 };
+    let a6 = x + asm(r: 6) { r: u64 };
+    let a7 = x + asm(r: 7) { r: u64 };
+    let a8 = x + asm(r: 8) { r: u64 };
+    let a9 = x + asm(r: 9) { r: u64 };
+    let a10 = x + asm(r: 10) { r: u64 };
+    let a11 = x + asm(r: 11) { r: u64 };
+    let a12 = x + asm(r: 12) { r: u64 };
+    let a13 = x + asm(r: 13) { r: u64 };
+    let a14 = x + asm(r: 14) { r: u64 };
+    let a15 = x + asm(r: 15) { r: u64 };
+    let a16 = x + asm(r: 16) { r: u64 };
+    let a17 = x + asm(r: 17) { r: u64 };
+    let a18 = x + asm(r: 18) { r: u64 };
+    let a19 = x + asm(r: 19) { r: u64 };
+    let a20 = x + asm(r: 20) { r: u64 };
+    let a21 = x + asm(r: 21) { r: u64 };
+    let a22 = x + asm(r: 22) { r: u64 };
+    let a23 = x + asm(r: 23) { r: u64 };
+    let a24 = x + asm(r: 24) { r: u64 };
+    let a25 = x + asm(r: 25) { r: u64 };
+    let a26 = x + asm(r: 26) { r: u64 };
+    let a27 = x + asm(r: 27) { r: u64 };
+    let a28 = x + asm(r: 28) { r: u64 };
+    let a29 = x + asm(r: 29) { r: u64 };
+    let a30 = x + asm(r: 30) { r: u64 };
+    let a31 = x + asm(r: 31) { r: u64 };
+    let a32 = x + asm(r: 32) { r: u64 };
+    let a33 = x + asm(r: 33) { r: u64 };
+    let a34 = x + asm(r: 34) { r: u64 };
+    let a35 = x + asm(r: 35) { r: u64 };
+    let a36 = x + asm(r: 36) { r: u64 };
+    let a37 = x + asm(r: 37) { r: u64 };
+    let a38 = x + asm(r: 38) { r: u64 };
+    let a39 = x + asm(r: 39) { r: u64 };
+    let a40 = x + asm(r: 40) { r: u64 };
+    let mut i = 0;
+    let mut sum = 0;
+    while i < 2 {
+        sum = sum
+            + a1
+            + a2
+            + a3
+            + a4
+            + a5
+            + a6
+            + a7
+            + a8
+            + a9
+            + a10
+            + a11
+            + a12
+            + a13
+            + a14
+            + a15
+            + a16
+            + a17
+            + a18
+            + a19
+            + a20
+            + a21
+            + a22
+            + a23
+            + a24
+            + a25
+            + a26
+            + a27
+            + a28
+            + a29
+            + a30
+            + a31
+            + a32
+            + a33
+            + a34
+            + a35
+            + a36
+            + a37
+            + a38
+            + a39
+            + a40;
+        i = i + 1;
+    }
+    sum
+}
+
+// The locals of this function are larger than the reach of the load and store immediates, so its
+// spill slots must be addressed via another register.
+#[inline(never)]
+fn sum_with_large_frame(x: u64, i: u64) -> u64 {
+    let a1 = x + asm(r: 1) { r: u64 };
+    let a2 = x + asm(r: 2) { r: u64 };
+    let a3 = x + asm(r: 3) { r: u64 };
+    let a4 = x + asm(r: 4) { r: u64 };
+    let a5 = x + asm(r: 5) { r: u64 };
+    let a6 = x + asm(r: 6) { r: u64 };
+    let a7 = x + asm(r: 7) { r: u64 };
+    let a8 = x + asm(r: 8) { r: u64 };
+    let a9 = x + asm(r: 9) { r: u64 };
+    let a10 = x + asm(r: 10) { r: u64 };
+    let a11 = x + asm(r: 11) { r: u64 };
+    let a12 = x + asm(r: 12) { r: u64 };
+    let a13 = x + asm(r: 13) { r: u64 };
+    let a14 = x + asm(r: 14) { r: u64 };
+    let a15 = x + asm(r: 15) { r: u64 };
+    let a16 = x + asm(r: 16) { r: u64 };
+    let a17 = x + asm(r: 17) { r: u64 };
+    let a18 = x + asm(r: 18) { r: u64 };
+    let a19 = x + asm(r: 19) { r: u64 };
+    let a20 = x + asm(r: 20) { r: u64 };
+    let a21 = x + asm(r: 21) { r: u64 };
+    let a22 = x + asm(r: 22) { r: u64 };
+    let a23 = x + asm(r: 23) { r: u64 };
+    let a24 = x + asm(r: 24) { r: u64 };
+    let a25 = x + asm(r: 25) { r: u64 };
+    let a26 = x + asm(r: 26) { r: u64 };
+    let a27 = x + asm(r: 27) { r: u64 };
+    let a28 = x + asm(r: 28) { r: u64 };
+    let a29 = x + asm(r: 29) { r: u64 };
+    let a30 = x + asm(r: 30) { r: u64 };
+    let a31 = x + asm(r: 31) { r: u64 };
+    let a32 = x + asm(r: 32) { r: u64 };
+    let a33 = x + asm(r: 33) { r: u64 };
+    let a34 = x + asm(r: 34) { r: u64 };
+    let a35 = x + asm(r: 35) { r: u64 };
+    let a36 = x + asm(r: 36) { r: u64 };
+    let a37 = x + asm(r: 37) { r: u64 };
+    let a38 = x + asm(r: 38) { r: u64 };
+    let a39 = x + asm(r: 39) { r: u64 };
+    let a40 = x + asm(r: 40) { r: u64 };
+    let words = [x, x, x, x, x, x, x, x];
+    let words = [words, words, words, words, words, words, words, words];
+    let words = [words, words, words, words, words, words, words];
+    let words = [words, words, words, words, words, words, words, words, words, words];
+    let mut j = 0;
+    let mut sum = 0;
+    while j < 2 {
+        sum = sum
+            + words[i][i][i][i]
+            + a1
+            + a2
+            + a3
+            + a4
+            + a5
+            + a6
+            + a7
+            + a8
+            + a9
+            + a10
+            + a11
+            + a12
+            + a13
+            + a14
+            + a15
+            + a16
+            + a17
+            + a18
+            + a19
+            + a20
+            + a21
+            + a22
+            + a23
+            + a24
+            + a25
+            + a26
+            + a27
+            + a28
+            + a29
+            + a30
+            + a31
+            + a32
+            + a33
+            + a34
+            + a35
+            + a36
+            + a37
+            + a38
+            + a39
+            + a40;
+        j = j + 1;
+    }
+    sum
+}
+
+// The locals of this function are larger than the reach of the MOVI immediate, so the offsets of
+// its spill slots must be loaded from the data section.
+#[inline(never)]
+fn sum_with_huge_frame(x: u64, i: u64) -> u64 {
+    let a1 = x + asm(r: 1) { r: u64 };
+    let a2 = x + asm(r: 2) { r: u64 };
+    let a3 = x + asm(r: 3) { r: u64 };
+    let a4 = x + asm(r: 4) { r: u64 };
+    let a5 = x + asm(r: 5) { r: u64 };
+    let a6 = x + asm(r: 6) { r: u64 };
+    let a7 = x + asm(r: 7) { r: u64 };
+    let a8 = x + asm(r: 8) { r: u64 };
+    let a9 = x + asm(r: 9) { r: u64 };
+    let a10 = x + asm(r: 10) { r: u64 };
+    let a11 = x + asm(r: 11) { r: u64 };
+    let a12 = x + asm(r: 12) { r: u64 };
+    let a13 = x + asm(r: 13) { r: u64 };
+    let a14 = x + asm(r: 14) { r: u64 };
+    let a15 = x + asm(r: 15) { r: u64 };
+    let a16 = x + asm(r: 16) { r: u64 };
+    let a17 = x + asm(r: 17) { r: u64 };
+    let a18 = x + asm(r: 18) { r: u64 };
+    let a19 = x + asm(r: 19) { r: u64 };
+    let a20 = x + asm(r: 20) { r: u64 };
+    let a21 = x + asm(r: 21) { r: u64 };
+    let a22 = x + asm(r: 22) { r: u64 };
+    let a23 = x + asm(r: 23) { r: u64 };
+    let a24 = x + asm(r: 24) { r: u64 };
+    let a25 = x + asm(r: 25) { r: u64 };
+    let a26 = x + asm(r: 26) { r: u64 };
+    let a27 = x + asm(r: 27) { r: u64 };
+    let a28 = x + asm(r: 28) { r: u64 };
+    let a29 = x + asm(r: 29) { r: u64 };
+    let a30 = x + asm(r: 30) { r: u64 };
+    let a31 = x + asm(r: 31) { r: u64 };
+    let a32 = x + asm(r: 32) { r: u64 };
+    let a33 = x + asm(r: 33) { r: u64 };
+    let a34 = x + asm(r: 34) { r: u64 };
+    let a35 = x + asm(r: 35) { r: u64 };
+    let a36 = x + asm(r: 36) { r: u64 };
+    let a37 = x + asm(r: 37) { r: u64 };
+    let a38 = x + asm(r: 38) { r: u64 };
+    let a39 = x + asm(r: 39) { r: u64 };
+    let a40 = x + asm(r: 40) { r: u64 };
+    let words = [x, x, x, x, x, x, x, x];
+    let words = [words, words, words, words, words, words, words, words];
+    let words = [words, words, words, words, words, words, words];
+    let words = [words, words, words, words, words, words, words, words, words, words];
+    let words = (words, words, words, words, words, words, words, words);
+    let mut j = 0;
+    let mut sum = 0;
+    while j < 2 {
+        sum = sum
+            + words.7[i][i][i][i]
+            + a1
+            + a2
+            + a3
+            + a4
+            + a5
+            + a6
+            + a7
+            + a8
+            + a9
+            + a10
+            + a11
+            + a12
+            + a13
+            + a14
+            + a15
+            + a16
+            + a17
+            + a18
+            + a19
+            + a20
+            + a21
+            + a22
+            + a23
+            + a24
+            + a25
+            + a26
+            + a27
+            + a28
+            + a29
+            + a30
+            + a31
+            + a32
+            + a33
+            + a34
+            + a35
+            + a36
+            + a37
+            + a38
+            + a39
+            + a40;
+        j = j + 1;
+    }
+    sum
+}
+
+fn main() -> u64 {
+    let b1 = asm(r: 1) { r: u64 };
+    let b2 = asm(r: 2) { r: u64 };
+    let b3 = asm(r: 3) { r: u64 };
+    let b4 = asm(r: 4) { r: u64 };
+    let b5 = asm(r: 5) { r: u64 };
+    let b6 = asm(r: 6) { r: u64 };
+    let b7 = asm(r: 7) { r: u64 };
+    let b8 = asm(r: 8) { r: u64 };
+    let b9 = asm(r: 9) { r: u64 };
+    let b10 = asm(r: 10) { r: u64 };
+    let b11 = asm(r: 11) { r: u64 };
+    let b12 = asm(r: 12) { r: u64 };
+    let b13 = asm(r: 13) { r: u64 };
+    let b14 = asm(r: 14) { r: u64 };
+    let b15 = asm(r: 15) { r: u64 };
+    let b16 = asm(r: 16) { r: u64 };
+    let b17 = asm(r: 17) { r: u64 };
+    let b18 = asm(r: 18) { r: u64 };
+    let b19 = asm(r: 19) { r: u64 };
+    let b20 = asm(r: 20) { r: u64 };
+    let b21 = asm(r: 21) { r: u64 };
+    let b22 = asm(r: 22) { r: u64 };
+    let b23 = asm(r: 23) { r: u64 };
+    let b24 = asm(r: 24) { r: u64 };
+    let b25 = asm(r: 25) { r: u64 };
+    let b26 = asm(r: 26) { r: u64 };
+    let b27 = asm(r: 27) { r: u64 };
+    let b28 = asm(r: 28) { r: u64 };
+    let b29 = asm(r: 29) { r: u64 };
+    let b30 = asm(r: 30) { r: u64 };
+    let b31 = asm(r: 31) { r: u64 };
+    let b32 = asm(r: 32) { r: u64 };
+    let b33 = asm(r: 33) { r: u64 };
+    let b34 = asm(r: 34) { r: u64 };
+    let b35 = asm(r: 35) { r: u64 };
+    let b36 = asm(r: 36) { r: u64 };
+    let b37 = asm(r: 37) { r: u64 };
+    let b38 = asm(r: 38) { r: u64 };
+    let b39 = asm(r: 39) { r: u64 };
+    let b40 = asm(r: 40) { r: u64 };
+    let c = sum_in_loop(1)
+        + sum_in_loop(2)
+        + sum_with_large_frame(3, 5)
+        + sum_with_huge_frame(4, 5);
+    c
+        + b1
+        + b2
+        + b3
+        + b4
+        + b5
+        + b6
+        + b7
+        + b8
+        + b9
+        + b10
+        + b11
+        + b12
+        + b13
+        + b14
+        + b15
+        + b16
+        + b17
+        + b18
+        + b19
+        + b20
+        + b21
+        + b22
+        + b23
+        + b24
+        + b25
+        + b26
+        + b27
+        + b28
+        + b29
+        + b30
+        + b31
+        + b32
+        + b33
+        + b34
+        + b35
+        + b36
+        + b37
+        + b38
+        + b39
+        + b40
+}
@@ -0,0 +1,25 @@
+contract;
+
+abi Counter {
+    #[storage(read)]
+    fn get() -> u64;
+    #[storage(read, write)]
+    fn increment() -> u64;
+}
+
+storage {
+    count: u64 = 10,
+}
+
+impl Counter for Contract {
+    #[storage(read)]
+    fn get() -> u64 {
+        storage.count
+    }
+
+    #[storage(read, write)]
+    fn increment() -> u64 {
+        storage.count += 1;
+        storage.count
+    }
+}
@@ -0,0 +1,39 @@
+contract;
+
+abi Counter {
+    #[storage(read)]
+    fn get() -> u64;
+    #[storage(read, write)]
+    fn increment() -> u64;
+}
+
+abi Caller {
+    fn counter() -> u64;
+    fn increment_counter() -> u64;
+}
+
+impl Caller for Contract {
+    fn counter() -> u64 {
+        let counter = abi(Counter, counter::CONTRACT_ID);
+        counter.get()
+    }
+
+    fn increment_counter() -> u64 {
+        let counter = abi(Counter, counter::CONTRACT_ID);
+        counter.increment()
+    }
+}
+
+#[test]
+fn test_dependency_storage_is_initialized() {
+    let caller = abi(Caller, CONTRACT_ID);
+    assert(caller.counter() == 10);
+}
+
+#[test]
+fn test_call_dependency() {
+    let caller = abi(Caller, CONTRACT_ID);
+    assert(caller.increment_counter() == 11);
+    assert(caller.increment_counter() == 12);
+    assert(caller.counter() == 12);
+}
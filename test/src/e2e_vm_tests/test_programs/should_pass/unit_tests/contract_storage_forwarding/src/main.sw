@@ -0,0 +1,133 @@
+contract;
+
+// The storage accesses below are forwarded and merged by the `storage_forward` optimization pass,
+// the tests check they still see the same values as the unoptimized accesses would.
+
+struct Fields {
+    a: u64,
+    b: u64,
+    c: u64,
+    d: u64,
+}
+
+storage {
+    fields: Fields = Fields {
+        a: 1,
+        b: 2,
+        c: 3,
+        d: 4,
+    },
+}
+
+// Consecutive keys, whose word accesses are merged into quad word accesses.  They aren't set by
+// the storage declaration.
+const KEY_0: b256 = 0x0000000000000000000000000000000000000000000000000000000000001000;
+const KEY_1: b256 = 0x0000000000000000000000000000000000000000000000000000000000001001;
+
+abi StorageForwarding {
+    #[storage(read)]
+    fn sum_fields() -> u64;
+    #[storage(read, write)]
+    fn write_then_read_fields(b: u64, c: u64) -> (u64, u64, u64, u64);
+    #[storage(read, write)]
+    fn write_then_read_across_call(a: u64) -> (u64, u64);
+    #[storage(read)]
+    fn read_words() -> (u64, u64);
+    #[storage(write)]
+    fn write_words(w0: u64, w1: u64);
+    #[storage(read)]
+    fn read_slots() -> (b256, b256);
+}
+
+#[storage(read)]
+#[inline(never)]
+fn read_d() -> u64 {
+    storage.fields.d
+}
+
+impl StorageForwarding for Contract {
+    #[storage(read)]
+    fn sum_fields() -> u64 {
+        storage.fields.a + storage.fields.b + storage.fields.c + storage.fields.d + storage.fields.a
+    }
+
+    #[storage(read, write)]
+    fn write_then_read_fields(b: u64, c: u64) -> (u64, u64, u64, u64) {
+        storage.fields.b = b;
+        storage.fields.c = c;
+        (
+            storage.fields.a,
+            storage.fields.b,
+            storage.fields.c,
+            storage.fields.d,
+        )
+    }
+
+    #[storage(read, write)]
+    fn write_then_read_across_call(a: u64) -> (u64, u64) {
+        storage.fields.a = a;
+        let d = read_d();
+        (storage.fields.a, d)
+    }
+
+    #[storage(read)]
+    fn read_words() -> (u64, u64) {
+        (__state_load_word(KEY_0), __state_load_word(KEY_1))
+    }
+
+    #[storage(write)]
+    fn write_words(w0: u64, w1: u64) {
+        let _ = __state_store_word(KEY_0, w0);
+        let _ = __state_store_word(KEY_1, w1);
+    }
+
+    #[storage(read)]
+    fn read_slots() -> (b256, b256) {
+        let slot_0 = 0x0000000000000000000000000000000000000000000000000000000000000000;
+        let slot_1 = 0x0000000000000000000000000000000000000000000000000000000000000000;
+        let _ = __state_load_quad(KEY_0, __addr_of(slot_0), 1);
+        let _ = __state_load_quad(KEY_1, __addr_of(slot_1), 1);
+        (slot_0, slot_1)
+    }
+}
+
+#[test]
+fn test_sum_fields() {
+    let caller = abi(StorageForwarding, CONTRACT_ID);
+    assert(caller.sum_fields() == 11);
+}
+
+#[test]
+fn test_write_then_read_fields() {
+    let caller = abi(StorageForwarding, CONTRACT_ID);
+    let (a, b, c, d) = caller.write_then_read_fields(20, 30);
+    assert(a == 1 && b == 20 && c == 30 && d == 4);
+    assert(caller.sum_fields() == 56);
+}
+
+#[test]
+fn test_write_then_read_across_call() {
+    let caller = abi(StorageForwarding, CONTRACT_ID);
+    let (a, d) = caller.write_then_read_across_call(10);
+    assert(a == 10 && d == 4);
+    assert(caller.sum_fields() == 29);
+}
+
+#[test]
+fn test_read_unset_words() {
+    let caller = abi(StorageForwarding, CONTRACT_ID);
+    let (w0, w1) = caller.read_words();
+    assert(w0 == 0 && w1 == 0);
+}
+
+#[test]
+fn test_write_then_read_words() {
+    let caller = abi(StorageForwarding, CONTRACT_ID);
+    caller.write_words(7, 8);
+    let (w0, w1) = caller.read_words();
+    assert(w0 == 7 && w1 == 8);
+
+    let (slot_0, slot_1) = caller.read_slots();
+    assert(slot_0 == 0x0000000000000007000000000000000000000000000000000000000000000000);
+    assert(slot_1 == 0x0000000000000008000000000000000000000000000000000000000000000000);
+}
@@ -0,0 +1,49 @@
+library fuzz_tests;
+
+struct Point {
+  x: u64,
+  y: u64,
+}
+
+enum Direction {
+  Left: (),
+  Right: u64,
+}
+
+#[test(fuzz)]
+fn fuzz_add_is_commutative(a: u32, b: u32) {
+  let (a, b): (u64, u64) = (a, b);
+  assert(a + b == b + a)
+}
+
+#[test(fuzz)]
+fn fuzz_bool(flag: bool) {
+  assert(flag == true || flag == false)
+}
+
+#[test(fuzz)]
+fn fuzz_struct(point: Point) {
+  let point = Point { x: point.x / 2, y: point.y / 2 };
+  assert(point.x <= 9223372036854775807 && point.y <= 9223372036854775807)
+}
+
+#[test(fuzz)]
+fn fuzz_b256_and_array(hash: b256, values: [u8; 3]) {
+  assert(hash == hash);
+  assert(values[0] <= 255 && values[1] <= 255 && values[2] <= 255)
+}
+
+#[test(fuzz)]
+fn fuzz_enum(direction: Direction) {
+  let distance = match direction {
+    Direction::Left => 0,
+    Direction::Right(distance) => distance,
+  };
+  assert(distance >= 0)
+}
+
+#[test(fuzz, should_revert)]
+fn fuzz_overflow(a: u64) {
+  let b = a + 18446744073709551615;
+  assert(b == 0)
+}
@@ -30,3 +30,8 @@ fn test_lt() {
 fn test_local() {
     assert(pow2(4) == 16)
 }
+
+#[test(ignore)]
+fn test_ignored() {
+    assert(false);
+}
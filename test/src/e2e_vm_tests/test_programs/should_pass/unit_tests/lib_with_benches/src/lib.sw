@@ -0,0 +1,27 @@
+library lib_with_benches;
+
+fn sum(n: u64) -> u64 {
+    let mut i = 0;
+    let mut total = 0;
+    while i < n {
+        total += i;
+        i += 1;
+    }
+    total
+}
+
+#[test]
+fn test_sum() {
+    assert(sum(4) == 6);
+}
+
+#[bench]
+fn bench_sum() {
+    let _ = sum(100);
+}
+
+// Benchmarks are not executed as tests, so this does not cause the tests to fail.
+#[bench]
+fn bench_revert() {
+    revert(0);
+}
@@ -0,0 +1,106 @@
+contract;
+
+// These tests are built with a non-default optimization level, checking that the code generated
+// by its pass pipeline behaves the same as that of the default level.
+
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+enum Shape {
+    Dot: Point,
+    Line: (Point, Point),
+}
+
+storage {
+    total: u64 = 0,
+    origin: Point = Point { x: 1, y: 2 },
+}
+
+abi OptLevel {
+    #[storage(read, write)]
+    fn add_to_total(n: u64) -> u64;
+    #[storage(read, write)]
+    fn move_origin(dx: u64, dy: u64) -> Point;
+}
+
+impl OptLevel for Contract {
+    #[storage(read, write)]
+    fn add_to_total(n: u64) -> u64 {
+        let mut i = 0;
+        while i < n {
+            storage.total = storage.total + i;
+            i = i + 1;
+        }
+        storage.total
+    }
+
+    #[storage(read, write)]
+    fn move_origin(dx: u64, dy: u64) -> Point {
+        let origin = storage.origin;
+        storage.origin = Point {
+            x: origin.x + dx,
+            y: origin.y + dy,
+        };
+        storage.origin
+    }
+}
+
+fn sum_to(n: u64) -> u64 {
+    let mut sum = 0;
+    let mut i = 1;
+    while i <= n {
+        sum = sum + i;
+        i = i + 1;
+    }
+    sum
+}
+
+fn length(shape: Shape) -> u64 {
+    match shape {
+        Shape::Dot(_) => 0,
+        Shape::Line((a, b)) => {
+            let dx = if a.x > b.x { a.x - b.x } else { b.x - a.x };
+            let dy = if a.y > b.y { a.y - b.y } else { b.y - a.y };
+            dx + dy
+        }
+    }
+}
+
+fn first<T>(pair: (T, T)) -> T {
+    pair.0
+}
+
+#[test]
+fn test_loops() {
+    assert(sum_to(0) == 0);
+    assert(sum_to(3) == 6);
+    assert(sum_to(100) == 5050);
+}
+
+#[test]
+fn test_aggregates() {
+    let mut points = [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }, Point { x: 5, y: 6 }];
+    points[1] = Point { x: 10, y: 4 };
+    let mut sum = 0;
+    let mut i = 0;
+    while i < 3 {
+        sum = sum + points[i].x * points[i].y;
+        i = i + 1;
+    }
+    assert(sum == 72);
+    assert(length(Shape::Dot(points[0])) == 0);
+    assert(length(Shape::Line((points[0], points[2]))) == 8);
+    assert(first((7, 8)) == 7);
+    assert(first((true, false)));
+}
+
+#[test]
+fn test_storage() {
+    let caller = abi(OptLevel, CONTRACT_ID);
+    assert(caller.add_to_total(4) == 6);
+    assert(caller.add_to_total(3) == 9);
+    let origin = caller.move_origin(10, 20);
+    assert(origin.x == 11 && origin.y == 22);
+}
@@ -0,0 +1,41 @@
+predicate;
+
+use std::outputs::output_count;
+
+struct Spend {
+    secret: u64,
+    expected_outputs: u64,
+}
+
+fn main(spend: Spend) -> bool {
+    spend.secret == 42 && output_count() == spend.expected_outputs
+}
+
+#[test(should_accept)]
+fn accepts_secret() -> Spend {
+    Spend {
+        secret: 42,
+        expected_outputs: 1,
+    }
+}
+
+#[test(should_reject)]
+fn rejects_wrong_secret() -> Spend {
+    Spend {
+        secret: 7,
+        expected_outputs: 1,
+    }
+}
+
+#[test(should_reject)]
+fn rejects_unexpected_outputs() -> Spend {
+    Spend {
+        secret: 42,
+        expected_outputs: 2,
+    }
+}
+
+#[test]
+fn test_plain() {
+    assert(1 + 1 == 2);
+}
@@ -4,3 +4,23 @@ library should_revert;
 fn should_revert_test() {
   assert(0 == 1)
 }
+
+#[test(should_revert = "18446744073709486084")]
+fn should_revert_with_code_test() {
+  assert(0 == 1)
+}
+
+#[test(should_revert = "0xffffffffffff0000")]
+fn should_revert_with_hex_code_test() {
+  require(0 == 1, ())
+}
+
+#[test(should_revert = "FAILED_ASSERT_SIGNAL")]
+fn should_revert_with_signal_name_test() {
+  assert(0 == 1)
+}
+
+#[test(should_revert = 42)]
+fn should_revert_with_int_code_test() {
+  revert(42)
+}
@@ -0,0 +1,93 @@
+contract;
+
+use std::{
+    auth::msg_sender,
+    block::height,
+    call_frames::msg_asset_id,
+    constants::BASE_ASSET_ID,
+    context::msg_amount,
+};
+
+abi Env {
+    fn height() -> u64;
+    fn sender() -> Address;
+    #[payable]
+    fn paid() -> (u64, ContractId);
+}
+
+impl Env for Contract {
+    fn height() -> u64 {
+        height()
+    }
+
+    fn sender() -> Address {
+        match msg_sender().unwrap() {
+            Identity::Address(addr) => addr,
+            _ => revert(0),
+        }
+    }
+
+    #[payable]
+    fn paid() -> (u64, ContractId) {
+        (msg_amount(), msg_asset_id())
+    }
+}
+
+#[test]
+fn test_block_height() {
+    let env = abi(Env, CONTRACT_ID);
+    assert(env.height() == 100);
+    assert(height() == 100);
+}
+
+#[test(block_height = 7)]
+fn test_block_height_attribute() {
+    let env = abi(Env, CONTRACT_ID);
+    assert(env.height() == 7);
+}
+
+#[test]
+fn test_caller() {
+    let env = abi(Env, CONTRACT_ID);
+    let caller = Address::from(0x0101010101010101010101010101010101010101010101010101010101010101);
+    assert(env.sender() == caller);
+}
+
+#[test(caller = "0x0202020202020202020202020202020202020202020202020202020202020202")]
+fn test_caller_attribute() {
+    let env = abi(Env, CONTRACT_ID);
+    let caller = Address::from(0x0202020202020202020202020202020202020202020202020202020202020202);
+    assert(env.sender() == caller);
+}
+
+#[test]
+fn test_forwarded_coins() {
+    let env = abi(Env, CONTRACT_ID);
+    let (amount, asset_id) = env.paid {
+        coins: 200,
+        asset_id: BASE_ASSET_ID.into(),
+    }();
+    assert(amount == 200);
+    assert(asset_id == BASE_ASSET_ID);
+}
+
+#[test(coins = 50, asset_id = "0x0303030303030303030303030303030303030303030303030303030303030303")]
+fn test_forwarded_coins_attribute() {
+    let env = abi(Env, CONTRACT_ID);
+    let asset = ContractId::from(0x0303030303030303030303030303030303030303030303030303030303030303);
+    let (amount, asset_id) = env.paid {
+        coins: 50,
+        asset_id: asset.into(),
+    }();
+    assert(amount == 50);
+    assert(asset_id == asset);
+}
+
+#[test(should_revert)]
+fn test_forwarded_coins_exceed_inputs() {
+    let env = abi(Env, CONTRACT_ID);
+    let _ = env.paid {
+        coins: 501,
+        asset_id: BASE_ASSET_ID.into(),
+    }();
+}
@@ -21,9 +21,9 @@ fn main() -> (u64, u64, u64, u64) {
 // regex: DATA=data_\d+
 // regex: REG=\$r\d+
 
-// There should only be 3 data entries here. One shared by `A` and `B` and the
-// other two are for `X` and `Y` respectively
+// There should only be 2 data entries here, for `X` and `Y` respectively. The
+// entry shared by `A` and `B` is removed as they are loaded immediately.
 
 // check: $(d1=$DATA) .word 42
 // check: $(d2=$DATA) .word 42
-// check: $(d3=$DATA) .word 42
+// not: .word 42
@@ -48,9 +48,9 @@ fn main() -> u64 {
 //
 // Matching fn b() here, which has a local bool var, initialised to false/$zero:
 //
-// check: move $(stack_ptr=$REG) $$sp
+// check: move $$$$locbase $$sp
 // check: cfei i8
-// check: sw   $stack_ptr $$zero i0
+// check: sw   $$$$locbase $$zero i0
 // ...
 // check: cfsi i8
 // check: jmp $$$$reta
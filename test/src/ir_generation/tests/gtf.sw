@@ -30,8 +30,6 @@ fn main() {
 
 // check: gtf  $REG $$one i66
 
-// check: lw   $(two=$REG) data_0
+// check: movi $(two=$REG) i2
 // check: gtf  $(b256_ptr=$REG) $two i119
 // check: mcpi $REG $b256_ptr i32
-
-// check: data_0 .word 2
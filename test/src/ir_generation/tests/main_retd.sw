@@ -32,14 +32,10 @@ fn main() -> D {
 // ::check-asm::
 
 // regex: REG=\$r\d+
-// regex: ID=[_[:alpha:]][_0-9[:alpha:]]*
 
 // B is 48 bytes.
 // check: mcpi $REG $REG i48
 
 // D is 72 bytes.
-// check: lw   $(len_reg=$REG) $(len_data=$ID)
+// check: movi $(len_reg=$REG) i72
 // check: retd  $REG $len_reg
-
-// check: .data:
-// check: $len_data .word 72
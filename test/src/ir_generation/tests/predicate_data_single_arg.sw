@@ -17,10 +17,10 @@ fn main(x: u64) -> bool {
 // nextln: jnzi $r1 $IMM
 // nextln: gtf  $(r3=$REG) $r2 i269
 // nextln: ji   $IMM
-// nextln: movi $(r0=$REG) i2
-// nextln: eq   $r0 $r1 $r0
-// nextln: xori $r0 $r0 i1
-// nextln: jnzi $r0 $IMM
+// nextln: movi $(two=$REG) i2
+// nextln: eq   $(is_msg=$REG) $r1 $two
+// nextln: xori $is_msg $is_msg i1
+// nextln: jnzi $is_msg $IMM
 // nextln: gtf  $r3 $r2 i287
 // nextln: ji   $IMM
 // nextln: ret  $$zero
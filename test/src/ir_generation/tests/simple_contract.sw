@@ -42,19 +42,19 @@ impl Test for Contract {
 // Get the called selector.
 // check: lw   $(sel_reg=$REG) $$fp i73
 
-// Check selector at data_2 2559618804 (0x9890aef4)
-// check: lw   $(data_2_reg=$REG) data_2
-// check: eq   $(eq_reg=$REG) $sel_reg $data_2_reg
+// Check selector at data_0 1108491158 (0x42123b96)
+// check: lw   $(data_0_reg=$REG) data_0
+// check: eq   $(eq_reg=$REG) $sel_reg $data_0_reg
 // check: jnzi $eq_reg
 
-// Check selector at data_3 1108491158 (0x42123b96)
-// check: lw   $(data_3_reg=$REG) data_3
-// check: eq   $(eq_reg=$REG) $sel_reg $data_3_reg
+// Check selector at data_1 4234334249 (0xfc62d029)
+// check: lw   $(data_1_reg=$REG) data_1
+// check: eq   $(eq_reg=$REG) $sel_reg $data_1_reg
 // check: jnzi $eq_reg
 
-// Check selector at data_4 4234334249 (0xfc62d029)
-// check: lw   $(data_4_reg=$REG) data_4
-// check: eq   $(eq_reg=$REG) $sel_reg $data_4_reg
+// Check selector at data_2 2559618804 (0x9890aef4)
+// check: lw   $(data_2_reg=$REG) data_2
+// check: eq   $(eq_reg=$REG) $sel_reg $data_2_reg
 // check: jnzi $eq_reg
 
 // Revert on no match.
@@ -64,7 +64,10 @@ impl Test for Contract {
 // Each function will read from $fp for the args.
 // check: lw   $REG $$fp i74
 
+// The sizes of the returned values are small enough to be loaded immediately, so only the
+// selectors remain in the data section.
 // check: .data:
-// check: data_2 .word 1108491158
-// check: data_3 .word 4234334249
-// check: data_4 .word 2559618804
+// check: data_0 .word 1108491158
+// check: data_1 .word 4234334249
+// check: data_2 .word 2559618804
+// not: data_3